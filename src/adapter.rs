@@ -0,0 +1,489 @@
+//! Adapters between blocking and async implementations
+//!
+//! [`AsyncBlocking`] implements the blocking traits on top of an async
+//! implementation, so blocking-only drivers can be used with async-first HALs. The
+//! futures are driven to completion by a [`BlockOn`] hook, which lets the
+//! application decide how to wait, e.g. by spinning or by sleeping until an interrupt.
+//!
+//! [`BlockingAsync`] goes the other way: it implements the async traits on top of
+//! a blocking implementation. Its futures complete the first time they are polled,
+//! blocking the executor for the duration of the transfer.
+//!
+//! Both adapters support the I2C traits and the SPI bus and device traits, except for the
+//! closure-based `SpiDeviceBus`.
+//!
+//! Only available with the `async` feature.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::adapter::{AsyncBlocking, Spin};
+//! use embedded_hal::i2c::{asynch, blocking::I2c};
+//!
+//! fn read_id<I2C: asynch::I2c>(i2c: I2C) -> Result<u8, I2C::Error> {
+//!     let mut i2c = AsyncBlocking::new(i2c, Spin);
+//!     let mut id = [0];
+//!     i2c.write_read(0x76, &[0xD0], &mut id)?;
+//!     Ok(id[0])
+//! }
+//! ```
+
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+
+use crate::i2c::{self, AddressMode};
+use crate::spi::{self, Word};
+
+/// Runs a future to completion
+pub trait BlockOn {
+    /// Blocks the caller until `future` completes and returns its output
+    fn block_on<F: Future>(&mut self, future: F) -> F::Output;
+}
+
+impl<T: BlockOn> BlockOn for &mut T {
+    fn block_on<F: Future>(&mut self, future: F) -> F::Output {
+        T::block_on(self, future)
+    }
+}
+
+/// [`BlockOn`] implementation which polls the future in a busy loop
+///
+/// Wakeups are ignored, so this works with any async implementation, but keeps the
+/// core busy while waiting.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Spin;
+
+impl BlockOn for Spin {
+    fn block_on<F: Future>(&mut self, future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+}
+
+/// Blocking implementation on top of an async implementation
+#[derive(Debug)]
+pub struct AsyncBlocking<T, B> {
+    inner: T,
+    block_on: B,
+}
+
+impl<T, B: BlockOn> AsyncBlocking<T, B> {
+    /// Creates a new adapter which waits for `inner` with `block_on`
+    pub fn new(inner: T, block_on: B) -> Self {
+        AsyncBlocking { inner, block_on }
+    }
+
+    /// Releases the async implementation and the `block_on` hook
+    pub fn release(self) -> (T, B) {
+        (self.inner, self.block_on)
+    }
+}
+
+impl<A, T, B> i2c::blocking::I2c<A> for AsyncBlocking<T, B>
+where
+    A: AddressMode,
+    T: i2c::asynch::I2c<A>,
+    B: BlockOn,
+{
+    type Error = T::Error;
+
+    fn transaction<'a>(
+        &mut self,
+        address: A,
+        operations: &mut [i2c::blocking::Operation<'a>],
+    ) -> Result<(), Self::Error> {
+        self.block_on
+            .block_on(self.inner.transaction(address, operations))
+    }
+
+    fn read(&mut self, address: A, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.block_on.block_on(self.inner.read(address, buffer))
+    }
+
+    fn write(&mut self, address: A, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.block_on.block_on(self.inner.write(address, bytes))
+    }
+
+    fn write_read(
+        &mut self,
+        address: A,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.block_on
+            .block_on(self.inner.write_read(address, bytes, buffer))
+    }
+}
+
+impl<T: spi::asynch::SpiBusFlush, B: BlockOn> spi::blocking::SpiBusFlush for AsyncBlocking<T, B> {
+    type Error = T::Error;
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.block_on.block_on(self.inner.flush())
+    }
+}
+
+impl<T, B, W> spi::blocking::SpiBusRead<W> for AsyncBlocking<T, B>
+where
+    T: spi::asynch::SpiBusRead<W>,
+    B: BlockOn,
+    W: Word,
+{
+    fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        self.block_on
+            .block_on(spi::asynch::SpiBusRead::read(&mut self.inner, words))
+    }
+}
+
+impl<T, B, W> spi::blocking::SpiBusWrite<W> for AsyncBlocking<T, B>
+where
+    T: spi::asynch::SpiBusWrite<W>,
+    B: BlockOn,
+    W: Word,
+{
+    fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        self.block_on
+            .block_on(spi::asynch::SpiBusWrite::write(&mut self.inner, words))
+    }
+}
+
+impl<T, B, W> spi::blocking::SpiBus<W> for AsyncBlocking<T, B>
+where
+    T: spi::asynch::SpiBus<W>,
+    B: BlockOn,
+    W: Word,
+{
+    fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+        self.block_on
+            .block_on(spi::asynch::SpiBus::transfer(&mut self.inner, read, write))
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        self.block_on
+            .block_on(spi::asynch::SpiBus::transfer_in_place(
+                &mut self.inner,
+                words,
+            ))
+    }
+}
+
+impl<T: spi::asynch::HalfDuplex, B: BlockOn> spi::blocking::HalfDuplex for AsyncBlocking<T, B> {}
+
+impl<T, B, W> spi::blocking::SpiDevice<W> for AsyncBlocking<T, B>
+where
+    T: spi::asynch::SpiDevice<W>,
+    B: BlockOn,
+    W: Word,
+{
+    type Error = T::Error;
+
+    fn transaction<'a>(
+        &mut self,
+        operations: &mut [spi::blocking::Operation<'a, W>],
+    ) -> Result<(), Self::Error> {
+        self.block_on.block_on(spi::asynch::SpiDevice::transaction(
+            &mut self.inner,
+            operations,
+        ))
+    }
+
+    fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        self.block_on
+            .block_on(spi::asynch::SpiDevice::read(&mut self.inner, words))
+    }
+
+    fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        self.block_on
+            .block_on(spi::asynch::SpiDevice::write(&mut self.inner, words))
+    }
+
+    fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+        self.block_on.block_on(spi::asynch::SpiDevice::transfer(
+            &mut self.inner,
+            read,
+            write,
+        ))
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        self.block_on
+            .block_on(spi::asynch::SpiDevice::transfer_in_place(
+                &mut self.inner,
+                words,
+            ))
+    }
+}
+
+impl<T, B, W> spi::blocking::SpiDeviceHalfDuplex<W> for AsyncBlocking<T, B>
+where
+    T: spi::asynch::SpiDeviceHalfDuplex<W>,
+    B: BlockOn,
+    W: Word,
+{
+    type Error = T::Error;
+
+    fn write_then_read(&mut self, write: &[W], read: &mut [W]) -> Result<(), Self::Error> {
+        self.block_on
+            .block_on(self.inner.write_then_read(write, read))
+    }
+
+    fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        self.block_on
+            .block_on(spi::asynch::SpiDeviceHalfDuplex::write(
+                &mut self.inner,
+                words,
+            ))
+    }
+
+    fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        self.block_on
+            .block_on(spi::asynch::SpiDeviceHalfDuplex::read(
+                &mut self.inner,
+                words,
+            ))
+    }
+}
+
+/// Async implementation on top of a blocking implementation
+#[derive(Debug)]
+pub struct BlockingAsync<T> {
+    inner: T,
+}
+
+impl<T> BlockingAsync<T> {
+    /// Creates a new adapter
+    pub fn new(inner: T) -> Self {
+        BlockingAsync { inner }
+    }
+
+    /// Releases the blocking implementation
+    pub fn release(self) -> T {
+        self.inner
+    }
+}
+
+impl<A, T> i2c::asynch::I2c<A> for BlockingAsync<T>
+where
+    A: AddressMode,
+    T: i2c::blocking::I2c<A>,
+{
+    type Error = T::Error;
+
+    async fn transaction<'a>(
+        &mut self,
+        address: A,
+        operations: &mut [i2c::blocking::Operation<'a>],
+    ) -> Result<(), Self::Error> {
+        self.inner.transaction(address, operations)
+    }
+
+    async fn read(&mut self, address: A, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.inner.read(address, buffer)
+    }
+
+    async fn write(&mut self, address: A, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.inner.write(address, bytes)
+    }
+
+    async fn write_read(
+        &mut self,
+        address: A,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.inner.write_read(address, bytes, buffer)
+    }
+}
+
+impl<T: spi::blocking::SpiBusFlush> spi::asynch::SpiBusFlush for BlockingAsync<T> {
+    type Error = T::Error;
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+}
+
+impl<T: spi::blocking::SpiBusRead<W>, W: Word> spi::asynch::SpiBusRead<W> for BlockingAsync<T> {
+    async fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        spi::blocking::SpiBusRead::read(&mut self.inner, words)
+    }
+}
+
+impl<T: spi::blocking::SpiBusWrite<W>, W: Word> spi::asynch::SpiBusWrite<W> for BlockingAsync<T> {
+    async fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        spi::blocking::SpiBusWrite::write(&mut self.inner, words)
+    }
+}
+
+impl<T: spi::blocking::SpiBus<W>, W: Word> spi::asynch::SpiBus<W> for BlockingAsync<T> {
+    async fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+        spi::blocking::SpiBus::transfer(&mut self.inner, read, write)
+    }
+
+    async fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        spi::blocking::SpiBus::transfer_in_place(&mut self.inner, words)
+    }
+}
+
+impl<T: spi::blocking::HalfDuplex> spi::asynch::HalfDuplex for BlockingAsync<T> {}
+
+impl<T: spi::blocking::SpiDevice<W>, W: Word> spi::asynch::SpiDevice<W> for BlockingAsync<T> {
+    type Error = T::Error;
+
+    async fn transaction<'a>(
+        &mut self,
+        operations: &mut [spi::blocking::Operation<'a, W>],
+    ) -> Result<(), Self::Error> {
+        spi::blocking::SpiDevice::transaction(&mut self.inner, operations)
+    }
+
+    async fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        spi::blocking::SpiDevice::read(&mut self.inner, words)
+    }
+
+    async fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        spi::blocking::SpiDevice::write(&mut self.inner, words)
+    }
+
+    async fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+        spi::blocking::SpiDevice::transfer(&mut self.inner, read, write)
+    }
+
+    async fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        spi::blocking::SpiDevice::transfer_in_place(&mut self.inner, words)
+    }
+}
+
+impl<T, W> spi::asynch::SpiDeviceHalfDuplex<W> for BlockingAsync<T>
+where
+    T: spi::blocking::SpiDeviceHalfDuplex<W>,
+    W: Word,
+{
+    type Error = T::Error;
+
+    async fn write_then_read(&mut self, write: &[W], read: &mut [W]) -> Result<(), Self::Error> {
+        self.inner.write_then_read(write, read)
+    }
+
+    async fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        spi::blocking::SpiDeviceHalfDuplex::write(&mut self.inner, words)
+    }
+
+    async fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        spi::blocking::SpiDeviceHalfDuplex::read(&mut self.inner, words)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c::asynch::Operation;
+    use crate::i2c::blocking::I2c as _;
+    use crate::i2c::ErrorKind;
+
+    /// Future which is pending the first time it is polled
+    struct YieldNow(bool);
+
+    impl Future for YieldNow {
+        type Output = ();
+
+        fn poll(mut self: core::pin::Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                Poll::Ready(())
+            } else {
+                self.0 = true;
+                Poll::Pending
+            }
+        }
+    }
+
+    /// Async device which answers every read with its address
+    struct Echo;
+
+    impl i2c::asynch::I2c for Echo {
+        type Error = ErrorKind;
+
+        async fn transaction<'a>(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'a>],
+        ) -> Result<(), Self::Error> {
+            YieldNow(false).await;
+            for operation in operations {
+                if let Operation::Read(buffer) = operation {
+                    buffer.fill(address);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Counts the futures it drives
+    struct Counting(usize);
+
+    impl BlockOn for Counting {
+        fn block_on<F: Future>(&mut self, future: F) -> F::Output {
+            self.0 += 1;
+            Spin.block_on(future)
+        }
+    }
+
+    #[test]
+    fn drives_futures_with_hook() {
+        let mut i2c = AsyncBlocking::new(Echo, Counting(0));
+        let mut buffer = [0; 2];
+        i2c.write_read(0x42, &[1], &mut buffer).unwrap();
+        assert_eq!(buffer, [0x42; 2]);
+        assert!(i2c.probe(0x42).unwrap());
+        assert_eq!(i2c.release().1 .0, 2);
+    }
+
+    /// Async SPI device echoing the written words, inverted
+    struct Invert;
+
+    impl spi::asynch::SpiDevice for Invert {
+        type Error = spi::ErrorKind;
+
+        async fn transaction<'a>(
+            &mut self,
+            operations: &mut [spi::blocking::Operation<'a>],
+        ) -> Result<(), Self::Error> {
+            YieldNow(false).await;
+            for operation in operations {
+                if let spi::blocking::Operation::TransferInPlace(words) = operation {
+                    for word in words.iter_mut() {
+                        *word = !*word;
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn spi_round_trip_through_both_adapters() {
+        use spi::blocking::SpiDevice as _;
+
+        let spi = AsyncBlocking::new(Invert, Spin);
+        let mut spi = AsyncBlocking::new(BlockingAsync::new(spi), Counting(0));
+        let mut words = [0x0F, 0xF0];
+        spi.transfer_in_place(&mut words).unwrap();
+        assert_eq!(words, [0xF0, 0x0F]);
+        assert_eq!(spi.release().1 .0, 1);
+    }
+
+    #[test]
+    fn round_trip_through_both_adapters() {
+        let i2c = AsyncBlocking::new(Echo, Spin);
+        let mut i2c = AsyncBlocking::new(BlockingAsync::new(i2c), Spin);
+        let mut buffer = [0; 3];
+        i2c.read(0x17, &mut buffer).unwrap();
+        assert_eq!(buffer, [0x17; 3]);
+    }
+}
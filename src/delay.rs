@@ -41,3 +41,63 @@ pub mod blocking {
         }
     }
 }
+
+/// Async delay traits
+///
+/// Only available with the `async` feature.
+#[cfg(feature = "async")]
+pub mod asynch {
+    /// Microsecond delay
+    ///
+    /// Unlike [`blocking::DelayUs`](super::blocking::DelayUs), the returned future lets the
+    /// executor run other tasks while waiting.
+    pub trait DelayUs {
+        /// Enumeration of `DelayUs` errors
+        type Error: core::fmt::Debug;
+
+        /// Pauses execution for at minimum `us` microseconds. Pause can be longer
+        /// if the implementation requires it due to precision/timing issues.
+        async fn delay_us(&mut self, us: u32) -> Result<(), Self::Error>;
+
+        /// Pauses execution for at minimum `ms` milliseconds. Pause can be longer
+        /// if the implementation requires it due to precision/timing issues.
+        async fn delay_ms(&mut self, ms: u32) -> Result<(), Self::Error> {
+            for _ in 0..ms {
+                self.delay_us(1000).await?;
+            }
+
+            Ok(())
+        }
+    }
+
+    impl<T> DelayUs for &mut T
+    where
+        T: DelayUs,
+    {
+        type Error = T::Error;
+
+        async fn delay_us(&mut self, us: u32) -> Result<(), Self::Error> {
+            T::delay_us(self, us).await
+        }
+
+        async fn delay_ms(&mut self, ms: u32) -> Result<(), Self::Error> {
+            T::delay_ms(self, ms).await
+        }
+    }
+}
+
+/// Source of microsecond timestamps
+///
+/// Used to measure durations, e.g. by [`spi::stats::Statistics`](crate::spi::stats::Statistics)
+/// and [`serial::bitbang::Receiver`](crate::serial::bitbang). Any `FnMut() -> u32` closure
+/// returning a microsecond timestamp is a clock, e.g. one reading a free-running hardware timer.
+pub trait Clock {
+    /// Returns the current time in microseconds, which may wrap around
+    fn now_us(&mut self) -> u32;
+}
+
+impl<F: FnMut() -> u32> Clock for F {
+    fn now_us(&mut self) -> u32 {
+        self()
+    }
+}
@@ -0,0 +1,402 @@
+//! Push buttons
+//!
+//! [`Button`] turns the level changes of a button's pin into events such as presses, long
+//! presses and double clicks, which user interfaces react to. It waits for the pin with
+//! [`Wait`] and times the events with an async delay, so a task can handle a button without
+//! polling it.
+//!
+//! The timing windows start when [`Button::next_event`] is called, so it should be called
+//! again right after an event has been handled.
+//!
+//! Only available with the `async` feature.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::delay::asynch::DelayUs;
+//! use embedded_hal::digital::asynch::Wait;
+//! use embedded_hal::digital::blocking::InputPin;
+//! use embedded_hal::digital::button::{Button, Error, Event, Timings};
+//! use embedded_hal::digital::PinState;
+//!
+//! /// Counts clicks of a button connecting its pin to ground, until it is held down
+//! async fn count_clicks<P, D, E>(pin: P, delay: D) -> Result<u32, Error<E, D::Error>>
+//! where
+//!     P: InputPin<Error = E> + Wait<Error = E>,
+//!     D: DelayUs,
+//!     E: embedded_hal::digital::Error,
+//! {
+//!     let timings = Timings::new().with_long_press_us(2_000_000);
+//!     let mut button = Button::new(pin, delay, PinState::Low).with_timings(timings);
+//!     let mut clicks = 0;
+//!     loop {
+//!         match button.next_event().await? {
+//!             Event::Press => clicks += 1,
+//!             Event::DoubleClick => clicks += 1,
+//!             Event::LongPress => return Ok(clicks),
+//!             Event::Release => {}
+//!         }
+//!     }
+//! }
+//! ```
+
+use super::asynch::Wait;
+use super::blocking::InputPin;
+use super::timeout::{self, with_timeout, Outcome};
+use super::PinState;
+use crate::delay::asynch::DelayUs;
+
+/// Button error
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error<P, D> {
+    /// Reading or waiting for the pin failed
+    Pin(P),
+    /// The delay failed
+    Delay(D),
+}
+
+impl<P: super::Error, D: core::fmt::Debug> super::Error for Error<P, D> {
+    fn kind(&self) -> super::ErrorKind {
+        match self {
+            Self::Pin(e) => e.kind(),
+            Self::Delay(_) => super::ErrorKind::Other,
+        }
+    }
+}
+
+/// Button event
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Event {
+    /// The button was pressed
+    Press,
+    /// The button was released
+    Release,
+    /// The button has been held down for the long press time since it was pressed
+    ///
+    /// Follows a [`Press`](Event::Press), the [`Release`](Event::Release) follows when the
+    /// button is released.
+    LongPress,
+    /// The button was pressed again within the double click time after a short press
+    ///
+    /// Replaces the [`Press`](Event::Press) of the second click. Double clicks are never
+    /// reported as long presses.
+    DoubleClick,
+}
+
+/// Button timings
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Timings {
+    /// Time in microseconds a level must be stable after a change to be accepted
+    pub debounce_us: u32,
+    /// Time in microseconds the button must be held down for a long press
+    pub long_press_us: u32,
+    /// Time in microseconds after a release in which a press is a double click
+    pub double_click_us: u32,
+}
+
+impl Timings {
+    /// Creates new timings with 10 ms debounce, 1 s long press and 300 ms double click time
+    pub const fn new() -> Self {
+        Timings {
+            debounce_us: 10_000,
+            long_press_us: 1_000_000,
+            double_click_us: 300_000,
+        }
+    }
+
+    /// Sets the debounce time in microseconds
+    pub const fn with_debounce_us(mut self, debounce_us: u32) -> Self {
+        self.debounce_us = debounce_us;
+        self
+    }
+
+    /// Sets the long press time in microseconds
+    pub const fn with_long_press_us(mut self, long_press_us: u32) -> Self {
+        self.long_press_us = long_press_us;
+        self
+    }
+
+    /// Sets the double click time in microseconds
+    pub const fn with_double_click_us(mut self, double_click_us: u32) -> Self {
+        self.double_click_us = double_click_us;
+        self
+    }
+}
+
+impl Default for Timings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum State {
+    Released,
+    /// Released after a short press, a press within the double click time is a double click
+    Clicked,
+    /// Pressed, holding it for the long press time is a long press
+    Pressed,
+    /// Pressed, after a long press or a double click
+    Held,
+}
+
+/// Button connected to an input pin
+#[derive(Debug)]
+pub struct Button<P, D> {
+    pin: P,
+    delay: D,
+    pressed: PinState,
+    timings: Timings,
+    state: State,
+}
+
+impl<P, D> Button<P, D> {
+    /// Creates a new button whose pin is at `pressed` while it is pressed, with the default
+    /// timings
+    pub fn new(pin: P, delay: D, pressed: PinState) -> Self {
+        Button {
+            pin,
+            delay,
+            pressed,
+            timings: Timings::new(),
+            state: State::Released,
+        }
+    }
+
+    /// Sets the timings
+    pub fn with_timings(mut self, timings: Timings) -> Self {
+        self.timings = timings;
+        self
+    }
+
+    /// Releases the pin and the delay
+    pub fn release(self) -> (P, D) {
+        (self.pin, self.delay)
+    }
+}
+
+impl<P, D, E> Button<P, D>
+where
+    P: InputPin<Error = E> + Wait<Error = E>,
+    D: DelayUs,
+{
+    /// Waits for the next event
+    ///
+    /// If the button is already pressed when the first event is awaited, a
+    /// [`Press`](Event::Press) is returned right after the debounce time.
+    pub async fn next_event(&mut self) -> Result<Event, Error<E, D::Error>> {
+        loop {
+            match self.state {
+                State::Released => {
+                    self.settle(true, None).await?;
+                    self.state = State::Pressed;
+                    return Ok(Event::Press);
+                }
+                State::Clicked => {
+                    let window = Some(self.timings.double_click_us);
+                    if !self.settle(true, window).await? {
+                        self.state = State::Released;
+                        continue;
+                    }
+                    self.state = State::Held;
+                    return Ok(Event::DoubleClick);
+                }
+                State::Pressed => {
+                    let window = Some(self.timings.long_press_us);
+                    if !self.settle(false, window).await? {
+                        self.state = State::Held;
+                        return Ok(Event::LongPress);
+                    }
+                    self.state = State::Clicked;
+                    return Ok(Event::Release);
+                }
+                State::Held => {
+                    self.settle(false, None).await?;
+                    self.state = State::Released;
+                    return Ok(Event::Release);
+                }
+            }
+        }
+    }
+
+    /// Waits until the button has been `pressed` or released for the debounce time,
+    /// returning `false` if `timeout_us` elapsed first
+    ///
+    /// The timeout restarts if the pin bounces.
+    async fn settle(
+        &mut self,
+        pressed: bool,
+        timeout_us: Option<u32>,
+    ) -> Result<bool, Error<E, D::Error>> {
+        let high = pressed == (self.pressed == PinState::High);
+        loop {
+            if !self.wait_for_level(high, timeout_us).await? {
+                return Ok(false);
+            }
+            let debounce_us = self.timings.debounce_us;
+            self.delay
+                .delay_us(debounce_us)
+                .await
+                .map_err(Error::Delay)?;
+            if self.pin.is_high().map_err(Error::Pin)? == high {
+                return Ok(true);
+            }
+        }
+    }
+
+    /// Waits for the pin to be `high`, returning `false` if `timeout_us` elapsed first
+    async fn wait_for_level(
+        &mut self,
+        high: bool,
+        timeout_us: Option<u32>,
+    ) -> Result<bool, Error<E, D::Error>> {
+        let pin = &mut self.pin;
+        let level = async {
+            if high {
+                pin.wait_for_high().await
+            } else {
+                pin.wait_for_low().await
+            }
+        };
+        let us = match timeout_us {
+            Some(us) => us,
+            None => return level.await.map(|()| true).map_err(Error::Pin),
+        };
+        match with_timeout(level, &mut self.delay, us).await {
+            Ok(outcome) => Ok(outcome == Outcome::Ready),
+            Err(timeout::Error::Pin(e)) => Err(Error::Pin(e)),
+            Err(timeout::Error::Delay(e)) => Err(Error::Delay(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::{BlockOn, Spin};
+    use core::cell::Cell;
+    use core::convert::Infallible;
+    use core::future::poll_fn;
+    use core::task::Poll;
+
+    /// Button connecting its pin to ground while pressed, with a clock advancing by 10 µs
+    /// whenever the pin or the delay is polled without being ready
+    struct Contact<'a> {
+        now: &'a Cell<u32>,
+        /// Times at which the button is pressed, in µs
+        presses: &'a [(u32, u32)],
+    }
+
+    impl Contact<'_> {
+        fn tick(&self) {
+            self.now.set(self.now.get() + 10);
+        }
+    }
+
+    impl InputPin for Contact<'_> {
+        type Error = Infallible;
+
+        fn is_high(&self) -> Result<bool, Self::Error> {
+            let now = self.now.get();
+            Ok(!self
+                .presses
+                .iter()
+                .any(|&(start, end)| (start..end).contains(&now)))
+        }
+
+        fn is_low(&self) -> Result<bool, Self::Error> {
+            self.is_high().map(|high| !high)
+        }
+    }
+
+    impl Wait for Contact<'_> {
+        type Error = Infallible;
+
+        async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+            poll_fn(|_| {
+                if self.is_high().unwrap() {
+                    return Poll::Ready(Ok(()));
+                }
+                self.tick();
+                Poll::Pending
+            })
+            .await
+        }
+
+        async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+            poll_fn(|_| {
+                if self.is_low().unwrap() {
+                    return Poll::Ready(Ok(()));
+                }
+                self.tick();
+                Poll::Pending
+            })
+            .await
+        }
+
+        async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+            self.wait_for_low().await?;
+            self.wait_for_high().await
+        }
+
+        async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+            self.wait_for_high().await?;
+            self.wait_for_low().await
+        }
+
+        async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+            if self.is_high()? {
+                self.wait_for_low().await
+            } else {
+                self.wait_for_high().await
+            }
+        }
+    }
+
+    impl DelayUs for Contact<'_> {
+        type Error = Infallible;
+
+        async fn delay_us(&mut self, us: u32) -> Result<(), Self::Error> {
+            let end = self.now.get() + us;
+            poll_fn(|_| {
+                if self.now.get() >= end {
+                    return Poll::Ready(Ok(()));
+                }
+                self.tick();
+                Poll::Pending
+            })
+            .await
+        }
+    }
+
+    #[test]
+    fn events() {
+        let now = Cell::new(0);
+        // A bounce, a double click and a long press
+        let presses = [
+            (2_000, 2_100),
+            (20_000, 60_000),
+            (150_000, 200_000),
+            (1_000_000, 2_500_000),
+        ];
+        let contact = || Contact {
+            now: &now,
+            presses: &presses,
+        };
+        let mut button = Button::new(contact(), contact(), PinState::Low)
+            .with_timings(Timings::new().with_debounce_us(5_000));
+        let expected = [
+            (Event::Press, 25_000),
+            (Event::Release, 65_000),
+            (Event::DoubleClick, 155_000),
+            (Event::Release, 205_000),
+            (Event::Press, 1_005_000),
+            (Event::LongPress, 2_005_000),
+            (Event::Release, 2_505_000),
+        ];
+        for &(event, time) in &expected {
+            assert_eq!(Spin.block_on(button.next_event()), Ok(event));
+            assert!((time..time + 100).contains(&now.get()), "{}", now.get());
+        }
+    }
+}
@@ -0,0 +1,300 @@
+//! Debounced input pins
+//!
+//! Mechanical switches bounce, i.e. their contacts open and close several times within a few
+//! milliseconds when actuated. [`Debounced`] wraps an input pin and a delay and only reports
+//! a level once the pin has kept it for the debounce time.
+//!
+//! Reading the level takes at least the debounce time, as the pin is sampled until it has
+//! been stable for that long. With the `async` feature, [`Debounced`] also implements
+//! [`Wait`](super::asynch::Wait) for pins implementing it and an async delay, waiting for
+//! debounced levels and edges.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::delay::blocking::DelayUs;
+//! use embedded_hal::digital::blocking::InputPin;
+//! use embedded_hal::digital::debounce::{Debounced, Error};
+//!
+//! /// Returns whether a button connecting its pin to ground is pressed
+//! fn is_pressed<P: InputPin, D: DelayUs>(button: P, delay: D) -> Result<bool, Error<P::Error, D::Error>> {
+//!     let button = Debounced::new(button, delay, 5_000);
+//!     button.is_low()
+//! }
+//! ```
+
+use core::cell::RefCell;
+
+use super::blocking::InputPin;
+use crate::delay::blocking::DelayUs;
+
+/// Debounced pin error
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error<P, D> {
+    /// Reading the pin failed
+    Pin(P),
+    /// The delay failed
+    Delay(D),
+}
+
+impl<P: super::Error, D: core::fmt::Debug> super::Error for Error<P, D> {
+    fn kind(&self) -> super::ErrorKind {
+        match self {
+            Self::Pin(e) => e.kind(),
+            Self::Delay(_) => super::ErrorKind::Other,
+        }
+    }
+}
+
+/// Input pin reporting levels which have been stable for the debounce time
+///
+/// The pin is sampled four times per debounce time.
+#[derive(Debug)]
+pub struct Debounced<P, D> {
+    pin: P,
+    delay: RefCell<D>,
+    debounce_us: u32,
+}
+
+impl<P, D> Debounced<P, D> {
+    /// Creates a new pin debounced for `debounce_us` microseconds
+    pub fn new(pin: P, delay: D, debounce_us: u32) -> Self {
+        Debounced {
+            pin,
+            delay: RefCell::new(delay),
+            debounce_us,
+        }
+    }
+
+    /// Releases the pin and the delay
+    pub fn release(self) -> (P, D) {
+        (self.pin, self.delay.into_inner())
+    }
+
+    fn step_us(&self) -> u32 {
+        (self.debounce_us / 4).max(1)
+    }
+}
+
+impl<P: InputPin, D: DelayUs> Debounced<P, D> {
+    fn level(&self) -> Result<bool, Error<P::Error, D::Error>> {
+        let step = self.step_us();
+        let mut delay = self.delay.borrow_mut();
+        let mut level = self.pin.is_high().map_err(Error::Pin)?;
+        let mut stable = 0;
+        while stable < self.debounce_us {
+            delay.delay_us(step).map_err(Error::Delay)?;
+            let sample = self.pin.is_high().map_err(Error::Pin)?;
+            if sample == level {
+                stable += step;
+            } else {
+                level = sample;
+                stable = 0;
+            }
+        }
+        Ok(level)
+    }
+}
+
+impl<P: InputPin, D: DelayUs> InputPin for Debounced<P, D> {
+    type Error = Error<P::Error, D::Error>;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        self.level()
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        self.level().map(|high| !high)
+    }
+}
+
+#[cfg(feature = "async")]
+mod asynch {
+    use super::{Debounced, Error};
+    use crate::delay::asynch::DelayUs;
+    use crate::digital::asynch::Wait;
+    use crate::digital::blocking::InputPin;
+
+    impl<P, D, E> Debounced<P, D>
+    where
+        P: InputPin<Error = E> + Wait<Error = E>,
+        D: DelayUs,
+    {
+        /// Waits until the pin has been at `high` for the debounce time
+        async fn settle(&mut self, high: bool) -> Result<(), Error<E, D::Error>> {
+            let step = self.step_us();
+            let delay = self.delay.get_mut();
+            let mut stable = 0;
+            while stable < self.debounce_us {
+                if self.pin.is_high().map_err(Error::Pin)? != high {
+                    if high {
+                        self.pin.wait_for_high().await
+                    } else {
+                        self.pin.wait_for_low().await
+                    }
+                    .map_err(Error::Pin)?;
+                    stable = 0;
+                }
+                delay.delay_us(step).await.map_err(Error::Delay)?;
+                if self.pin.is_high().map_err(Error::Pin)? == high {
+                    stable += step;
+                } else {
+                    stable = 0;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl<P, D, E> Wait for Debounced<P, D>
+    where
+        P: InputPin<Error = E> + Wait<Error = E>,
+        D: DelayUs,
+        E: crate::digital::Error,
+    {
+        type Error = Error<E, D::Error>;
+
+        async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+            self.settle(true).await
+        }
+
+        async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+            self.settle(false).await
+        }
+
+        /// Waits for the pin to be stable low and then stable high
+        async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+            self.settle(false).await?;
+            self.settle(true).await
+        }
+
+        /// Waits for the pin to be stable high and then stable low
+        async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+            self.settle(true).await?;
+            self.settle(false).await
+        }
+
+        /// Waits for the pin to be stable at the opposite of its current level
+        async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+            let high = self.pin.is_high().map_err(Error::Pin)?;
+            self.settle(high).await?;
+            self.settle(!high).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+    use core::convert::Infallible;
+
+    /// Button whose level is given by a function of the time, with a clock advanced by the
+    /// delay
+    struct Button<'a>(&'a Cell<u32>, fn(u32) -> bool);
+
+    /// Bounces between 0 and 3 ms and is pressed afterwards
+    fn bouncing(now: u32) -> bool {
+        now < 3_000 && (now / 700) % 2 == 1
+    }
+
+    impl InputPin for Button<'_> {
+        type Error = Infallible;
+
+        fn is_high(&self) -> Result<bool, Self::Error> {
+            Ok((self.1)(self.0.get()))
+        }
+
+        fn is_low(&self) -> Result<bool, Self::Error> {
+            self.is_high().map(|high| !high)
+        }
+    }
+
+    impl DelayUs for Button<'_> {
+        type Error = Infallible;
+
+        fn delay_us(&mut self, us: u32) -> Result<(), Self::Error> {
+            self.0.set(self.0.get() + us);
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl crate::delay::asynch::DelayUs for Button<'_> {
+        type Error = Infallible;
+
+        async fn delay_us(&mut self, us: u32) -> Result<(), Self::Error> {
+            DelayUs::delay_us(self, us)
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl crate::digital::asynch::Wait for Button<'_> {
+        type Error = Infallible;
+
+        async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+            while self.is_low()? {
+                self.0.set(self.0.get() + 100);
+            }
+            Ok(())
+        }
+
+        async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+            while self.is_high()? {
+                self.0.set(self.0.get() + 100);
+            }
+            Ok(())
+        }
+
+        async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+            self.wait_for_low().await?;
+            self.wait_for_high().await
+        }
+
+        async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+            self.wait_for_high().await?;
+            self.wait_for_low().await
+        }
+
+        async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+            if self.is_high()? {
+                self.wait_for_low().await
+            } else {
+                self.wait_for_high().await
+            }
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn waits_for_debounced_level() {
+        use crate::adapter::{BlockOn, Spin};
+        use crate::digital::asynch::Wait;
+
+        let now = Cell::new(700);
+        let mut button = Debounced::new(Button(&now, bouncing), Button(&now, bouncing), 2_000);
+        Spin.block_on(button.wait_for_low()).unwrap();
+        assert_eq!(now.get(), 4_800);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn glitch_in_last_step_restarts_wait() {
+        use crate::adapter::{BlockOn, Spin};
+        use crate::digital::asynch::Wait;
+
+        let glitch = |now| (1_800..2_100).contains(&now);
+        let now = Cell::new(0);
+        let mut button = Debounced::new(Button(&now, glitch), Button(&now, glitch), 2_000);
+        Spin.block_on(button.wait_for_low()).unwrap();
+        assert_eq!(now.get(), 4_100);
+    }
+
+    #[test]
+    fn waits_for_stable_level() {
+        let now = Cell::new(0);
+        let button = Debounced::new(Button(&now, bouncing), Button(&now, bouncing), 2_000);
+        assert_eq!(button.is_low(), Ok(true));
+        assert!(now.get() >= 5_000);
+    }
+}
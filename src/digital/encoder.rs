@@ -0,0 +1,317 @@
+//! Quadrature decoding of rotary encoders
+//!
+//! Rotary encoders output two square waves, A and B, a quarter period apart. The order of
+//! their edges gives the direction of rotation: A rises before B when turning forward, and B
+//! rises before A when turning backward. [`Encoder`] decodes them from two input pins for
+//! MCUs without a quadrature encoder interface, or with too few of them.
+//!
+//! The pins can be polled with [`Encoder::update`], e.g. from a timer interrupt, fast enough
+//! to see every level change. With the `async` feature and pins implementing
+//! [`Wait`](super::asynch::Wait), [`Encoder::wait_for_step`] waits for the pins to change
+//! instead.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::digital::blocking::InputPin;
+//! use embedded_hal::digital::encoder::{Encoder, Error};
+//!
+//! /// Adjusts a setting by one per detent of a knob with one period per detent
+//! fn adjust<A, B, E>(knob: &mut Encoder<A, B>, setting: &mut i32) -> Result<(), Error<E>>
+//! where
+//!     A: InputPin<Error = E>,
+//!     B: InputPin<Error = E>,
+//!     E: embedded_hal::digital::Error,
+//! {
+//!     *setting += knob.update()?;
+//!     Ok(())
+//! }
+//! ```
+
+use super::blocking::InputPin;
+
+/// Encoder error
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error<E> {
+    /// Reading a pin failed
+    Pin(E),
+    /// Both pins changed since the last update, so the direction is unknown
+    ///
+    /// The pins weren't polled often enough. The step is dropped and decoding continues with
+    /// the current levels.
+    MissedStep,
+}
+
+impl<E: super::Error> super::Error for Error<E> {
+    fn kind(&self) -> super::ErrorKind {
+        match self {
+            Self::Pin(e) => e.kind(),
+            Self::MissedStep => super::ErrorKind::Other,
+        }
+    }
+}
+
+/// Counts per period of the encoder signals
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Decoding {
+    /// One count per period, for encoders with one period per detent
+    X1,
+    /// Two counts per period
+    X2,
+    /// Four counts per period, one for every edge, giving the highest resolution
+    X4,
+}
+
+impl Decoding {
+    fn edges_per_count(self) -> i8 {
+        match self {
+            Decoding::X1 => 4,
+            Decoding::X2 => 2,
+            Decoding::X4 => 1,
+        }
+    }
+}
+
+/// Quadrature decoder over two input pins
+#[derive(Debug)]
+pub struct Encoder<A, B> {
+    a: A,
+    b: B,
+    decoding: Decoding,
+    /// Levels of A (bit 1) and B (bit 0) at the last update, unknown before the first
+    levels: Option<u8>,
+    /// Edges counted towards the next count, with the sign of its direction
+    edges: i8,
+}
+
+impl<A, B> Encoder<A, B> {
+    /// Creates a new encoder
+    ///
+    /// The levels of the pins are read on the first update, which always returns zero.
+    pub fn new(a: A, b: B, decoding: Decoding) -> Self {
+        Encoder {
+            a,
+            b,
+            decoding,
+            levels: None,
+            edges: 0,
+        }
+    }
+
+    /// Releases the pins
+    pub fn release(self) -> (A, B) {
+        (self.a, self.b)
+    }
+}
+
+impl<A, B, E> Encoder<A, B>
+where
+    A: InputPin<Error = E>,
+    B: InputPin<Error = E>,
+{
+    /// Reads the pins, returning the counts since the last update
+    ///
+    /// Positive counts are forward steps, i.e. A leading B.
+    pub fn update(&mut self) -> Result<i32, Error<E>> {
+        let levels = (self.a.is_high().map_err(Error::Pin)? as u8) << 1
+            | self.b.is_high().map_err(Error::Pin)? as u8;
+        let previous = match self.levels.replace(levels) {
+            Some(previous) => previous,
+            None => return Ok(0),
+        };
+        // Indexed by the previous and the current levels, `None` if both pins changed
+        const STEPS: [Option<i8>; 16] = [
+            Some(0),
+            Some(-1),
+            Some(1),
+            None,
+            Some(1),
+            Some(0),
+            None,
+            Some(-1),
+            Some(-1),
+            None,
+            Some(0),
+            Some(1),
+            None,
+            Some(1),
+            Some(-1),
+            Some(0),
+        ];
+        let step = STEPS[usize::from(previous << 2 | levels)].ok_or(Error::MissedStep)?;
+        let edges = self.edges + step;
+        let per_count = self.decoding.edges_per_count();
+        // Truncating keeps a pin bouncing at a count boundary from changing the count back and
+        // forth
+        self.edges = edges % per_count;
+        Ok(i32::from(edges / per_count))
+    }
+}
+
+#[cfg(feature = "async")]
+mod asynch {
+    use super::{Encoder, Error};
+    use crate::digital::asynch::Wait;
+    use crate::digital::blocking::InputPin;
+    use core::future::{poll_fn, Future};
+    use core::pin::pin;
+    use core::task::Poll;
+
+    impl<A, B, E> Encoder<A, B>
+    where
+        A: InputPin<Error = E> + Wait<Error = E>,
+        B: InputPin<Error = E> + Wait<Error = E>,
+    {
+        /// Waits until the encoder has moved by at least one count, returning the counts
+        ///
+        /// Same as calling [`update`](Encoder::update) whenever one of the pins changes,
+        /// until it returns a count other than zero.
+        pub async fn wait_for_step(&mut self) -> Result<i32, Error<E>> {
+            loop {
+                let counts = self.update()?;
+                if counts != 0 {
+                    return Ok(counts);
+                }
+                let mut a = pin!(self.a.wait_for_any_edge());
+                let mut b = pin!(self.b.wait_for_any_edge());
+                poll_fn(|cx| match (a.as_mut().poll(cx), b.as_mut().poll(cx)) {
+                    (Poll::Ready(Err(e)), _) | (_, Poll::Ready(Err(e))) => Poll::Ready(Err(e)),
+                    (Poll::Pending, Poll::Pending) => Poll::Pending,
+                    _ => Poll::Ready(Ok(())),
+                })
+                .await
+                .map_err(Error::Pin)?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+    use core::convert::Infallible;
+
+    /// Signals stepping through levels of A (bit 1) and B (bit 0)
+    struct Signals {
+        levels: &'static [u8],
+        now: Cell<usize>,
+    }
+
+    impl Signals {
+        fn advance(&self) {
+            self.now.set(self.now.get() + 1);
+        }
+    }
+
+    struct Phase<'a>(&'a Signals, u8);
+
+    impl InputPin for Phase<'_> {
+        type Error = Infallible;
+
+        fn is_high(&self) -> Result<bool, Self::Error> {
+            Ok(self.0.levels[self.0.now.get()] & self.1 != 0)
+        }
+
+        fn is_low(&self) -> Result<bool, Self::Error> {
+            self.is_high().map(|high| !high)
+        }
+    }
+
+    /// A forward period, a backward period, a forward edge and a missed step
+    const LEVELS: &[u8] = &[0, 2, 3, 1, 0, 1, 3, 2, 0, 2, 1];
+
+    fn decode(decoding: Decoding) -> [Result<i32, Error<Infallible>>; 11] {
+        let signals = Signals {
+            levels: LEVELS,
+            now: Cell::new(0),
+        };
+        let mut encoder = Encoder::new(Phase(&signals, 2), Phase(&signals, 1), decoding);
+        let mut counts = [Ok(0); 11];
+        for count in &mut counts {
+            *count = encoder.update();
+            signals.advance();
+        }
+        counts
+    }
+
+    #[test]
+    fn decodes_steps() {
+        assert_eq!(
+            decode(Decoding::X4),
+            [
+                Ok(0),
+                Ok(1),
+                Ok(1),
+                Ok(1),
+                Ok(1),
+                Ok(-1),
+                Ok(-1),
+                Ok(-1),
+                Ok(-1),
+                Ok(1),
+                Err(Error::MissedStep)
+            ]
+        );
+        let x2 = decode(Decoding::X2);
+        assert_eq!(x2[..5], [Ok(0), Ok(0), Ok(1), Ok(0), Ok(1)]);
+        assert_eq!(x2[5..9], [Ok(0), Ok(-1), Ok(0), Ok(-1)]);
+        let x1 = decode(Decoding::X1);
+        assert_eq!(x1[..5], [Ok(0), Ok(0), Ok(0), Ok(0), Ok(1)]);
+        assert_eq!(x1[5..9], [Ok(0), Ok(0), Ok(0), Ok(-1)]);
+    }
+
+    #[cfg(feature = "async")]
+    impl crate::digital::asynch::Wait for Phase<'_> {
+        type Error = Infallible;
+
+        async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+            while self.is_low()? {
+                self.0.advance();
+            }
+            Ok(())
+        }
+
+        async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+            while self.is_high()? {
+                self.0.advance();
+            }
+            Ok(())
+        }
+
+        async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+            self.wait_for_low().await?;
+            self.wait_for_high().await
+        }
+
+        async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+            self.wait_for_high().await?;
+            self.wait_for_low().await
+        }
+
+        /// Advances the signals on A, never returns on B
+        async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+            if self.1 == 1 {
+                core::future::pending::<()>().await;
+            }
+            self.0.advance();
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn waits_for_steps() {
+        use crate::adapter::{BlockOn, Spin};
+
+        let signals = Signals {
+            levels: LEVELS,
+            now: Cell::new(0),
+        };
+        let mut encoder = Encoder::new(Phase(&signals, 2), Phase(&signals, 1), Decoding::X1);
+        assert_eq!(Spin.block_on(encoder.wait_for_step()), Ok(1));
+        assert_eq!(signals.now.get(), 4);
+        assert_eq!(Spin.block_on(encoder.wait_for_step()), Ok(-1));
+        assert_eq!(signals.now.get(), 8);
+    }
+}
@@ -0,0 +1,190 @@
+//! Type-erased pins
+//!
+//! Each HAL pin usually has its own type, so pins of different ports or HALs can't be stored
+//! in the same array. [`ErasedOutput`] and [`ErasedInput`] borrow a pin of any type and
+//! forward to it through a trait object, mapping its errors to their [`ErrorKind`]. This
+//! needs no allocator, but the pins must outlive the erased pins, e.g. by being `static` or
+//! owned by the caller.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::digital::blocking::OutputPin;
+//! use embedded_hal::digital::erased::ErasedOutput;
+//! use embedded_hal::digital::ErrorKind;
+//!
+//! /// Lights the first `level` LEDs of a bar graph
+//! fn show(leds: &mut [ErasedOutput<'_>], level: usize) -> Result<(), ErrorKind> {
+//!     for (n, led) in leds.iter_mut().enumerate() {
+//!         led.set_state((n < level).into())?;
+//!     }
+//!     Ok(())
+//! }
+//!
+//! fn show_on_two_ports<A: OutputPin, B: OutputPin>(a: &mut A, b: &mut B) -> Result<(), ErrorKind> {
+//!     show(&mut [ErasedOutput::new(a), ErasedOutput::new(b)], 1)
+//! }
+//! ```
+
+use super::blocking::{InputPin, OutputPin};
+use super::{Error, ErrorKind};
+
+/// Object safe output pin with erased error type
+trait DynOutputPin {
+    fn erased_set_low(&mut self) -> Result<(), ErrorKind>;
+    fn erased_set_high(&mut self) -> Result<(), ErrorKind>;
+}
+
+impl<P: OutputPin> DynOutputPin for P {
+    fn erased_set_low(&mut self) -> Result<(), ErrorKind> {
+        self.set_low().map_err(|e| e.kind())
+    }
+
+    fn erased_set_high(&mut self) -> Result<(), ErrorKind> {
+        self.set_high().map_err(|e| e.kind())
+    }
+}
+
+/// Object safe input pin with erased error type
+trait DynInputPin {
+    fn erased_is_high(&self) -> Result<bool, ErrorKind>;
+    fn erased_is_low(&self) -> Result<bool, ErrorKind>;
+}
+
+impl<P: InputPin> DynInputPin for P {
+    fn erased_is_high(&self) -> Result<bool, ErrorKind> {
+        self.is_high().map_err(|e| e.kind())
+    }
+
+    fn erased_is_low(&self) -> Result<bool, ErrorKind> {
+        self.is_low().map_err(|e| e.kind())
+    }
+}
+
+/// Output pin of any type
+pub struct ErasedOutput<'a> {
+    pin: &'a mut dyn DynOutputPin,
+}
+
+impl<'a> ErasedOutput<'a> {
+    /// Creates a new erased pin borrowing `pin`
+    pub fn new<P: OutputPin>(pin: &'a mut P) -> Self {
+        ErasedOutput { pin }
+    }
+}
+
+impl core::fmt::Debug for ErasedOutput<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ErasedOutput").finish()
+    }
+}
+
+impl OutputPin for ErasedOutput<'_> {
+    type Error = ErrorKind;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.pin.erased_set_low()
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.pin.erased_set_high()
+    }
+}
+
+/// Input pin of any type
+#[derive(Copy, Clone)]
+pub struct ErasedInput<'a> {
+    pin: &'a dyn DynInputPin,
+}
+
+impl<'a> ErasedInput<'a> {
+    /// Creates a new erased pin borrowing `pin`
+    pub fn new<P: InputPin>(pin: &'a P) -> Self {
+        ErasedInput { pin }
+    }
+}
+
+impl core::fmt::Debug for ErasedInput<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ErasedInput").finish()
+    }
+}
+
+impl InputPin for ErasedInput<'_> {
+    type Error = ErrorKind;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        self.pin.erased_is_high()
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        self.pin.erased_is_low()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digital::NoPin;
+    use core::cell::Cell;
+
+    /// Pin of an I/O expander which has been disconnected
+    struct Remote;
+
+    impl OutputPin for Remote {
+        type Error = ErrorKind;
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            Err(ErrorKind::Disconnected)
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            Err(ErrorKind::Disconnected)
+        }
+    }
+
+    struct Local(Cell<bool>);
+
+    impl OutputPin for Local {
+        type Error = core::convert::Infallible;
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.0.set(false);
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.0.set(true);
+            Ok(())
+        }
+    }
+
+    impl InputPin for Local {
+        type Error = core::convert::Infallible;
+
+        fn is_high(&self) -> Result<bool, Self::Error> {
+            Ok(self.0.get())
+        }
+
+        fn is_low(&self) -> Result<bool, Self::Error> {
+            Ok(!self.0.get())
+        }
+    }
+
+    #[test]
+    fn mixed_pins() {
+        let (mut local, mut remote, mut none) = (Local(Cell::new(false)), Remote, NoPin);
+        let mut outputs = [
+            ErasedOutput::new(&mut local),
+            ErasedOutput::new(&mut remote),
+            ErasedOutput::new(&mut none),
+        ];
+        assert_eq!(outputs[0].set_high(), Ok(()));
+        assert_eq!(outputs[1].set_high(), Err(ErrorKind::Disconnected));
+        assert_eq!(outputs[2].set_low(), Ok(()));
+
+        let inputs = [ErasedInput::new(&local), ErasedInput::new(&NoPin)];
+        assert_eq!(inputs[0].is_high(), Ok(true));
+        assert_eq!(inputs[1].is_low(), Ok(true));
+    }
+}
@@ -0,0 +1,80 @@
+//! Pin interrupts
+//!
+//! [`PinInterrupt`] arms the interrupt of an input pin and registers a handler which the HAL
+//! calls from its interrupt service routine, so drivers can react to pin changes without
+//! HAL-specific extension traits. HALs clear the pending flag of the pin before calling the
+//! handler.
+//!
+//! Handlers are plain functions, as they run in interrupt context and can't borrow from the
+//! code registering them. They typically set an atomic flag or pend a task.
+//!
+//! ## Example
+//!
+//! ```
+//! use core::sync::atomic::{AtomicBool, Ordering};
+//! use embedded_hal::digital::interrupt::{Edge, PinInterrupt, Trigger};
+//!
+//! static DATA_READY: AtomicBool = AtomicBool::new(false);
+//!
+//! fn on_data_ready() {
+//!     DATA_READY.store(true, Ordering::Release);
+//! }
+//!
+//! /// Arms the data ready line of an accelerometer
+//! fn listen<P: PinInterrupt>(drdy: &mut P) -> Result<(), P::Error> {
+//!     drdy.register_interrupt(Trigger::Edge(Edge::Rising), on_data_ready)
+//! }
+//! ```
+
+use super::PinState;
+
+/// Pin transition
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Edge {
+    /// Transition from low to high
+    Rising,
+    /// Transition from high to low
+    Falling,
+    /// Transition in either direction
+    Any,
+}
+
+/// Condition raising a pin interrupt
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Trigger {
+    /// Transitions of the pin
+    Edge(Edge),
+    /// The pin being at a level, which keeps raising the interrupt until the cause is removed,
+    /// e.g. by reading the status of the device pulling the line
+    Level(PinState),
+}
+
+/// Input pin with interrupt support
+pub trait PinInterrupt {
+    /// Error type
+    type Error: super::Error;
+
+    /// Arms the interrupt for `trigger`, calling `handler` in interrupt context whenever it
+    /// is raised
+    ///
+    /// Replaces a previously registered handler. Returns an error if the pin doesn't support
+    /// `trigger`, e.g. level triggers.
+    fn register_interrupt(&mut self, trigger: Trigger, handler: fn()) -> Result<(), Self::Error>;
+
+    /// Disarms the interrupt and removes the handler
+    ///
+    /// Once this method returns, the handler isn't called anymore.
+    fn unregister_interrupt(&mut self) -> Result<(), Self::Error>;
+}
+
+impl<T: PinInterrupt> PinInterrupt for &mut T {
+    type Error = T::Error;
+
+    fn register_interrupt(&mut self, trigger: Trigger, handler: fn()) -> Result<(), Self::Error> {
+        T::register_interrupt(self, trigger, handler)
+    }
+
+    fn unregister_interrupt(&mut self) -> Result<(), Self::Error> {
+        T::unregister_interrupt(self)
+    }
+}
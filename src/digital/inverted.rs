@@ -0,0 +1,198 @@
+//! Pins with inverted logic
+//!
+//! [`InvertedInput`] and [`InvertedOutput`] swap high and low of the pin they wrap, so
+//! active-low signals such as enable lines or LEDs connected to the supply can be handled by
+//! drivers which expect active-high pins, and vice versa.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::digital::blocking::OutputPin;
+//! use embedded_hal::digital::inverted::InvertedOutput;
+//!
+//! /// Driver switching a load on with an active-high enable pin
+//! struct Load<P>(P);
+//!
+//! impl<P: OutputPin> Load<P> {
+//!     pub fn on(&mut self) -> Result<(), P::Error> {
+//!         self.0.set_high()
+//!     }
+//! }
+//!
+//! /// Creates the driver for a board on which the enable pin is active-low
+//! fn active_low_load<P: OutputPin>(enable: P) -> Load<InvertedOutput<P>> {
+//!     Load(InvertedOutput::new(enable))
+//! }
+//! ```
+
+use super::blocking::{InputPin, OutputPin, StatefulOutputPin, ToggleableOutputPin};
+
+/// Input pin reporting the opposite of its electrical level
+#[derive(Debug, Clone, Copy)]
+pub struct InvertedInput<P> {
+    pin: P,
+}
+
+impl<P> InvertedInput<P> {
+    /// Creates a new inverted input pin
+    pub fn new(pin: P) -> Self {
+        InvertedInput { pin }
+    }
+
+    /// Releases the pin
+    pub fn release(self) -> P {
+        self.pin
+    }
+}
+
+impl<P: InputPin> InputPin for InvertedInput<P> {
+    type Error = P::Error;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        self.pin.is_low()
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        self.pin.is_high()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<P: super::asynch::Wait> super::asynch::Wait for InvertedInput<P> {
+    type Error = P::Error;
+
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        self.pin.wait_for_low().await
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        self.pin.wait_for_high().await
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        self.pin.wait_for_falling_edge().await
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        self.pin.wait_for_rising_edge().await
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        self.pin.wait_for_any_edge().await
+    }
+}
+
+/// Output pin driving the opposite of the requested level
+#[derive(Debug, Clone, Copy)]
+pub struct InvertedOutput<P> {
+    pin: P,
+}
+
+impl<P> InvertedOutput<P> {
+    /// Creates a new inverted output pin
+    ///
+    /// The electrical level of the pin is left unchanged.
+    pub fn new(pin: P) -> Self {
+        InvertedOutput { pin }
+    }
+
+    /// Releases the pin
+    pub fn release(self) -> P {
+        self.pin
+    }
+}
+
+impl<P: OutputPin> OutputPin for InvertedOutput<P> {
+    type Error = P::Error;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.pin.set_high()
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.pin.set_low()
+    }
+}
+
+impl<P: StatefulOutputPin> StatefulOutputPin for InvertedOutput<P> {
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        self.pin.is_set_low()
+    }
+
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        self.pin.is_set_high()
+    }
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        self.pin.toggle()
+    }
+}
+
+impl<P: ToggleableOutputPin> ToggleableOutputPin for InvertedOutput<P> {
+    type Error = P::Error;
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        self.pin.toggle()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digital::PinState;
+    use core::cell::Cell;
+    use core::convert::Infallible;
+
+    struct Pin<'a>(&'a Cell<bool>);
+
+    impl InputPin for Pin<'_> {
+        type Error = Infallible;
+
+        fn is_high(&self) -> Result<bool, Self::Error> {
+            Ok(self.0.get())
+        }
+
+        fn is_low(&self) -> Result<bool, Self::Error> {
+            Ok(!self.0.get())
+        }
+    }
+
+    impl OutputPin for Pin<'_> {
+        type Error = Infallible;
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.0.set(false);
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.0.set(true);
+            Ok(())
+        }
+    }
+
+    impl StatefulOutputPin for Pin<'_> {
+        fn is_set_high(&self) -> Result<bool, Self::Error> {
+            Ok(self.0.get())
+        }
+
+        fn is_set_low(&self) -> Result<bool, Self::Error> {
+            Ok(!self.0.get())
+        }
+    }
+
+    #[test]
+    fn swaps_levels() {
+        let level = Cell::new(false);
+        let input = InvertedInput::new(Pin(&level));
+        let mut output = InvertedOutput::new(Pin(&level));
+        assert_eq!(input.is_high(), Ok(true));
+        output.set_state(PinState::High).unwrap();
+        assert!(!level.get());
+        assert_eq!(output.is_set_high(), Ok(true));
+        StatefulOutputPin::toggle(&mut output).unwrap();
+        assert!(level.get());
+        assert_eq!(output.is_set_low(), Ok(true));
+        assert_eq!(input.is_low(), Ok(true));
+    }
+}
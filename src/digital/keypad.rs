@@ -0,0 +1,485 @@
+//! Matrix keypad scanning
+//!
+//! The keys of a matrix keypad connect a row line to a column line. [`Keypad`] selects one row
+//! at a time by driving it low and reads which columns are pulled low through pressed keys,
+//! with pull-ups keeping the other columns high. Rows and columns can be single pins or, where
+//! the HAL provides them, pin groups implementing [`OutputPort`] and [`InputPort`].
+//!
+//! Unselected rows are driven high, so the row pins should be open-drain, or the keys should
+//! have diodes, to avoid shorting two rows through two pressed keys of the same column.
+//!
+//! Without diodes, three pressed keys at three corners of a rectangle also connect the row
+//! and column of the fourth corner, which then looks pressed as well. Such scans are
+//! detected and ignored, see [`Keypad::is_ghosting`].
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::delay::blocking::DelayUs;
+//! use embedded_hal::digital::blocking::{InputPin, OutputPin};
+//! use embedded_hal::digital::keypad::{Error, Event, Keypad};
+//!
+//! const KEYS: [[char; 3]; 4] = [
+//!     ['1', '2', '3'],
+//!     ['4', '5', '6'],
+//!     ['7', '8', '9'],
+//!     ['*', '0', '#'],
+//! ];
+//!
+//! /// Returns the next key pressed on a phone keypad, if any
+//! fn next_key<R, C, D, E>(keypad: &mut Keypad<&mut [R], &[C], D>) -> Result<Option<char>, Error<E, D::Error>>
+//! where
+//!     R: OutputPin<Error = E>,
+//!     C: InputPin<Error = E>,
+//!     D: DelayUs,
+//!     E: embedded_hal::digital::Error,
+//! {
+//!     while let Some(event) = keypad.poll()? {
+//!         if let Event::Pressed(key) = event {
+//!             return Ok(Some(KEYS[key.row as usize][key.column as usize]));
+//!         }
+//!     }
+//!     Ok(None)
+//! }
+//! ```
+
+use super::blocking::{InputPin, InputPort, OutputPin, OutputPort};
+use crate::delay::blocking::DelayUs;
+
+/// Keypad error
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error<P, D> {
+    /// Setting or reading a pin failed
+    Pin(P),
+    /// The delay failed
+    Delay(D),
+}
+
+impl<P: super::Error, D: core::fmt::Debug> super::Error for Error<P, D> {
+    fn kind(&self) -> super::ErrorKind {
+        match self {
+            Self::Pin(e) => e.kind(),
+            Self::Delay(_) => super::ErrorKind::Other,
+        }
+    }
+}
+
+/// Error returned by [`Keypad::new`] for keypads with more than 64 keys
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TooManyKeys;
+
+/// Row lines of a keypad
+pub trait Rows {
+    /// Error type
+    type Error: super::Error;
+
+    /// Returns the number of rows
+    fn count(&self) -> usize;
+
+    /// Drives `row` low and all other rows high, or all rows high if `row` is `None`
+    fn select(&mut self, row: Option<usize>) -> Result<(), Self::Error>;
+}
+
+impl<P: OutputPin> Rows for &mut [P] {
+    type Error = P::Error;
+
+    fn count(&self) -> usize {
+        <[P]>::len(self)
+    }
+
+    fn select(&mut self, row: Option<usize>) -> Result<(), Self::Error> {
+        // Release the previous row first, so that two rows are never selected at once
+        for (n, pin) in self.iter_mut().enumerate() {
+            if Some(n) != row {
+                pin.set_high()?;
+            }
+        }
+        match row {
+            Some(row) => self[row].set_low(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<P: OutputPort> Rows for P {
+    type Error = P::Error;
+
+    fn count(&self) -> usize {
+        P::WIDTH as usize
+    }
+
+    fn select(&mut self, row: Option<usize>) -> Result<(), Self::Error> {
+        self.write(match row {
+            Some(row) => !(1 << row),
+            None => u32::MAX,
+        })
+    }
+}
+
+/// Column lines of a keypad
+pub trait Columns {
+    /// Error type
+    type Error: super::Error;
+
+    /// Returns the number of columns
+    fn count(&self) -> usize;
+
+    /// Returns the columns which are low, with bit `n` set for column `n`
+    fn read(&self) -> Result<u32, Self::Error>;
+}
+
+impl<P: InputPin> Columns for &[P] {
+    type Error = P::Error;
+
+    fn count(&self) -> usize {
+        <[P]>::len(self)
+    }
+
+    fn read(&self) -> Result<u32, Self::Error> {
+        let mut low = 0;
+        for (n, pin) in self.iter().enumerate() {
+            if pin.is_low()? {
+                low |= 1 << n;
+            }
+        }
+        Ok(low)
+    }
+}
+
+impl<P: InputPort> Columns for P {
+    type Error = P::Error;
+
+    fn count(&self) -> usize {
+        P::WIDTH as usize
+    }
+
+    fn read(&self) -> Result<u32, Self::Error> {
+        let mask = 1u32.checked_shl(P::WIDTH).map_or(u32::MAX, |bit| bit - 1);
+        Ok(!InputPort::read(self)? & mask)
+    }
+}
+
+/// Position of a key in the matrix
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Key {
+    /// Row of the key
+    pub row: u8,
+    /// Column of the key
+    pub column: u8,
+}
+
+/// Keypad event
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Event {
+    /// The key was pressed
+    Pressed(Key),
+    /// The key was released
+    Released(Key),
+}
+
+/// Matrix keypad scanner
+#[derive(Debug)]
+pub struct Keypad<R, C, D> {
+    rows: R,
+    columns: C,
+    delay: D,
+    settle_us: u32,
+    debounce_scans: u8,
+    /// Keys of the last scan, with bit `row * columns + column` set for pressed keys
+    scanned: u64,
+    /// Number of scans in a row which returned `scanned`
+    stable_scans: u8,
+    /// Debounced keys, as reported by the events
+    pressed: u64,
+    /// Keys whose events haven't been returned yet
+    pending: u64,
+    ghosting: bool,
+}
+
+impl<R: Rows, C: Columns, D> Keypad<R, C, D> {
+    /// Creates a new keypad
+    ///
+    /// By default, the columns are read 10 µs after selecting a row, and a change is reported
+    /// once three scans in a row returned it.
+    ///
+    /// Returns [`TooManyKeys`] if the keypad has more than 64 keys.
+    pub fn new(rows: R, columns: C, delay: D) -> Result<Self, TooManyKeys> {
+        if rows.count() * columns.count() > 64 {
+            return Err(TooManyKeys);
+        }
+        Ok(Keypad {
+            rows,
+            columns,
+            delay,
+            settle_us: 10,
+            debounce_scans: 3,
+            scanned: 0,
+            stable_scans: 0,
+            pressed: 0,
+            pending: 0,
+            ghosting: false,
+        })
+    }
+
+    /// Sets the time in microseconds between selecting a row and reading the columns
+    pub fn with_settle_us(mut self, settle_us: u32) -> Self {
+        self.settle_us = settle_us;
+        self
+    }
+
+    /// Sets the number of scans in a row which must return a change for it to be reported
+    pub fn with_debounce_scans(mut self, scans: u8) -> Self {
+        self.debounce_scans = scans.max(1);
+        self
+    }
+
+    /// Returns whether the last scan was ignored because pressed keys may have hidden others
+    ///
+    /// This is the case when two rows have two or more pressed columns in common. Changes
+    /// are reported again once fewer keys are pressed.
+    pub fn is_ghosting(&self) -> bool {
+        self.ghosting
+    }
+
+    /// Returns whether `key` is pressed, after debouncing
+    pub fn is_pressed(&self, key: Key) -> bool {
+        self.pressed & self.bit(key) != 0
+    }
+
+    /// Releases the rows, the columns and the delay
+    pub fn release(self) -> (R, C, D) {
+        (self.rows, self.columns, self.delay)
+    }
+
+    fn bit(&self, key: Key) -> u64 {
+        1 << (usize::from(key.row) * self.columns.count() + usize::from(key.column))
+    }
+}
+
+impl<R, C, D, E> Keypad<R, C, D>
+where
+    R: Rows<Error = E>,
+    C: Columns<Error = E>,
+    D: DelayUs,
+{
+    /// Returns the next key event, scanning the keypad if no events are pending
+    ///
+    /// Returns `None` if the scan didn't change the debounced keys. Call this regularly,
+    /// e.g. every few milliseconds, so that the debounce scans span the bouncing of the keys.
+    pub fn poll(&mut self) -> Result<Option<Event>, Error<E, D::Error>> {
+        if self.pending == 0 {
+            self.scan()?;
+        }
+        if self.pending == 0 {
+            return Ok(None);
+        }
+        let n = self.pending.trailing_zeros() as usize;
+        let bit = 1 << n;
+        self.pending &= !bit;
+        self.pressed ^= bit;
+        let columns = self.columns.count();
+        let key = Key {
+            row: (n / columns) as u8,
+            column: (n % columns) as u8,
+        };
+        Ok(Some(if self.pressed & bit != 0 {
+            Event::Pressed(key)
+        } else {
+            Event::Released(key)
+        }))
+    }
+
+    fn scan(&mut self) -> Result<(), Error<E, D::Error>> {
+        let columns = self.columns.count();
+        let mut keys = 0;
+        let mut ghosting = false;
+        for row in 0..self.rows.count() {
+            self.rows.select(Some(row)).map_err(Error::Pin)?;
+            self.delay.delay_us(self.settle_us).map_err(Error::Delay)?;
+            let pressed = self.columns.read().map_err(Error::Pin)?;
+            for other in 0..row {
+                let common = pressed & (keys >> (other * columns)) as u32;
+                ghosting |= common.count_ones() >= 2;
+            }
+            keys |= u64::from(pressed) << (row * columns);
+        }
+        self.rows.select(None).map_err(Error::Pin)?;
+
+        self.ghosting = ghosting;
+        if ghosting {
+            return Ok(());
+        }
+        if keys == self.scanned {
+            self.stable_scans = self.stable_scans.saturating_add(1);
+        } else {
+            self.scanned = keys;
+            self.stable_scans = 1;
+        }
+        if self.stable_scans >= self.debounce_scans {
+            self.pending = self.scanned ^ self.pressed;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+    use core::convert::Infallible;
+
+    /// Keypad with 4 rows and 3 columns, without diodes
+    struct Matrix {
+        /// Pressed keys, with bit `row * 3 + column` set
+        keys: Cell<u64>,
+        row: Cell<Option<usize>>,
+    }
+
+    impl Matrix {
+        fn press(&self, row: u8, column: u8) {
+            self.keys.set(self.keys.get() | 1 << (row * 3 + column));
+        }
+
+        /// Returns whether `column` is connected to the selected row through pressed keys
+        fn is_low(&self, column: usize) -> bool {
+            let row = match self.row.get() {
+                Some(row) => row,
+                None => return false,
+            };
+            let keys = self.keys.get();
+            let pressed = |row: usize, column: usize| keys & 1 << (row * 3 + column) != 0;
+            // Rows connected to the selected row through another column
+            (0..4).any(|other| {
+                pressed(other, column)
+                    && (0..3).any(|via| pressed(other, via) && (other == row || pressed(row, via)))
+            })
+        }
+    }
+
+    struct Line<'a>(&'a Matrix, usize);
+
+    impl OutputPin for Line<'_> {
+        type Error = Infallible;
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.0.row.set(Some(self.1));
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            if self.0.row.get() == Some(self.1) {
+                self.0.row.set(None);
+            }
+            Ok(())
+        }
+    }
+
+    impl InputPin for Line<'_> {
+        type Error = Infallible;
+
+        fn is_high(&self) -> Result<bool, Self::Error> {
+            self.is_low().map(|low| !low)
+        }
+
+        fn is_low(&self) -> Result<bool, Self::Error> {
+            Ok(self.0.is_low(self.1))
+        }
+    }
+
+    impl DelayUs for Line<'_> {
+        type Error = Infallible;
+
+        fn delay_us(&mut self, _us: u32) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn debounced_events_and_ghosting() {
+        let matrix = Matrix {
+            keys: Cell::new(0),
+            row: Cell::new(None),
+        };
+        let line = |n| Line(&matrix, n);
+        let mut rows = [line(0), line(1), line(2), line(3)];
+        let columns = [line(0), line(1), line(2)];
+        let mut keypad = Keypad::new(&mut rows[..], &columns[..], line(0)).unwrap();
+        let key = |row, column| Key { row, column };
+
+        matrix.press(1, 2);
+        matrix.press(3, 0);
+        assert_eq!(keypad.poll(), Ok(None));
+        assert_eq!(keypad.poll(), Ok(None));
+        assert_eq!(keypad.poll(), Ok(Some(Event::Pressed(key(1, 2)))));
+        assert_eq!(keypad.poll(), Ok(Some(Event::Pressed(key(3, 0)))));
+        assert_eq!(keypad.poll(), Ok(None));
+        assert!(keypad.is_pressed(key(1, 2)));
+        assert_eq!(matrix.row.get(), None);
+
+        // Pressing a third corner makes the fourth one look pressed
+        matrix.press(1, 0);
+        for _ in 0..3 {
+            assert_eq!(keypad.poll(), Ok(None));
+            assert!(keypad.is_ghosting());
+        }
+        matrix.keys.set(0);
+        for _ in 0..2 {
+            assert_eq!(keypad.poll(), Ok(None));
+        }
+        assert!(!keypad.is_ghosting());
+        assert_eq!(keypad.poll(), Ok(Some(Event::Released(key(1, 2)))));
+        assert_eq!(keypad.poll(), Ok(Some(Event::Released(key(3, 0)))));
+    }
+
+    /// Row or column lines which are always high
+    struct Lines(usize);
+
+    impl Rows for Lines {
+        type Error = Infallible;
+
+        fn count(&self) -> usize {
+            self.0
+        }
+
+        fn select(&mut self, _row: Option<usize>) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    impl Columns for Lines {
+        type Error = Infallible;
+
+        fn count(&self) -> usize {
+            self.0
+        }
+
+        fn read(&self) -> Result<u32, Self::Error> {
+            Ok(0)
+        }
+    }
+
+    /// Port without any pins
+    struct EmptyPort;
+
+    impl InputPort for EmptyPort {
+        type Error = Infallible;
+
+        const WIDTH: u32 = 0;
+
+        fn read(&self) -> Result<u32, Self::Error> {
+            Ok(0)
+        }
+    }
+
+    #[test]
+    fn matrix_sizes() {
+        assert!(Keypad::new(Lines(8), Lines(8), ()).is_ok());
+        assert!(matches!(
+            Keypad::new(Lines(5), Lines(13), ()),
+            Err(TooManyKeys)
+        ));
+
+        let keypad = Keypad::new(Lines(4), EmptyPort, ()).unwrap();
+        assert_eq!(Columns::read(&keypad.columns), Ok(0));
+    }
+}
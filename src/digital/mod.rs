@@ -0,0 +1,762 @@
+//! Digital I/O
+
+#[cfg(feature = "async")]
+pub mod button;
+pub mod debounce;
+pub mod encoder;
+pub mod erased;
+pub mod interrupt;
+pub mod inverted;
+pub mod keypad;
+pub mod readback;
+#[cfg(feature = "critical-section")]
+pub mod split;
+#[cfg(feature = "async")]
+pub mod timeout;
+
+use core::{convert::From, ops::Not};
+
+/// Digital I/O error
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::digital::{Error, ErrorKind};
+/// use embedded_hal::i2c;
+///
+/// /// Error of a pin of an I2C I/O expander
+/// #[derive(Debug)]
+/// pub enum ExpanderError<E> {
+///     I2c(E),
+///     PinIsInput,
+/// }
+///
+/// impl<E: i2c::Error> Error for ExpanderError<E> {
+///     fn kind(&self) -> ErrorKind {
+///         match self {
+///             Self::I2c(e) => match e.kind() {
+///                 i2c::ErrorKind::NoAcknowledge(_) => ErrorKind::Disconnected,
+///                 _ => ErrorKind::Bus,
+///             },
+///             Self::PinIsInput => ErrorKind::InvalidState,
+///         }
+///     }
+/// }
+/// ```
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic digital I/O error kind
+    ///
+    /// By using this method, digital I/O errors freely defined by HAL implementations
+    /// can be converted to a set of generic digital I/O errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// Digital I/O error kind
+///
+/// This represents a common set of digital I/O operation errors. Pins of MCUs usually can't
+/// fail, but pins of I/O expanders and other remote devices can. HAL implementations are free
+/// to define more specific or additional error types. However, by providing a mapping to these
+/// common errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Communication with the device providing the pin failed, e.g. an I2C or SPI transfer to
+    /// an I/O expander
+    Bus,
+    /// The device providing the pin didn't respond, e.g. because it was removed or powered down
+    Disconnected,
+    /// The pin isn't in a state allowing the operation, e.g. an output operation on a pin
+    /// configured as input
+    InvalidState,
+    /// A different error occurred. The original error may contain more information.
+    Other,
+}
+
+impl Error for ErrorKind {
+    fn kind(&self) -> ErrorKind {
+        *self
+    }
+}
+
+impl core::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Bus => write!(f, "Communication with the device providing the pin failed"),
+            Self::Disconnected => write!(f, "The device providing the pin didn't respond"),
+            Self::InvalidState => write!(f, "The pin isn't in a state allowing the operation"),
+            Self::Other => write!(
+                f,
+                "A different error occurred. The original error may contain more information"
+            ),
+        }
+    }
+}
+
+/// Digital output pin state
+///
+/// Conversions from and to `bool` and logical negation are also implemented
+/// for this type.
+/// ```rust
+/// # use embedded_hal::digital::PinState;
+/// let state = PinState::from(false);
+/// assert_eq!(state, PinState::Low);
+/// assert_eq!(!state, PinState::High);
+/// assert!(bool::from(!state));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PinState {
+    /// Low pin state
+    Low,
+    /// High pin state
+    High,
+}
+
+impl From<bool> for PinState {
+    fn from(value: bool) -> Self {
+        match value {
+            false => PinState::Low,
+            true => PinState::High,
+        }
+    }
+}
+
+impl From<PinState> for bool {
+    fn from(value: PinState) -> bool {
+        match value {
+            PinState::Low => false,
+            PinState::High => true,
+        }
+    }
+}
+
+impl Not for PinState {
+    type Output = PinState;
+
+    fn not(self) -> Self::Output {
+        match self {
+            PinState::High => PinState::Low,
+            PinState::Low => PinState::High,
+        }
+    }
+}
+
+/// Internal pull resistor of an input pin
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Pull {
+    /// No pull resistor, the pin is floating unless driven externally
+    None,
+    /// Pull-up resistor
+    Up,
+    /// Pull-down resistor
+    Down,
+}
+
+impl Default for Pull {
+    /// No pull resistor
+    fn default() -> Self {
+        Pull::None
+    }
+}
+
+/// Output driver configuration of a pin
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum DriveMode {
+    /// The pin actively drives both levels
+    PushPull,
+    /// The pin only drives the low level and floats otherwise, so a pull-up sets the high
+    /// level and several pins can share a line
+    OpenDrain,
+}
+
+impl Default for DriveMode {
+    /// Push-pull
+    fn default() -> Self {
+        DriveMode::PushPull
+    }
+}
+
+/// Low-power state of a pin not in use
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum ParkState {
+    /// The pin neither drives the line nor pulls it, but its input buffer stays enabled
+    HighImpedance,
+    /// The pin is connected to the analog peripherals with its digital input buffer disabled,
+    /// which avoids the leakage current of a floating input
+    Analog,
+    /// The pin is disconnected from all peripherals, with the lowest power consumption the
+    /// hardware supports
+    Disabled,
+}
+
+/// Placeholder for an unconnected pin
+///
+/// Drivers with optional pins, e.g. a reset line, can be instantiated with `NoPin` if the pin
+/// isn't connected. Setting its state does nothing and it reads as low.
+///
+/// ```
+/// use embedded_hal::digital::blocking::OutputPin;
+/// use embedded_hal::digital::NoPin;
+///
+/// /// Display driver with an optional reset pin
+/// struct Display<RST> {
+///     reset: RST,
+/// }
+///
+/// impl<RST: OutputPin> Display<RST> {
+///     pub fn reset(&mut self) -> Result<(), RST::Error> {
+///         self.reset.set_low()?;
+///         // ... wait for the display to reset ...
+///         self.reset.set_high()
+///     }
+/// }
+///
+/// let mut display = Display { reset: NoPin };
+/// display.reset().unwrap();
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct NoPin;
+
+impl blocking::OutputPin for NoPin {
+    type Error = core::convert::Infallible;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl blocking::InputPin for NoPin {
+    type Error = core::convert::Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        Ok(false)
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        Ok(true)
+    }
+}
+
+/// Blocking digital I/O traits
+pub mod blocking {
+    use super::{DriveMode, Error, ParkState, PinState, Pull};
+
+    /// Single digital push-pull output pin
+    pub trait OutputPin {
+        /// Error type
+        type Error: Error;
+
+        /// Drives the pin low
+        ///
+        /// *NOTE* the actual electrical state of the pin may not actually be low, e.g. due to external
+        /// electrical sources
+        fn set_low(&mut self) -> Result<(), Self::Error>;
+
+        /// Drives the pin high
+        ///
+        /// *NOTE* the actual electrical state of the pin may not actually be high, e.g. due to external
+        /// electrical sources
+        fn set_high(&mut self) -> Result<(), Self::Error>;
+
+        /// Drives the pin high or low depending on the provided value
+        ///
+        /// *NOTE* the actual electrical state of the pin may not actually be high or low, e.g. due to external
+        /// electrical sources
+        fn set_state(&mut self, state: PinState) -> Result<(), Self::Error> {
+            match state {
+                PinState::Low => self.set_low(),
+                PinState::High => self.set_high(),
+            }
+        }
+    }
+
+    impl<T: OutputPin> OutputPin for &mut T {
+        type Error = T::Error;
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            T::set_low(self)
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            T::set_high(self)
+        }
+
+        fn set_state(&mut self, state: PinState) -> Result<(), Self::Error> {
+            T::set_state(self, state)
+        }
+    }
+
+    /// Push-pull output pin that can read its output state
+    pub trait StatefulOutputPin: OutputPin {
+        /// Is the pin in drive high mode?
+        ///
+        /// *NOTE* this does *not* read the electrical state of the pin
+        fn is_set_high(&self) -> Result<bool, Self::Error>;
+
+        /// Is the pin in drive low mode?
+        ///
+        /// *NOTE* this does *not* read the electrical state of the pin
+        fn is_set_low(&self) -> Result<bool, Self::Error>;
+
+        /// Toggles the pin, driving it low if it is in drive high mode and high otherwise
+        ///
+        /// Implementations with a hardware toggle mechanism can override this method.
+        ///
+        /// ```
+        /// use embedded_hal::digital::blocking::StatefulOutputPin;
+        ///
+        /// /// Blinks an LED `n` times, calling `wait` between the changes
+        /// fn blink<P: StatefulOutputPin>(led: &mut P, n: u32, mut wait: impl FnMut()) -> Result<(), P::Error> {
+        ///     for _ in 0..2 * n {
+        ///         led.toggle()?;
+        ///         wait();
+        ///     }
+        ///     Ok(())
+        /// }
+        /// ```
+        fn toggle(&mut self) -> Result<(), Self::Error> {
+            if self.is_set_high()? {
+                self.set_low()
+            } else {
+                self.set_high()
+            }
+        }
+    }
+
+    impl<T: StatefulOutputPin> StatefulOutputPin for &mut T {
+        fn is_set_high(&self) -> Result<bool, Self::Error> {
+            T::is_set_high(self)
+        }
+
+        fn is_set_low(&self) -> Result<bool, Self::Error> {
+            T::is_set_low(self)
+        }
+
+        fn toggle(&mut self) -> Result<(), Self::Error> {
+            T::toggle(self)
+        }
+    }
+
+    /// Output pin that can be toggled
+    ///
+    /// Pins implementing [`StatefulOutputPin`] can be toggled with
+    /// [`StatefulOutputPin::toggle`] instead. Implement this trait for pins which can only be
+    /// toggled using hardware mechanisms.
+    pub trait ToggleableOutputPin {
+        /// Error type
+        type Error: Error;
+
+        /// Toggle pin output.
+        fn toggle(&mut self) -> Result<(), Self::Error>;
+    }
+
+    impl<T: ToggleableOutputPin> ToggleableOutputPin for &mut T {
+        type Error = T::Error;
+
+        fn toggle(&mut self) -> Result<(), Self::Error> {
+            T::toggle(self)
+        }
+    }
+
+    /// Single digital input pin
+    pub trait InputPin {
+        /// Error type
+        type Error: Error;
+
+        /// Is the input pin high?
+        fn is_high(&self) -> Result<bool, Self::Error>;
+
+        /// Is the input pin low?
+        fn is_low(&self) -> Result<bool, Self::Error>;
+    }
+
+    impl<T: InputPin> InputPin for &T {
+        type Error = T::Error;
+
+        fn is_high(&self) -> Result<bool, Self::Error> {
+            T::is_high(self)
+        }
+
+        fn is_low(&self) -> Result<bool, Self::Error> {
+            T::is_low(self)
+        }
+    }
+
+    /// Group of output pins written in one operation
+    ///
+    /// Bit `n` of a word is the state of pin `n` of the group, with `1` driving the pin high.
+    /// Drivers of parallel buses, e.g. for character LCDs, write all data lines at once
+    /// instead of setting each pin in turn.
+    ///
+    /// ```
+    /// use embedded_hal::digital::blocking::{OutputPin, OutputPort};
+    ///
+    /// /// Writes a byte to an 8-bit parallel bus, latched by the falling edge of `strobe`
+    /// fn write_byte<D: OutputPort, S: OutputPin<Error = D::Error>>(
+    ///     data: &mut D,
+    ///     strobe: &mut S,
+    ///     byte: u8,
+    /// ) -> Result<(), D::Error> {
+    ///     assert!(D::WIDTH >= 8);
+    ///     data.write(u32::from(byte))?;
+    ///     strobe.set_high()?;
+    ///     strobe.set_low()
+    /// }
+    /// ```
+    pub trait OutputPort {
+        /// Error type
+        type Error: Error;
+
+        /// Number of pins in the group
+        const WIDTH: u32;
+
+        /// Drives all pins of the group according to `word`
+        ///
+        /// Bits above [`WIDTH`](Self::WIDTH) are ignored. Implementations should change all
+        /// pins at the same time, or document in which order they change.
+        fn write(&mut self, word: u32) -> Result<(), Self::Error>;
+    }
+
+    impl<T: OutputPort> OutputPort for &mut T {
+        type Error = T::Error;
+
+        const WIDTH: u32 = T::WIDTH;
+
+        fn write(&mut self, word: u32) -> Result<(), Self::Error> {
+            T::write(self, word)
+        }
+    }
+
+    /// Group of input pins read in one operation
+    ///
+    /// Bit `n` of the returned word is the state of pin `n` of the group, with `1` meaning
+    /// high, e.g. the position of switch `n` of a DIP switch.
+    pub trait InputPort {
+        /// Error type
+        type Error: Error;
+
+        /// Number of pins in the group
+        const WIDTH: u32;
+
+        /// Reads all pins of the group at the same time
+        ///
+        /// Bits above [`WIDTH`](Self::WIDTH) are zero.
+        fn read(&self) -> Result<u32, Self::Error>;
+    }
+
+    impl<T: InputPort> InputPort for &T {
+        type Error = T::Error;
+
+        const WIDTH: u32 = T::WIDTH;
+
+        fn read(&self) -> Result<u32, Self::Error> {
+            T::read(self)
+        }
+    }
+
+    /// Pin with configurable internal pull resistor
+    ///
+    /// Drivers requiring a pull resistor, e.g. for a button or an open-drain interrupt line,
+    /// can enable it themselves instead of relying on the user to configure the pin.
+    ///
+    /// ```
+    /// use embedded_hal::digital::blocking::{InputPin, SetPull};
+    /// use embedded_hal::digital::Pull;
+    ///
+    /// /// Button connecting its pin to ground when pressed
+    /// struct Button<P>(P);
+    ///
+    /// impl<P: InputPin + SetPull<Error = <P as InputPin>::Error>> Button<P> {
+    ///     pub fn new(mut pin: P) -> Result<Self, <P as InputPin>::Error> {
+    ///         pin.set_pull(Pull::Up)?;
+    ///         Ok(Button(pin))
+    ///     }
+    ///
+    ///     pub fn is_pressed(&self) -> Result<bool, <P as InputPin>::Error> {
+    ///         self.0.is_low()
+    ///     }
+    /// }
+    /// ```
+    pub trait SetPull {
+        /// Error type
+        type Error: Error;
+
+        /// Configures the internal pull resistor
+        ///
+        /// Returns an error if the pin doesn't support `pull`.
+        fn set_pull(&mut self, pull: Pull) -> Result<(), Self::Error>;
+    }
+
+    impl<T: SetPull> SetPull for &mut T {
+        type Error = T::Error;
+
+        fn set_pull(&mut self, pull: Pull) -> Result<(), Self::Error> {
+            T::set_pull(self, pull)
+        }
+    }
+
+    /// Output pin whose drive mode can be changed at runtime
+    ///
+    /// Bit-banged buses switch to open-drain while other devices may drive the line, e.g. to
+    /// let a target stretch the clock, and back to push-pull for faster edges.
+    ///
+    /// ```
+    /// use embedded_hal::digital::blocking::{OutputPin, SetDriveMode};
+    /// use embedded_hal::digital::DriveMode;
+    ///
+    /// /// Releases a line shared with other devices
+    /// fn release<P>(line: &mut P) -> Result<(), <P as OutputPin>::Error>
+    /// where
+    ///     P: OutputPin + SetDriveMode<Error = <P as OutputPin>::Error>,
+    /// {
+    ///     line.set_drive_mode(DriveMode::OpenDrain)?;
+    ///     line.set_high()
+    /// }
+    /// ```
+    pub trait SetDriveMode {
+        /// Error type
+        type Error: Error;
+
+        /// Sets the drive mode, keeping the output state
+        ///
+        /// Returns an error if the pin doesn't support `mode`.
+        fn set_drive_mode(&mut self, mode: DriveMode) -> Result<(), Self::Error>;
+    }
+
+    impl<T: SetDriveMode> SetDriveMode for &mut T {
+        type Error = T::Error;
+
+        fn set_drive_mode(&mut self, mode: DriveMode) -> Result<(), Self::Error> {
+            T::set_drive_mode(self, mode)
+        }
+    }
+
+    /// Pin which can be parked in a low-power state between uses
+    ///
+    /// Low-power firmware parks unused pins, and pins of buses shared with devices which are
+    /// powered down, so that they neither drive the line nor draw current through their input
+    /// buffers.
+    ///
+    /// ```
+    /// use embedded_hal::digital::blocking::Park;
+    /// use embedded_hal::digital::ParkState;
+    ///
+    /// /// Parks the pins of a sensor while it is powered down, calling `sleep` in between
+    /// fn sleep_with<P: Park>(pins: &mut [P], sleep: impl FnOnce()) -> Result<(), P::Error> {
+    ///     for pin in pins.iter_mut() {
+    ///         pin.park(ParkState::Analog)?;
+    ///     }
+    ///     sleep();
+    ///     for pin in pins.iter_mut() {
+    ///         pin.restore()?;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub trait Park {
+        /// Error type
+        type Error: Error;
+
+        /// Puts the pin in `state`, remembering its current configuration
+        ///
+        /// Returns an error if the pin doesn't support `state`. Parking a parked pin changes
+        /// its state but keeps the configuration remembered when it was first parked.
+        fn park(&mut self, state: ParkState) -> Result<(), Self::Error>;
+
+        /// Restores the configuration the pin had before it was parked
+        ///
+        /// Output pins drive the state they had before they were parked. Does nothing if the
+        /// pin isn't parked.
+        fn restore(&mut self) -> Result<(), Self::Error>;
+    }
+
+    impl<T: Park> Park for &mut T {
+        type Error = T::Error;
+
+        fn park(&mut self, state: ParkState) -> Result<(), Self::Error> {
+            T::park(self, state)
+        }
+
+        fn restore(&mut self) -> Result<(), Self::Error> {
+            T::restore(self)
+        }
+    }
+
+    /// Counting of edges on an input pin
+    ///
+    /// Flow meters, tachometers and energy meters output a pulse per unit of flow, rotation or
+    /// energy. Implementations count the edges with a hardware counter, e.g. a timer clocked by
+    /// the pin, or in software from the pin's interrupt. Which edges are counted is up to the
+    /// implementation, usually configurable when creating it.
+    ///
+    /// ```
+    /// use embedded_hal::digital::blocking::EdgeCounter;
+    ///
+    /// /// Returns the flow in millilitres since the last call for a sensor with 450 pulses per
+    /// /// litre
+    /// fn flow<C: EdgeCounter>(sensor: &mut C) -> Result<u32, C::Error> {
+    ///     let pulses = sensor.count()?;
+    ///     sensor.reset()?;
+    ///     Ok(pulses * 1000 / 450)
+    /// }
+    /// ```
+    pub trait EdgeCounter {
+        /// Error type
+        type Error: Error;
+
+        /// Returns the number of edges counted since the last reset
+        ///
+        /// The count wraps around on overflow. Hardware counters narrower than 32 bits wrap
+        /// around earlier, so implementations should document their width.
+        fn count(&mut self) -> Result<u32, Self::Error>;
+
+        /// Resets the count to zero
+        ///
+        /// Edges between a call to [`count`](EdgeCounter::count) and this call are lost. To
+        /// avoid this, keep counting and compute the difference of two counts with
+        /// `wrapping_sub` instead.
+        fn reset(&mut self) -> Result<(), Self::Error>;
+    }
+
+    impl<T: EdgeCounter> EdgeCounter for &mut T {
+        type Error = T::Error;
+
+        fn count(&mut self) -> Result<u32, Self::Error> {
+            T::count(self)
+        }
+
+        fn reset(&mut self) -> Result<(), Self::Error> {
+            T::reset(self)
+        }
+    }
+
+    /// Single pin that can switch from input to output mode, and vice-versa.
+    ///
+    /// Example use (assumes the `Error` type is the same for the `IoPin`,
+    /// `InputPin`, and `OutputPin`):
+    ///
+    /// ```
+    /// use core::time::Duration;
+    /// use embedded_hal::digital::blocking::{IoPin, InputPin, OutputPin};
+    ///
+    /// pub fn ping_and_read<TInputPin, TOutputPin, TError: embedded_hal::digital::Error>(
+    ///     mut pin: TOutputPin, delay_fn: &dyn Fn(Duration) -> ()) -> Result<bool, TError>
+    /// where
+    ///     TInputPin : InputPin<Error = TError> + IoPin<TInputPin, TOutputPin, Error = TError>,
+    ///     TOutputPin : OutputPin<Error = TError> + IoPin<TInputPin, TOutputPin, Error = TError>,
+    /// {
+    ///     // Ping
+    ///     pin.set_low()?;
+    ///     delay_fn(Duration::from_millis(10));
+    ///     pin.set_high()?;
+    ///
+    ///     // Read
+    ///     let pin = pin.into_input_pin()?;
+    ///     delay_fn(Duration::from_millis(10));
+    ///     pin.is_high()
+    /// }
+    /// ```
+    pub trait IoPin<TInput, TOutput>
+    where
+        TInput: InputPin + IoPin<TInput, TOutput>,
+        TOutput: OutputPin + IoPin<TInput, TOutput>,
+    {
+        /// Error type.
+        type Error: Error;
+
+        /// Tries to convert this pin to input mode.
+        ///
+        /// If the pin is already in input mode, this method should succeed.
+        fn into_input_pin(self) -> Result<TInput, Self::Error>;
+
+        /// Tries to convert this pin to output mode with the given initial state.
+        ///
+        /// If the pin is already in the requested state, this method should
+        /// succeed.
+        fn into_output_pin(self, state: PinState) -> Result<TOutput, Self::Error>;
+    }
+}
+
+/// Async digital I/O traits
+///
+/// Only available with the `async` feature.
+#[cfg(feature = "async")]
+pub mod asynch {
+    /// Waiting for the state of an input pin
+    ///
+    /// Implementations typically use the pin's interrupt, so the executor can run other tasks
+    /// or sleep while waiting, e.g. for a sensor's data ready line.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use embedded_hal::digital::asynch::Wait;
+    ///
+    /// /// Counts pulses until `done` returns `true`
+    /// async fn count_pulses<P: Wait>(pin: &mut P, mut done: impl FnMut(u32) -> bool) -> Result<u32, P::Error> {
+    ///     let mut pulses = 0;
+    ///     while !done(pulses) {
+    ///         pin.wait_for_rising_edge().await?;
+    ///         pulses += 1;
+    ///     }
+    ///     Ok(pulses)
+    /// }
+    /// ```
+    pub trait Wait {
+        /// Error type
+        type Error: super::Error;
+
+        /// Waits until the pin is high
+        ///
+        /// Returns right away if the pin is already high.
+        async fn wait_for_high(&mut self) -> Result<(), Self::Error>;
+
+        /// Waits until the pin is low
+        ///
+        /// Returns right away if the pin is already low.
+        async fn wait_for_low(&mut self) -> Result<(), Self::Error>;
+
+        /// Waits for the next transition from low to high
+        async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error>;
+
+        /// Waits for the next transition from high to low
+        async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error>;
+
+        /// Waits for the next transition in either direction
+        async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error>;
+    }
+
+    impl<T: Wait> Wait for &mut T {
+        type Error = T::Error;
+
+        async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+            T::wait_for_high(self).await
+        }
+
+        async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+            T::wait_for_low(self).await
+        }
+
+        async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+            T::wait_for_rising_edge(self).await
+        }
+
+        async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+            T::wait_for_falling_edge(self).await
+        }
+
+        async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+            T::wait_for_any_edge(self).await
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! Output pins verified by reading back the line
+//!
+//! Outputs switching contactors, relays or valves in safety-related equipment must detect a
+//! failed driver or a shorted line. [`Readback`] pairs an output pin with an input pin on the
+//! same net, e.g. the output itself read through its input buffer or a feedback contact, and
+//! checks after every change that the line has actually reached the commanded level.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::delay::blocking::DelayUs;
+//! use embedded_hal::digital::blocking::{InputPin, OutputPin};
+//! use embedded_hal::digital::readback::{Error, Readback};
+//!
+//! /// Closes a contactor whose auxiliary contact pulls the feedback pin high when closed
+//! fn close<O, I, D, E>(coil: O, feedback: I, delay: D) -> Result<(), Error<E, D::Error>>
+//! where
+//!     O: OutputPin<Error = E>,
+//!     I: InputPin<Error = E>,
+//!     D: DelayUs,
+//!     E: embedded_hal::digital::Error,
+//! {
+//!     // The contactor takes up to 50 ms to close
+//!     let mut contactor = Readback::new(coil, feedback, delay, 50_000);
+//!     contactor.set_high()
+//! }
+//! ```
+
+use super::blocking::{InputPin, OutputPin};
+use super::PinState;
+use crate::delay::blocking::DelayUs;
+
+/// Readback error
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error<P, D> {
+    /// Setting or reading a pin failed
+    Pin(P),
+    /// The delay failed
+    Delay(D),
+    /// The line didn't reach the commanded state, e.g. because the driver failed or the line
+    /// is shorted
+    Mismatch(PinState),
+}
+
+impl<P: super::Error, D: core::fmt::Debug> super::Error for Error<P, D> {
+    fn kind(&self) -> super::ErrorKind {
+        match self {
+            Self::Pin(e) => e.kind(),
+            Self::Delay(_) | Self::Mismatch(_) => super::ErrorKind::Other,
+        }
+    }
+}
+
+/// Output pin checking its state with an input pin on the same net
+#[derive(Debug)]
+pub struct Readback<O, I, D> {
+    output: O,
+    input: I,
+    delay: D,
+    settle_us: u32,
+    state: Option<PinState>,
+}
+
+impl<O, I, D> Readback<O, I, D> {
+    /// Creates a new pin, reading back the line `settle_us` microseconds after changing it
+    ///
+    /// The delay isn't used if `settle_us` is zero.
+    pub fn new(output: O, input: I, delay: D, settle_us: u32) -> Self {
+        Readback {
+            output,
+            input,
+            delay,
+            settle_us,
+            state: None,
+        }
+    }
+
+    /// Releases the pins and the delay
+    pub fn release(self) -> (O, I, D) {
+        (self.output, self.input, self.delay)
+    }
+}
+
+impl<O, I, D, E> Readback<O, I, D>
+where
+    O: OutputPin<Error = E>,
+    I: InputPin<Error = E>,
+    D: DelayUs,
+{
+    /// Checks that the line is still at the last commanded state
+    ///
+    /// Call this periodically to detect faults occurring while the output doesn't change.
+    /// Does nothing if no state has been commanded yet.
+    pub fn check(&mut self) -> Result<(), Error<E, D::Error>> {
+        let state = match self.state {
+            Some(state) => state,
+            None => return Ok(()),
+        };
+        let high = self.input.is_high().map_err(Error::Pin)?;
+        if high == bool::from(state) {
+            Ok(())
+        } else {
+            Err(Error::Mismatch(state))
+        }
+    }
+
+    fn set(&mut self, state: PinState) -> Result<(), Error<E, D::Error>> {
+        self.output.set_state(state).map_err(Error::Pin)?;
+        self.state = Some(state);
+        if self.settle_us > 0 {
+            self.delay.delay_us(self.settle_us).map_err(Error::Delay)?;
+        }
+        self.check()
+    }
+}
+
+impl<O, I, D, E> OutputPin for Readback<O, I, D>
+where
+    O: OutputPin<Error = E>,
+    I: InputPin<Error = E>,
+    D: DelayUs,
+    E: super::Error,
+{
+    type Error = Error<E, D::Error>;
+
+    /// Sets the pin low, returning [`Error::Mismatch`] if the line isn't low after the settle
+    /// time
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.set(PinState::Low)
+    }
+
+    /// Sets the pin high, returning [`Error::Mismatch`] if the line isn't high after the
+    /// settle time
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.set(PinState::High)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+    use core::convert::Infallible;
+
+    /// Line following its driver after 100 µs, unless it is stuck
+    struct Net {
+        driven: Cell<bool>,
+        changed: Cell<u32>,
+        now: Cell<u32>,
+        stuck: Cell<Option<bool>>,
+    }
+
+    struct Terminal<'a>(&'a Net);
+
+    impl OutputPin for Terminal<'_> {
+        type Error = Infallible;
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.0.driven.set(false);
+            self.0.changed.set(self.0.now.get());
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.0.driven.set(true);
+            self.0.changed.set(self.0.now.get());
+            Ok(())
+        }
+    }
+
+    impl InputPin for Terminal<'_> {
+        type Error = Infallible;
+
+        fn is_high(&self) -> Result<bool, Self::Error> {
+            let net = self.0;
+            let settled = net.now.get() >= net.changed.get() + 100;
+            Ok(net.stuck.get().unwrap_or(net.driven.get() == settled))
+        }
+
+        fn is_low(&self) -> Result<bool, Self::Error> {
+            self.is_high().map(|high| !high)
+        }
+    }
+
+    impl DelayUs for Terminal<'_> {
+        type Error = Infallible;
+
+        fn delay_us(&mut self, us: u32) -> Result<(), Self::Error> {
+            self.0.now.set(self.0.now.get() + us);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn detects_mismatches() {
+        let net = Net {
+            driven: Cell::new(false),
+            changed: Cell::new(0),
+            now: Cell::new(1000),
+            stuck: Cell::new(None),
+        };
+        let mut pin = Readback::new(Terminal(&net), Terminal(&net), Terminal(&net), 0);
+        assert_eq!(pin.check(), Ok(()));
+        assert_eq!(pin.set_high(), Err(Error::Mismatch(PinState::High)));
+
+        let mut pin = Readback::new(Terminal(&net), Terminal(&net), Terminal(&net), 150);
+        assert_eq!(pin.set_high(), Ok(()));
+        assert_eq!(pin.set_low(), Ok(()));
+        net.stuck.set(Some(true));
+        assert_eq!(pin.check(), Err(Error::Mismatch(PinState::Low)));
+    }
+}
@@ -0,0 +1,143 @@
+//! Pins of a port register shared with critical sections
+//!
+//! Some microcontrollers only have a single output data register per port, without
+//! registers for setting or clearing individual pins. Changing a pin then takes a
+//! read-modify-write of the whole register, which must not be interrupted by a change of
+//! another pin of the same port. [`SplitPort`] wraps such a register behind the small
+//! [`PortRegister`] trait and hands out a [`PortPin`] per pin, whose changes are done in a
+//! critical section from the [`critical-section`] crate.
+//!
+//! Only available with the `critical-section` feature.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::digital::blocking::OutputPin;
+//! use embedded_hal::digital::split::{PortRegister, SplitPort};
+//!
+//! /// Drives a status LED on pin 3 and a buzzer on pin 5 of the same port
+//! fn alarm<R: PortRegister>(port: &SplitPort<R>) {
+//!     let mut led = port.pin(3).unwrap();
+//!     let mut buzzer = port.pin(5).unwrap();
+//!     led.set_high().unwrap();
+//!     buzzer.set_high().unwrap();
+//! }
+//! ```
+//!
+//! [`critical-section`]: https://crates.io/crates/critical-section
+
+use core::cell::RefCell;
+use core::convert::Infallible;
+
+use super::blocking::{OutputPin, StatefulOutputPin, ToggleableOutputPin};
+
+/// Output data register of a port
+pub trait PortRegister {
+    /// Returns the output states of the pins, with bit `n` set if pin `n` is driven high
+    fn read(&mut self) -> u32;
+
+    /// Sets the output states of all pins
+    fn write(&mut self, value: u32);
+}
+
+#[derive(Debug)]
+struct State<R> {
+    register: R,
+    /// Pins handed out
+    taken: u32,
+}
+
+/// Port register split into individually owned pins
+#[derive(Debug)]
+pub struct SplitPort<R> {
+    state: critical_section::Mutex<RefCell<State<R>>>,
+}
+
+impl<R: PortRegister> SplitPort<R> {
+    /// Creates a new port from its output data register
+    pub const fn new(register: R) -> Self {
+        SplitPort {
+            state: critical_section::Mutex::new(RefCell::new(State { register, taken: 0 })),
+        }
+    }
+
+    /// Returns pin `n`, or `None` if it has already been taken or if `n` is larger than 31
+    pub fn pin(&self, n: u32) -> Option<PortPin<'_, R>> {
+        let mask = 1u32.checked_shl(n)?;
+        critical_section::with(|cs| {
+            let mut state = self.state.borrow_ref_mut(cs);
+            if state.taken & mask != 0 {
+                return None;
+            }
+            state.taken |= mask;
+            Some(PortPin { port: self, mask })
+        })
+    }
+
+    fn modify(&self, f: impl FnOnce(u32) -> u32) {
+        critical_section::with(|cs| {
+            let register = &mut self.state.borrow_ref_mut(cs).register;
+            let value = register.read();
+            register.write(f(value));
+        })
+    }
+
+    fn read(&self) -> u32 {
+        critical_section::with(|cs| self.state.borrow_ref_mut(cs).register.read())
+    }
+}
+
+/// Pin of a [`SplitPort`]
+///
+/// Dropping the pin returns it to the port.
+#[derive(Debug)]
+pub struct PortPin<'a, R: PortRegister> {
+    port: &'a SplitPort<R>,
+    mask: u32,
+}
+
+impl<R: PortRegister> Drop for PortPin<'_, R> {
+    fn drop(&mut self) {
+        critical_section::with(|cs| self.port.state.borrow_ref_mut(cs).taken &= !self.mask);
+    }
+}
+
+impl<R: PortRegister> OutputPin for PortPin<'_, R> {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        let mask = self.mask;
+        self.port.modify(|value| value & !mask);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        let mask = self.mask;
+        self.port.modify(|value| value | mask);
+        Ok(())
+    }
+}
+
+impl<R: PortRegister> StatefulOutputPin for PortPin<'_, R> {
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.port.read() & self.mask != 0)
+    }
+
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        Ok(self.port.read() & self.mask == 0)
+    }
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        let mask = self.mask;
+        self.port.modify(|value| value ^ mask);
+        Ok(())
+    }
+}
+
+impl<R: PortRegister> ToggleableOutputPin for PortPin<'_, R> {
+    type Error = Infallible;
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        StatefulOutputPin::toggle(self)
+    }
+}
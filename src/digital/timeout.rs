@@ -0,0 +1,138 @@
+//! Waiting for pins with a timeout
+//!
+//! Drivers waiting for a handshake line, e.g. the busy or data ready pin of a device, must
+//! not hang if the device doesn't respond. [`with_timeout`] races a [`Wait`] future against
+//! an async delay and returns which one completed first.
+//!
+//! Only available with the `async` feature.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::delay::asynch::DelayUs;
+//! use embedded_hal::digital::asynch::Wait;
+//! use embedded_hal::digital::timeout::{with_timeout, Error, Outcome};
+//!
+//! /// Waits up to 100 ms for a display controller to release its busy line
+//! async fn wait_until_idle<P: Wait, D: DelayUs>(
+//!     busy: &mut P,
+//!     delay: &mut D,
+//! ) -> Result<bool, Error<P::Error, D::Error>> {
+//!     let outcome = with_timeout(busy.wait_for_low(), delay, 100_000).await?;
+//!     Ok(outcome == Outcome::Ready)
+//! }
+//! ```
+//!
+//! [`Wait`]: super::asynch::Wait
+
+use core::future::{poll_fn, Future};
+use core::pin::pin;
+use core::task::Poll;
+
+use crate::delay::asynch::DelayUs;
+
+/// Error of [`with_timeout`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error<P, D> {
+    /// Waiting for the pin failed
+    Pin(P),
+    /// The delay failed
+    Delay(D),
+}
+
+impl<P: super::Error, D: core::fmt::Debug> super::Error for Error<P, D> {
+    fn kind(&self) -> super::ErrorKind {
+        match self {
+            Self::Pin(e) => e.kind(),
+            Self::Delay(_) => super::ErrorKind::Other,
+        }
+    }
+}
+
+/// Which of a wait and its timeout completed first
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Outcome {
+    /// The pin reached the level or edge it was waited for
+    Ready,
+    /// The timeout elapsed first
+    Timeout,
+}
+
+/// Waits for `wait` to complete, at most `timeout_us` microseconds
+///
+/// `wait` is usually a method of [`Wait`](super::asynch::Wait). If both complete in the same
+/// poll, the wait wins. The other future is dropped, so waits must be cancellable, which
+/// the contract of `Wait` guarantees.
+pub async fn with_timeout<F, E, D>(
+    wait: F,
+    delay: &mut D,
+    timeout_us: u32,
+) -> Result<Outcome, Error<E, D::Error>>
+where
+    F: Future<Output = Result<(), E>>,
+    D: DelayUs,
+{
+    let mut wait = pin!(wait);
+    let mut timeout = pin!(delay.delay_us(timeout_us));
+    poll_fn(|cx| {
+        if let Poll::Ready(result) = wait.as_mut().poll(cx) {
+            return Poll::Ready(result.map(|()| Outcome::Ready).map_err(Error::Pin));
+        }
+        timeout
+            .as_mut()
+            .poll(cx)
+            .map(|result| result.map(|()| Outcome::Timeout).map_err(Error::Delay))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::{BlockOn, Spin};
+    use core::cell::Cell;
+    use core::convert::Infallible;
+
+    /// Clock advancing by 1 µs per poll of a pending delay
+    struct Clock<'a>(&'a Cell<u32>);
+
+    impl DelayUs for Clock<'_> {
+        type Error = Infallible;
+
+        async fn delay_us(&mut self, us: u32) -> Result<(), Self::Error> {
+            let end = self.0.get() + us;
+            poll_fn(|_| {
+                if self.0.get() >= end {
+                    return Poll::Ready(Ok(()));
+                }
+                self.0.set(self.0.get() + 1);
+                Poll::Pending
+            })
+            .await
+        }
+    }
+
+    /// Waits until the clock reaches `time`
+    async fn until(now: &Cell<u32>, time: u32) -> Result<(), Infallible> {
+        poll_fn(|_| {
+            if now.get() >= time {
+                Poll::Ready(Ok(()))
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    #[test]
+    fn races_wait_against_delay() {
+        let now = Cell::new(0);
+        let mut clock = Clock(&now);
+        let outcome = Spin.block_on(with_timeout(until(&now, 50), &mut clock, 100));
+        assert_eq!(outcome, Ok(Outcome::Ready));
+        assert_eq!(now.get(), 50);
+        let outcome = Spin.block_on(with_timeout(until(&now, 500), &mut clock, 100));
+        assert_eq!(outcome, Ok(Outcome::Timeout));
+        assert_eq!(now.get(), 150);
+    }
+}
@@ -0,0 +1,97 @@
+//! Buffer ownership for DMA transfers
+//!
+//! A DMA transfer keeps accessing its buffer after the function which started it has
+//! returned, so the buffer must not be moved, freed or used by anybody else until
+//! the transfer is complete. Borrowed slices cannot express this when the transfer
+//! is completed from an interrupt handler.
+//!
+//! Traits which hand buffers to DMA-based implementations therefore take ownership
+//! of a buffer implementing [`ReadBuffer`] or [`WriteBuffer`] and return it when the
+//! transfer is complete. These traits are implemented for `'static` slices, and can
+//! be implemented for other stable, owned buffers (e.g. pool-allocated boxes).
+//!
+//! Blocking DMA traits start the transfer and return a [`TransferHandle`] owning the
+//! buffers, so the caller can do other work while the DMA controller moves the data.
+
+/// Buffer a DMA transfer reads from
+///
+/// # Safety
+///
+/// The pointer and length returned by `read_buffer` must describe a valid memory
+/// region which does not move and is not freed while `self` is alive, even if `self`
+/// is moved.
+pub unsafe trait ReadBuffer {
+    /// Word type of the buffer
+    type Word;
+
+    /// Returns the address and the length in words of the buffer
+    ///
+    /// # Safety
+    ///
+    /// The memory region must not be written through the returned pointer.
+    unsafe fn read_buffer(&self) -> (*const Self::Word, usize);
+}
+
+/// Buffer a DMA transfer writes to
+///
+/// # Safety
+///
+/// The pointer and length returned by `write_buffer` must describe a valid memory
+/// region which does not move and is not freed while `self` is alive, even if `self`
+/// is moved, and which is not accessed through anything but `self`.
+pub unsafe trait WriteBuffer {
+    /// Word type of the buffer
+    type Word;
+
+    /// Returns the address and the length in words of the buffer
+    ///
+    /// # Safety
+    ///
+    /// Only valid values of `Word` may be written to the memory region.
+    unsafe fn write_buffer(&mut self) -> (*mut Self::Word, usize);
+}
+
+/// DMA transfer in progress, owning its buffers
+///
+/// Dropping a handle before the transfer is complete must abort the transfer or wait for
+/// its completion, as the buffers are released when the handle is dropped.
+pub trait TransferHandle {
+    /// Buffers of the transfer, returned once it is complete
+    type Buffers;
+
+    /// Error type
+    type Error;
+
+    /// Returns whether the transfer is complete, i.e. [`wait`](TransferHandle::wait) returns
+    /// without blocking
+    fn is_done(&self) -> bool;
+
+    /// Blocks until the transfer is complete and returns the buffers
+    ///
+    /// If the transfer fails, the buffers are returned along with the error.
+    fn wait(self) -> Result<Self::Buffers, (Self::Error, Self::Buffers)>;
+}
+
+unsafe impl<W> ReadBuffer for &'static [W] {
+    type Word = W;
+
+    unsafe fn read_buffer(&self) -> (*const W, usize) {
+        (self.as_ptr(), self.len())
+    }
+}
+
+unsafe impl<W> ReadBuffer for &'static mut [W] {
+    type Word = W;
+
+    unsafe fn read_buffer(&self) -> (*const W, usize) {
+        (self.as_ptr(), self.len())
+    }
+}
+
+unsafe impl<W> WriteBuffer for &'static mut [W] {
+    type Word = W;
+
+    unsafe fn write_buffer(&mut self) -> (*mut W, usize) {
+        (self.as_mut_ptr(), self.len())
+    }
+}
@@ -1,8 +1,26 @@
-//! Implementation of `core::fmt::Write` for the HAL's `serial::Write`.
+//! Implementations of `core::fmt::Write` for the HAL's serial writers.
 //!
-//! TODO write example of usage
+//! [`FmtWrite`] wraps a blocking serial writer, so `write!` and `writeln!` can target it:
+//!
+//! ```
+//! use core::fmt::Write as _;
+//! use embedded_hal::fmt::FmtWrite;
+//! use embedded_hal::serial::blocking::Write;
+//!
+//! /// Prints a measurement, returning the error of the serial port if printing failed
+//! fn print<S: Write>(serial: S, temperature: i16) -> Result<(), Option<S::Error>> {
+//!     let mut out = FmtWrite::new(serial);
+//!     writeln!(out, "temperature: {} C", temperature).map_err(|_| out.take_error())
+//! }
+//! ```
+//!
+//! With the `ufmt` feature, [`FmtWrite`] also implements `uWrite`, so the smaller `ufmt`
+//! macros can target it. As `uWrite` has an associated error type, the error of the serial
+//! writer is returned directly instead of being stored.
 use core::fmt::{Result, Write};
 
+use crate::serial::blocking;
+
 impl<Word, Error: crate::serial::Error> Write
     for dyn crate::serial::nb::Write<Word, Error = Error> + '_
 where
@@ -16,3 +34,154 @@ where
         Ok(())
     }
 }
+
+/// Handling of errors in the middle of a formatted write
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Stops writing and fails the formatted write
+    Abort,
+    /// Skips the rest of the string which failed and continues with the next one
+    ///
+    /// Useful for log output which should continue even if parts of it are lost.
+    Skip,
+}
+
+/// `core::fmt::Write` implementation for a blocking serial writer
+///
+/// As `core::fmt::Error` can't carry the error of the writer, the first error is stored and can
+/// be retrieved with [`take_error`](FmtWrite::take_error).
+#[derive(Debug)]
+pub struct FmtWrite<S: blocking::Write> {
+    serial: S,
+    policy: ErrorPolicy,
+    error: Option<S::Error>,
+}
+
+impl<S: blocking::Write> FmtWrite<S> {
+    /// Creates a new writer which aborts on the first error
+    pub fn new(serial: S) -> Self {
+        FmtWrite {
+            serial,
+            policy: ErrorPolicy::Abort,
+            error: None,
+        }
+    }
+
+    /// Sets the handling of errors
+    pub fn with_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the first error since the last call, if any
+    pub fn take_error(&mut self) -> Option<S::Error> {
+        self.error.take()
+    }
+
+    /// Releases the serial writer
+    pub fn release(self) -> S {
+        self.serial
+    }
+}
+
+impl<S: blocking::Write> Write for FmtWrite<S> {
+    fn write_str(&mut self, s: &str) -> Result {
+        match self.serial.write_all(s.as_bytes()) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                if self.error.is_none() {
+                    self.error = Some(e);
+                }
+                match self.policy {
+                    ErrorPolicy::Abort => Err(core::fmt::Error),
+                    ErrorPolicy::Skip => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl<S: blocking::Write> ufmt_write::uWrite for FmtWrite<S> {
+    type Error = S::Error;
+
+    fn write_str(&mut self, s: &str) -> core::result::Result<(), Self::Error> {
+        match (self.serial.write_all(s.as_bytes()), self.policy) {
+            (Err(e), ErrorPolicy::Skip) => {
+                if self.error.is_none() {
+                    self.error = Some(e);
+                }
+                Ok(())
+            }
+            (result, _) => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::ErrorKind;
+
+    /// Port failing writes of strings starting with `!`
+    struct Port {
+        sent: [u8; 16],
+        len: usize,
+    }
+
+    impl blocking::Write for Port {
+        type Error = ErrorKind;
+
+        fn write(&mut self, buffer: &[u8]) -> core::result::Result<usize, Self::Error> {
+            if buffer[0] == b'!' {
+                return Err(ErrorKind::Other);
+            }
+            self.sent[self.len..self.len + buffer.len()].copy_from_slice(buffer);
+            self.len += buffer.len();
+            Ok(buffer.len())
+        }
+
+        fn flush(&mut self) -> core::result::Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn error_policies() {
+        let port = Port {
+            sent: [0; 16],
+            len: 0,
+        };
+        let (fail, one, two) = ("!", 1, 2);
+        let mut out = FmtWrite::new(port);
+        assert!(write!(out, "{}{}{}", one, fail, two).is_err());
+        assert_eq!(out.take_error(), Some(ErrorKind::Other));
+        assert_eq!(out.take_error(), None);
+        let mut out = out.with_policy(ErrorPolicy::Skip);
+        assert!(write!(out, "{}{}{}", two, fail, one).is_ok());
+        assert_eq!(out.take_error(), Some(ErrorKind::Other));
+        let port = out.release();
+        assert_eq!(&port.sent[..port.len], b"121");
+    }
+
+    #[cfg(feature = "ufmt")]
+    #[test]
+    fn ufmt_errors() {
+        use ufmt_write::uWrite;
+
+        let port = Port {
+            sent: [0; 16],
+            len: 0,
+        };
+        let mut out = FmtWrite::new(port);
+        assert_eq!(uWrite::write_str(&mut out, "1"), Ok(()));
+        assert_eq!(uWrite::write_str(&mut out, "!"), Err(ErrorKind::Other));
+        assert_eq!(out.take_error(), None);
+        let mut out = out.with_policy(ErrorPolicy::Skip);
+        assert_eq!(uWrite::write_str(&mut out, "!"), Ok(()));
+        assert_eq!(uWrite::write_char(&mut out, '2'), Ok(()));
+        assert_eq!(out.take_error(), Some(ErrorKind::Other));
+        let port = out.release();
+        assert_eq!(&port.sent[..port.len], b"12");
+    }
+}
@@ -0,0 +1,81 @@
+//! Async I2C API
+//!
+//! The async counterpart of [`blocking::I2c`](super::blocking::I2c). The transaction
+//! contract and the meaning of the operations are the same; the methods return
+//! once the transfer has completed instead of blocking the caller.
+//!
+//! Only available with the `async` feature.
+
+pub use super::blocking::Operation;
+use super::{AddressMode, Error, SevenBitAddress};
+
+/// Async I2C
+pub trait I2c<A: AddressMode = SevenBitAddress> {
+    /// Error type
+    type Error: Error;
+
+    /// Execute the provided operations on the I2C bus.
+    ///
+    /// Same contract as [`blocking::I2c::transaction`](super::blocking::I2c::transaction).
+    async fn transaction<'a>(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'a>],
+    ) -> Result<(), Self::Error>;
+
+    /// Reads enough bytes from slave with `address` to fill `buffer`
+    async fn read(&mut self, address: A, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.transaction(address, &mut [Operation::Read(buffer)])
+            .await
+    }
+
+    /// Writes bytes to slave with address `address`
+    async fn write(&mut self, address: A, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.transaction(address, &mut [Operation::Write(bytes)])
+            .await
+    }
+
+    /// Writes bytes to slave with address `address` and then reads enough bytes to fill `buffer` *in a
+    /// single transaction*
+    async fn write_read(
+        &mut self,
+        address: A,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.transaction(
+            address,
+            &mut [Operation::Write(bytes), Operation::Read(buffer)],
+        )
+        .await
+    }
+}
+
+impl<A: AddressMode, T: I2c<A>> I2c<A> for &mut T {
+    type Error = T::Error;
+
+    async fn transaction<'a>(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'a>],
+    ) -> Result<(), Self::Error> {
+        T::transaction(self, address, operations).await
+    }
+
+    async fn read(&mut self, address: A, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        T::read(self, address, buffer).await
+    }
+
+    async fn write(&mut self, address: A, bytes: &[u8]) -> Result<(), Self::Error> {
+        T::write(self, address, bytes).await
+    }
+
+    async fn write_read(
+        &mut self,
+        address: A,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        T::write_read(self, address, bytes, buffer).await
+    }
+}
@@ -0,0 +1,603 @@
+//! Bit-banged I2C controller
+//!
+//! [`Bitbang`] drives SCL and SDA with two open-drain GPIO pins and a delay, for boards whose
+//! I2C peripherals are unusable or connected to the wrong pins. It also serves as a reference
+//! for the bus-level contract of the [`I2c`] trait:
+//!
+//! - SDA only changes while SCL is low, except for START, repeated START and STOP conditions.
+//! - A START pulls SDA low while SCL is high, a STOP releases SDA while SCL is high.
+//! - Each byte is sent MSB first and acknowledged by the receiver pulling SDA low during a
+//!   ninth clock pulse.
+//! - The controller acknowledges all bytes it reads, except the last one of a transaction or
+//!   before a write, so the target releases SDA for the following condition.
+//!
+//! Both pins must be configured as open-drain with pull-ups, and must be readable while
+//! driven high, so that the controller sees the acknowledgements and other controllers. If a
+//! bit the controller sends as high reads as low, another controller is using the bus and the
+//! transfer stops with [`Error::ArbitrationLoss`], releasing both lines without sending a
+//! STOP.
+//!
+//! Targets may stretch the clock by holding SCL low. This is only supported if enabled with
+//! [`Bitbang::with_clock_stretching`], as it costs a read of SCL per clock pulse.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::delay::blocking::DelayUs;
+//! use embedded_hal::digital::blocking::{InputPin, OutputPin};
+//! use embedded_hal::i2c::bitbang::{Bitbang, Error};
+//! use embedded_hal::i2c::blocking::I2c;
+//!
+//! /// Reads the ID of a BME280 sensor on spare pins
+//! fn read_id<P, D, E>(scl: P, sda: P, delay: D) -> Result<u8, Error<E, D::Error>>
+//! where
+//!     P: OutputPin<Error = E> + InputPin<Error = E>,
+//!     D: DelayUs,
+//!     E: embedded_hal::digital::Error,
+//! {
+//!     let mut i2c = Bitbang::new(scl, sda, delay).with_clock_stretching(1_000);
+//!     let mut id = [0];
+//!     i2c.write_read(0x76, &[0xD0], &mut id)?;
+//!     Ok(id[0])
+//! }
+//! ```
+
+use super::blocking::{BusRecovery, I2c, Operation};
+use super::{ErrorKind, NoAcknowledgeSource, SevenBitAddress};
+use crate::delay::blocking::DelayUs;
+use crate::digital::blocking::{InputPin, OutputPin};
+
+/// Bit-banged controller error
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error<P, D> {
+    /// Setting or reading a pin failed
+    Pin(P),
+    /// The delay failed
+    Delay(D),
+    /// The address or a data byte wasn't acknowledged
+    NoAcknowledge(NoAcknowledgeSource),
+    /// Another controller pulled SDA low while this controller sent a high bit
+    ArbitrationLoss,
+    /// SDA was held low, so the bus couldn't be started or recovered
+    Bus,
+    /// A target stretched the clock for longer than the timeout
+    Timeout,
+}
+
+impl<P: core::fmt::Debug, D: core::fmt::Debug> super::Error for Error<P, D> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Pin(_) | Self::Delay(_) => ErrorKind::Other,
+            Self::NoAcknowledge(source) => ErrorKind::NoAcknowledge(*source),
+            Self::ArbitrationLoss => ErrorKind::ArbitrationLoss,
+            Self::Bus => ErrorKind::Bus,
+            Self::Timeout => ErrorKind::Timeout,
+        }
+    }
+}
+
+/// I2C controller driving GPIO pins
+///
+/// `SCL` and `SDA` must share their error type, which is the case for the pins of most HALs.
+#[derive(Debug)]
+pub struct Bitbang<SCL, SDA, D> {
+    scl: SCL,
+    sda: SDA,
+    delay: D,
+    half_period_us: u32,
+    stretch_timeout_us: Option<u32>,
+}
+
+impl<SCL, SDA, D> Bitbang<SCL, SDA, D> {
+    /// Creates a new controller with a half clock period of 5 µs, i.e. at most 100 kHz, and
+    /// without clock stretching
+    ///
+    /// Both pins must already be released, i.e. set high.
+    pub fn new(scl: SCL, sda: SDA, delay: D) -> Self {
+        Bitbang {
+            scl,
+            sda,
+            delay,
+            half_period_us: 5,
+            stretch_timeout_us: None,
+        }
+    }
+
+    /// Sets the half clock period in microseconds
+    pub fn with_half_period_us(mut self, half_period_us: u32) -> Self {
+        self.half_period_us = half_period_us;
+        self
+    }
+
+    /// Enables clock stretching, failing with [`Error::Timeout`] if a target holds SCL low
+    /// for longer than `timeout_us` microseconds
+    pub fn with_clock_stretching(mut self, timeout_us: u32) -> Self {
+        self.stretch_timeout_us = Some(timeout_us);
+        self
+    }
+
+    /// Releases the pins and the delay
+    pub fn release(self) -> (SCL, SDA, D) {
+        (self.scl, self.sda, self.delay)
+    }
+}
+
+impl<SCL, SDA, D, E> Bitbang<SCL, SDA, D>
+where
+    SCL: OutputPin<Error = E> + InputPin<Error = E>,
+    SDA: OutputPin<Error = E> + InputPin<Error = E>,
+    D: DelayUs,
+{
+    fn wait(&mut self) -> Result<(), Error<E, D::Error>> {
+        self.delay
+            .delay_us(self.half_period_us)
+            .map_err(Error::Delay)
+    }
+
+    /// Releases SCL, waiting for targets stretching the clock if enabled
+    fn scl_high(&mut self) -> Result<(), Error<E, D::Error>> {
+        self.scl.set_high().map_err(Error::Pin)?;
+        if let Some(timeout_us) = self.stretch_timeout_us {
+            let mut waited = 0;
+            while self.scl.is_low().map_err(Error::Pin)? {
+                if waited >= timeout_us {
+                    return Err(Error::Timeout);
+                }
+                self.delay.delay_us(1).map_err(Error::Delay)?;
+                waited += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn scl_low(&mut self) -> Result<(), Error<E, D::Error>> {
+        self.scl.set_low().map_err(Error::Pin)
+    }
+
+    fn sda_set(&mut self, high: bool) -> Result<(), Error<E, D::Error>> {
+        if high {
+            self.sda.set_high().map_err(Error::Pin)
+        } else {
+            self.sda.set_low().map_err(Error::Pin)
+        }
+    }
+
+    /// Sends a START, or a repeated START if SCL is low, leaving SCL low
+    fn start(&mut self) -> Result<(), Error<E, D::Error>> {
+        self.sda_set(true)?;
+        self.wait()?;
+        self.scl_high()?;
+        if self.sda.is_low().map_err(Error::Pin)? {
+            return Err(Error::Bus);
+        }
+        self.wait()?;
+        self.sda_set(false)?;
+        self.wait()?;
+        self.scl_low()
+    }
+
+    /// Sends a STOP, leaving the bus idle
+    fn stop(&mut self) -> Result<(), Error<E, D::Error>> {
+        self.sda_set(false)?;
+        self.wait()?;
+        self.scl_high()?;
+        self.wait()?;
+        self.sda_set(true)?;
+        self.wait()
+    }
+
+    /// Clocks out `bit` and returns the level of SDA while SCL was high
+    fn clock_bit(&mut self, bit: bool) -> Result<bool, Error<E, D::Error>> {
+        self.sda_set(bit)?;
+        self.wait()?;
+        self.scl_high()?;
+        self.wait()?;
+        let level = self.sda.is_high().map_err(Error::Pin)?;
+        self.scl_low()?;
+        Ok(level)
+    }
+
+    /// Sends a byte, returning whether it was acknowledged
+    fn write_byte(&mut self, byte: u8) -> Result<bool, Error<E, D::Error>> {
+        for bit in (0..8).rev() {
+            let high = byte & (1 << bit) != 0;
+            if !self.clock_bit(high)? && high {
+                // Stop driving the bus, which now belongs to the other controller
+                self.sda_set(true)?;
+                self.scl.set_high().map_err(Error::Pin)?;
+                return Err(Error::ArbitrationLoss);
+            }
+        }
+        Ok(!self.clock_bit(true)?)
+    }
+
+    fn read_byte(&mut self, ack: bool) -> Result<u8, Error<E, D::Error>> {
+        let mut byte = 0;
+        for _ in 0..8 {
+            byte = byte << 1 | self.clock_bit(true)? as u8;
+        }
+        self.clock_bit(!ack)?;
+        Ok(byte)
+    }
+
+    /// Executes the operations, without the final STOP
+    fn operations(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Error<E, D::Error>> {
+        let mut previous_read = None;
+        for i in 0..operations.len() {
+            let read = matches!(operations[i], Operation::Read(_));
+            if previous_read != Some(read) {
+                self.start()?;
+                if !self.write_byte(address << 1 | read as u8)? {
+                    return Err(Error::NoAcknowledge(NoAcknowledgeSource::Address));
+                }
+                previous_read = Some(read);
+            }
+            // Reads are acknowledged unless the next operation isn't a read
+            let next_read = matches!(operations.get(i + 1), Some(Operation::Read(_)));
+            match &mut operations[i] {
+                Operation::Write(bytes) => {
+                    for &byte in bytes.iter() {
+                        if !self.write_byte(byte)? {
+                            return Err(Error::NoAcknowledge(NoAcknowledgeSource::Data));
+                        }
+                    }
+                }
+                Operation::Read(buffer) => {
+                    let len = buffer.len();
+                    for (n, byte) in buffer.iter_mut().enumerate() {
+                        *byte = self.read_byte(n + 1 < len || next_read)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<SCL, SDA, D, E> I2c<SevenBitAddress> for Bitbang<SCL, SDA, D>
+where
+    SCL: OutputPin<Error = E> + InputPin<Error = E>,
+    SDA: OutputPin<Error = E> + InputPin<Error = E>,
+    D: DelayUs,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E, D::Error>;
+
+    /// Executes the operations, sending a STOP unless arbitration was lost
+    ///
+    /// An empty transaction doesn't touch the bus.
+    fn transaction<'a>(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'a>],
+    ) -> Result<(), Self::Error> {
+        if operations.is_empty() {
+            return Ok(());
+        }
+        match self.operations(address, operations) {
+            Err(Error::ArbitrationLoss) => Err(Error::ArbitrationLoss),
+            result => {
+                self.stop()?;
+                result
+            }
+        }
+    }
+}
+
+impl<SCL, SDA, D, E> BusRecovery for Bitbang<SCL, SDA, D>
+where
+    SCL: OutputPin<Error = E> + InputPin<Error = E>,
+    SDA: OutputPin<Error = E> + InputPin<Error = E>,
+    D: DelayUs,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E, D::Error>;
+
+    fn recover(&mut self) -> Result<(), Self::Error> {
+        self.sda_set(true)?;
+        for _ in 0..9 {
+            if self.sda.is_high().map_err(Error::Pin)? {
+                break;
+            }
+            self.scl_low()?;
+            self.wait()?;
+            self.scl_high()?;
+            self.wait()?;
+        }
+        if self.sda.is_low().map_err(Error::Pin)? {
+            return Err(Error::Bus);
+        }
+        self.scl_low()?;
+        self.wait()?;
+        self.stop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::RefCell;
+    use core::convert::Infallible;
+
+    const ADDRESS: u8 = 0x48;
+
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    enum Phase {
+        Idle,
+        /// Receiving the address or data bytes
+        Receive,
+        /// Sending data bytes
+        Transmit,
+    }
+
+    /// Bus with a target at `ADDRESS` reading and writing a register file
+    struct Bus {
+        scl: bool,
+        sda: bool,
+        target_sda_low: bool,
+        /// Whether a second controller starts a transfer together with the controller
+        /// under test, pulling SDA low for its first address bit
+        contender: bool,
+        contender_sda_low: bool,
+        /// Polls of SCL for which the target keeps stretching the clock
+        stretch: u32,
+        phase: Phase,
+        /// Clock pulses of the current byte, including the acknowledge
+        bits: u8,
+        byte: u8,
+        addressed: bool,
+        acked: bool,
+        registers: [u8; 4],
+        pointer: usize,
+        /// Bytes written since the last START
+        written: usize,
+        /// Number of STOP conditions
+        stops: u32,
+    }
+
+    impl Bus {
+        fn sda_line(&self) -> bool {
+            self.sda && !self.target_sda_low && !self.contender_sda_low
+        }
+
+        fn transmit_bit(&mut self) {
+            self.target_sda_low = self.byte & (0x80 >> self.bits) == 0;
+        }
+
+        fn set_sda(&mut self, high: bool) {
+            let before = self.sda_line();
+            self.sda = high;
+            if self.scl && before != self.sda_line() {
+                if high {
+                    self.phase = Phase::Idle;
+                    self.stops += 1;
+                } else {
+                    self.phase = Phase::Receive;
+                    self.contender_sda_low = self.contender;
+                    self.addressed = false;
+                    self.written = 0;
+                }
+                self.bits = 0;
+                self.byte = 0;
+            }
+        }
+
+        fn set_scl(&mut self, high: bool) {
+            if high == self.scl {
+                return;
+            }
+            self.scl = high;
+            match (self.phase, high) {
+                (Phase::Receive, true) if self.bits < 8 => {
+                    self.byte = self.byte << 1 | self.sda_line() as u8;
+                    self.bits += 1;
+                }
+                (Phase::Transmit, true) => {
+                    if self.bits == 8 {
+                        self.acked = !self.sda_line();
+                    }
+                    self.bits += 1;
+                }
+                (Phase::Receive, false) if self.bits == 8 => {
+                    if !self.addressed {
+                        self.addressed = self.byte >> 1 == ADDRESS;
+                        self.acked = self.addressed;
+                    } else {
+                        if self.written == 0 {
+                            self.pointer = usize::from(self.byte);
+                        } else {
+                            self.registers[self.pointer % 4] = self.byte;
+                            self.pointer += 1;
+                        }
+                        self.written += 1;
+                        self.acked = true;
+                    }
+                    self.target_sda_low = self.acked;
+                    self.bits = 9;
+                }
+                (Phase::Receive, false) if self.bits == 9 => {
+                    self.target_sda_low = false;
+                    if !self.acked {
+                        self.phase = Phase::Idle;
+                    } else if self.written == 0 && self.byte & 1 == 1 {
+                        self.phase = Phase::Transmit;
+                        self.byte = self.registers[self.pointer % 4];
+                        self.bits = 0;
+                        self.transmit_bit();
+                    } else {
+                        self.bits = 0;
+                        self.byte = 0;
+                    }
+                }
+                (Phase::Transmit, false) => match self.bits {
+                    0..=7 => self.transmit_bit(),
+                    8 => self.target_sda_low = false,
+                    _ => {
+                        self.pointer += 1;
+                        if self.acked {
+                            self.byte = self.registers[self.pointer % 4];
+                            self.bits = 0;
+                            self.transmit_bit();
+                        } else {
+                            self.phase = Phase::Idle;
+                        }
+                    }
+                },
+                _ => {}
+            }
+        }
+    }
+
+    struct Scl<'a>(&'a RefCell<Bus>);
+    struct Sda<'a>(&'a RefCell<Bus>);
+
+    impl OutputPin for Scl<'_> {
+        type Error = Infallible;
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.0.borrow_mut().set_scl(false);
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            let mut bus = self.0.borrow_mut();
+            if bus.stretch == 0 {
+                bus.set_scl(true);
+            }
+            Ok(())
+        }
+    }
+
+    impl InputPin for Scl<'_> {
+        type Error = Infallible;
+
+        fn is_high(&self) -> Result<bool, Self::Error> {
+            let mut bus = self.0.borrow_mut();
+            if bus.stretch > 0 {
+                bus.stretch -= 1;
+                if bus.stretch == 0 {
+                    bus.set_scl(true);
+                }
+            }
+            Ok(bus.scl)
+        }
+
+        fn is_low(&self) -> Result<bool, Self::Error> {
+            self.is_high().map(|high| !high)
+        }
+    }
+
+    impl OutputPin for Sda<'_> {
+        type Error = Infallible;
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.0.borrow_mut().set_sda(false);
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.0.borrow_mut().set_sda(true);
+            Ok(())
+        }
+    }
+
+    impl InputPin for Sda<'_> {
+        type Error = Infallible;
+
+        fn is_high(&self) -> Result<bool, Self::Error> {
+            Ok(self.0.borrow().sda_line())
+        }
+
+        fn is_low(&self) -> Result<bool, Self::Error> {
+            Ok(!self.0.borrow().sda_line())
+        }
+    }
+
+    impl DelayUs for Scl<'_> {
+        type Error = Infallible;
+
+        fn delay_us(&mut self, _us: u32) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    fn idle() -> RefCell<Bus> {
+        RefCell::new(Bus {
+            scl: true,
+            sda: true,
+            target_sda_low: false,
+            contender: false,
+            contender_sda_low: false,
+            stretch: 0,
+            phase: Phase::Idle,
+            bits: 0,
+            byte: 0,
+            addressed: false,
+            acked: false,
+            registers: [0x60, 0, 0, 0],
+            pointer: 0,
+            written: 0,
+            stops: 0,
+        })
+    }
+
+    #[test]
+    fn transactions() {
+        let bus = idle();
+        let mut i2c = Bitbang::new(Scl(&bus), Sda(&bus), Scl(&bus));
+        i2c.write(ADDRESS, &[1, 0xa5, 0x5a]).unwrap();
+        assert_eq!(bus.borrow().registers, [0x60, 0xa5, 0x5a, 0]);
+
+        let mut buffer = [0; 3];
+        i2c.write_read(ADDRESS, &[0], &mut buffer).unwrap();
+        assert_eq!(buffer, [0x60, 0xa5, 0x5a]);
+        let mut first = [0; 1];
+        let mut second = [0; 2];
+        i2c.transaction(
+            ADDRESS,
+            &mut [Operation::Read(&mut first), Operation::Read(&mut second)],
+        )
+        .unwrap();
+        assert_eq!((first, second), ([0], [0x60, 0xa5]));
+
+        assert_eq!(i2c.probe(ADDRESS + 1), Ok(false));
+        let bus = bus.borrow();
+        assert_eq!(bus.stops, 4);
+        assert!(bus.scl && bus.sda_line());
+    }
+
+    #[test]
+    fn empty_transaction_leaves_bus_idle() {
+        let bus = idle();
+        let mut i2c = Bitbang::new(Scl(&bus), Sda(&bus), Scl(&bus));
+        assert_eq!(i2c.transaction(ADDRESS, &mut []), Ok(()));
+        let bus = bus.borrow();
+        assert_eq!(bus.stops, 0);
+        assert_eq!(bus.phase, Phase::Idle);
+    }
+
+    #[test]
+    fn arbitration_loss_releases_bus() {
+        let bus = idle();
+        let mut i2c = Bitbang::new(Scl(&bus), Sda(&bus), Scl(&bus));
+        bus.borrow_mut().contender = true;
+        assert_eq!(i2c.write(ADDRESS, &[0]), Err(Error::ArbitrationLoss));
+        let bus = bus.borrow();
+        assert_eq!(bus.stops, 0);
+        assert!(bus.scl && bus.sda);
+    }
+
+    #[test]
+    fn clock_stretching() {
+        let bus = idle();
+        let mut i2c = Bitbang::new(Scl(&bus), Sda(&bus), Scl(&bus)).with_clock_stretching(10);
+        bus.borrow_mut().stretch = 5;
+        assert_eq!(i2c.probe(ADDRESS), Ok(true));
+        bus.borrow_mut().stretch = 20;
+        assert_eq!(i2c.probe(ADDRESS), Err(Error::Timeout));
+    }
+}
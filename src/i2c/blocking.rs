@@ -0,0 +1,792 @@
+//! Blocking I2C API
+
+use super::{AddressMode, Error, ErrorKind, NoAcknowledgeSource, SevenBitAddress};
+
+/// Blocking read
+pub trait Read<A: AddressMode = SevenBitAddress> {
+    /// Error type
+    type Error: Error;
+
+    /// Reads enough bytes from slave with `address` to fill `buffer`
+    ///
+    /// # I2C Events (contract)
+    ///
+    /// ``` text
+    /// Master: ST SAD+R        MAK    MAK ...    NMAK SP
+    /// Slave:           SAK B0     B1     ... BN
+    /// ```
+    ///
+    /// Where
+    ///
+    /// - `ST` = start condition
+    /// - `SAD+R` = slave address followed by bit 1 to indicate reading
+    /// - `SAK` = slave acknowledge
+    /// - `Bi` = ith byte of data
+    /// - `MAK` = master acknowledge
+    /// - `NMAK` = master no acknowledge
+    /// - `SP` = stop condition
+    fn read(&mut self, address: A, buffer: &mut [u8]) -> Result<(), Self::Error>;
+}
+
+impl<A: AddressMode, T: Read<A>> Read<A> for &mut T {
+    type Error = T::Error;
+
+    fn read(&mut self, address: A, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        T::read(self, address, buffer)
+    }
+}
+
+/// Blocking write
+pub trait Write<A: AddressMode = SevenBitAddress> {
+    /// Error type
+    type Error: Error;
+
+    /// Writes bytes to slave with address `address`
+    ///
+    /// # I2C Events (contract)
+    ///
+    /// ``` text
+    /// Master: ST SAD+W     B0     B1     ... BN     SP
+    /// Slave:           SAK    SAK    SAK ...    SAK
+    /// ```
+    ///
+    /// Where
+    ///
+    /// - `ST` = start condition
+    /// - `SAD+W` = slave address followed by bit 0 to indicate writing
+    /// - `SAK` = slave acknowledge
+    /// - `Bi` = ith byte of data
+    /// - `SP` = stop condition
+    fn write(&mut self, address: A, bytes: &[u8]) -> Result<(), Self::Error>;
+}
+
+impl<A: AddressMode, T: Write<A>> Write<A> for &mut T {
+    type Error = T::Error;
+
+    fn write(&mut self, address: A, bytes: &[u8]) -> Result<(), Self::Error> {
+        T::write(self, address, bytes)
+    }
+}
+
+/// Blocking write (iterator version)
+pub trait WriteIter<A: AddressMode = SevenBitAddress> {
+    /// Error type
+    type Error: Error;
+
+    /// Writes bytes to slave with address `address`
+    ///
+    /// # I2C Events (contract)
+    ///
+    /// Same as `Write`
+    fn write_iter<B>(&mut self, address: A, bytes: B) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>;
+}
+
+impl<A: AddressMode, T: WriteIter<A>> WriteIter<A> for &mut T {
+    type Error = T::Error;
+
+    fn write_iter<B>(&mut self, address: A, bytes: B) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        T::write_iter(self, address, bytes)
+    }
+}
+
+/// Blocking write + read
+pub trait WriteRead<A: AddressMode = SevenBitAddress> {
+    /// Error type
+    type Error: Error;
+
+    /// Writes bytes to slave with address `address` and then reads enough bytes to fill `buffer` *in a
+    /// single transaction*
+    ///
+    /// # I2C Events (contract)
+    ///
+    /// ``` text
+    /// Master: ST SAD+W     O0     O1     ... OM     SR SAD+R        MAK    MAK ...    NMAK SP
+    /// Slave:           SAK    SAK    SAK ...    SAK          SAK I0     I1     ... IN
+    /// ```
+    ///
+    /// Where
+    ///
+    /// - `ST` = start condition
+    /// - `SAD+W` = slave address followed by bit 0 to indicate writing
+    /// - `SAK` = slave acknowledge
+    /// - `Oi` = ith outgoing byte of data
+    /// - `SR` = repeated start condition
+    /// - `SAD+R` = slave address followed by bit 1 to indicate reading
+    /// - `Ii` = ith incoming byte of data
+    /// - `MAK` = master acknowledge
+    /// - `NMAK` = master no acknowledge
+    /// - `SP` = stop condition
+    fn write_read(
+        &mut self,
+        address: A,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error>;
+}
+
+impl<A: AddressMode, T: WriteRead<A>> WriteRead<A> for &mut T {
+    type Error = T::Error;
+
+    fn write_read(
+        &mut self,
+        address: A,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        T::write_read(self, address, bytes, buffer)
+    }
+}
+
+/// Blocking write (iterator version) + read
+pub trait WriteIterRead<A: AddressMode = SevenBitAddress> {
+    /// Error type
+    type Error: Error;
+
+    /// Writes bytes to slave with address `address` and then reads enough bytes to fill `buffer` *in a
+    /// single transaction*
+    ///
+    /// # I2C Events (contract)
+    ///
+    /// Same as the `WriteRead` trait
+    fn write_iter_read<B>(
+        &mut self,
+        address: A,
+        bytes: B,
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>;
+}
+
+impl<A: AddressMode, T: WriteIterRead<A>> WriteIterRead<A> for &mut T {
+    type Error = T::Error;
+
+    fn write_iter_read<B>(
+        &mut self,
+        address: A,
+        bytes: B,
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        T::write_iter_read(self, address, bytes, buffer)
+    }
+}
+
+/// Transactional I2C operation.
+///
+/// Several operations can be combined as part of a transaction.
+#[derive(Debug, PartialEq)]
+pub enum Operation<'a> {
+    /// Read data into the provided buffer
+    Read(&'a mut [u8]),
+    /// Write data from the provided buffer
+    Write(&'a [u8]),
+}
+
+/// Transactional I2C interface.
+///
+/// This allows combining operations within an I2C transaction.
+pub trait Transactional<A: AddressMode = SevenBitAddress> {
+    /// Error type
+    type Error: Error;
+
+    /// Execute the provided operations on the I2C bus.
+    ///
+    /// Transaction contract:
+    /// - Before executing the first operation an ST is sent automatically. This is followed by SAD+R/W as appropriate.
+    /// - Data from adjacent operations of the same type are sent after each other without an SP or SR.
+    /// - Between adjacent operations of a different type an SR and SAD+R/W is sent.
+    /// - After executing the last operation an SP is sent automatically.
+    /// - If the last operation is a `Read` the master does not send an acknowledge for the last byte.
+    ///
+    /// - `ST` = start condition
+    /// - `SAD+R/W` = slave address followed by bit 1 to indicate reading or 0 to indicate writing
+    /// - `SR` = repeated start condition
+    /// - `SP` = stop condition
+    fn exec<'a>(&mut self, address: A, operations: &mut [Operation<'a>])
+        -> Result<(), Self::Error>;
+}
+
+impl<A: AddressMode, T: Transactional<A>> Transactional<A> for &mut T {
+    type Error = T::Error;
+
+    fn exec<'a>(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'a>],
+    ) -> Result<(), Self::Error> {
+        T::exec(self, address, operations)
+    }
+}
+
+/// Transactional I2C interface (iterator version).
+///
+/// This allows combining operation within an I2C transaction.
+pub trait TransactionalIter<A: AddressMode = SevenBitAddress> {
+    /// Error type
+    type Error: Error;
+
+    /// Execute the provided operations on the I2C bus (iterator version).
+    ///
+    /// Transaction contract:
+    /// - Before executing the first operation an ST is sent automatically. This is followed by SAD+R/W as appropriate.
+    /// - Data from adjacent operations of the same type are sent after each other without an SP or SR.
+    /// - Between adjacent operations of a different type an SR and SAD+R/W is sent.
+    /// - After executing the last operation an SP is sent automatically.
+    /// - If the last operation is a `Read` the master does not send an acknowledge for the last byte.
+    ///
+    /// - `ST` = start condition
+    /// - `SAD+R/W` = slave address followed by bit 1 to indicate reading or 0 to indicate writing
+    /// - `SR` = repeated start condition
+    /// - `SP` = stop condition
+    fn exec_iter<'a, O>(&mut self, address: A, operations: O) -> Result<(), Self::Error>
+    where
+        O: IntoIterator<Item = Operation<'a>>;
+}
+
+impl<A: AddressMode, T: TransactionalIter<A>> TransactionalIter<A> for &mut T {
+    type Error = T::Error;
+
+    fn exec_iter<'a, O>(&mut self, address: A, operations: O) -> Result<(), Self::Error>
+    where
+        O: IntoIterator<Item = Operation<'a>>,
+    {
+        T::exec_iter(self, address, operations)
+    }
+}
+
+/// Transactional I2C operation with explicit framing.
+///
+/// In addition to the operations of [`Operation`], this allows to place repeated
+/// start conditions explicitly and to end a transaction without a stop condition.
+#[derive(Debug, PartialEq)]
+pub enum FramedOperation<'a> {
+    /// Read data into the provided buffer
+    Read(&'a mut [u8]),
+    /// Write data from the provided buffer
+    Write(&'a [u8]),
+    /// Send an SR and SAD+R/W before the next operation, even if it is of the
+    /// same type as the previous one
+    RepeatedStart,
+    /// End the transaction without an SP, keeping control of the bus
+    ///
+    /// This must be the last operation of a transaction. The next transaction
+    /// executed on the bus then starts with an SR instead of an ST.
+    NoStop,
+}
+
+/// Transactional I2C interface with explicit framing.
+///
+/// This allows expressing framings which are not covered by [`Transactional`], e.g.
+/// consecutive writes separated by a repeated start, or a write which is followed by a
+/// read in a later transaction without releasing the bus in between.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::i2c::blocking::{FramedOperation, TransactionalFramed};
+///
+/// /// Sends a bootloader command as two write frames separated by a repeated start
+/// fn send_command<I2C: TransactionalFramed>(
+///     i2c: &mut I2C,
+///     command: u8,
+///     payload: &[u8],
+/// ) -> Result<(), I2C::Error> {
+///     i2c.exec_framed(
+///         0x42,
+///         &mut [
+///             FramedOperation::Write(&[command, !command]),
+///             FramedOperation::RepeatedStart,
+///             FramedOperation::Write(payload),
+///         ],
+///     )
+/// }
+/// ```
+pub trait TransactionalFramed<A: AddressMode = SevenBitAddress> {
+    /// Error type
+    type Error: Error;
+
+    /// Execute the provided operations on the I2C bus.
+    ///
+    /// Transaction contract:
+    /// - Before executing the first operation an ST is sent automatically, or an SR if the
+    ///   previous transaction ended with [`NoStop`](FramedOperation::NoStop). This is followed
+    ///   by SAD+R/W as appropriate.
+    /// - Data from adjacent operations of the same type are sent after each other without an SP or SR.
+    /// - Between adjacent operations of a different type an SR and SAD+R/W is sent.
+    /// - A [`RepeatedStart`](FramedOperation::RepeatedStart) sends an SR and SAD+R/W before the next
+    ///   operation regardless of its type.
+    /// - After executing the last operation an SP is sent automatically, unless the last
+    ///   operation is [`NoStop`](FramedOperation::NoStop).
+    /// - If the last data operation is a `Read` the master does not send an acknowledge for the last byte.
+    ///
+    /// - `ST` = start condition
+    /// - `SAD+R/W` = slave address followed by bit 1 to indicate reading or 0 to indicate writing
+    /// - `SR` = repeated start condition
+    /// - `SP` = stop condition
+    fn exec_framed<'a>(
+        &mut self,
+        address: A,
+        operations: &mut [FramedOperation<'a>],
+    ) -> Result<(), Self::Error>;
+}
+
+impl<A: AddressMode, T: TransactionalFramed<A>> TransactionalFramed<A> for &mut T {
+    type Error = T::Error;
+
+    fn exec_framed<'a>(
+        &mut self,
+        address: A,
+        operations: &mut [FramedOperation<'a>],
+    ) -> Result<(), Self::Error> {
+        T::exec_framed(self, address, operations)
+    }
+}
+
+/// I2C operation with its own target address.
+///
+/// Used by [`TransactionalMulti`] to address several targets within a single transaction.
+#[derive(Debug, PartialEq)]
+pub struct AddressedOperation<'a, A: AddressMode = SevenBitAddress> {
+    /// Address of the target of this operation
+    pub address: A,
+    /// Operation to execute
+    pub operation: Operation<'a>,
+}
+
+/// Transactional I2C interface addressing several targets.
+///
+/// This allows combining operations on different targets in a single transaction, i.e.
+/// without releasing the bus in between. This is required e.g. by some EEPROMs which are
+/// split in several address blocks, and by PMICs which need to be accessed through several
+/// addresses without another controller interleaving transfers.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::i2c::blocking::{AddressedOperation, Operation, TransactionalMulti};
+///
+/// /// Reads a byte from each of two register blocks of a device without releasing the bus
+/// fn read_blocks<I2C: TransactionalMulti>(i2c: &mut I2C) -> Result<[u8; 2], I2C::Error> {
+///     let mut a = [0];
+///     let mut b = [0];
+///     i2c.exec_multi(&mut [
+///         AddressedOperation { address: 0x50, operation: Operation::Write(&[0x10]) },
+///         AddressedOperation { address: 0x50, operation: Operation::Read(&mut a) },
+///         AddressedOperation { address: 0x51, operation: Operation::Write(&[0x10]) },
+///         AddressedOperation { address: 0x51, operation: Operation::Read(&mut b) },
+///     ])?;
+///     Ok([a[0], b[0]])
+/// }
+/// ```
+pub trait TransactionalMulti<A: AddressMode = SevenBitAddress> {
+    /// Error type
+    type Error: Error;
+
+    /// Execute the provided operations on the I2C bus.
+    ///
+    /// Transaction contract:
+    /// - Before executing the first operation an ST is sent automatically. This is followed by SAD+R/W as appropriate.
+    /// - Data from adjacent operations of the same type and with the same address are sent after each other without an SP or SR.
+    /// - Between adjacent operations of a different type or with a different address an SR and SAD+R/W is sent.
+    /// - After executing the last operation an SP is sent automatically.
+    /// - If the last operation is a `Read` the master does not send an acknowledge for the last byte.
+    ///
+    /// - `ST` = start condition
+    /// - `SAD+R/W` = slave address followed by bit 1 to indicate reading or 0 to indicate writing
+    /// - `SR` = repeated start condition
+    /// - `SP` = stop condition
+    fn exec_multi<'a>(
+        &mut self,
+        operations: &mut [AddressedOperation<'a, A>],
+    ) -> Result<(), Self::Error>;
+}
+
+impl<A: AddressMode, T: TransactionalMulti<A>> TransactionalMulti<A> for &mut T {
+    type Error = T::Error;
+
+    fn exec_multi<'a>(
+        &mut self,
+        operations: &mut [AddressedOperation<'a, A>],
+    ) -> Result<(), Self::Error> {
+        T::exec_multi(self, operations)
+    }
+}
+
+/// Maximum number of buffers of the default [`I2c::write_read_vectored`] implementation
+pub const MAX_VECTORED: usize = 16;
+
+/// Error of [`I2c::write_read_vectored`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum VectoredError<E> {
+    /// There were more buffers than the implementation supports, nothing was transferred
+    TooManyBuffers,
+    /// The transaction failed
+    I2c(E),
+}
+
+impl<E: Error> Error for VectoredError<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::TooManyBuffers => ErrorKind::Other,
+            Self::I2c(e) => e.kind(),
+        }
+    }
+}
+
+/// Blocking I2C
+///
+/// This trait combines the read, write, write + read and transactional operations
+/// in a single trait, so that HAL implementations only implement [`transaction`] and
+/// device drivers only need to name a single bound. The other methods are provided in
+/// terms of [`transaction`], but implementations are free to override them with more
+/// efficient versions.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::i2c::blocking::I2c;
+///
+/// const ADDR: u8 = 0x15;
+/// const TEMP_REGISTER: u8 = 0x1;
+///
+/// pub struct TemperatureSensorDriver<I2C> {
+///     i2c: I2C,
+/// }
+///
+/// impl<I2C: I2c> TemperatureSensorDriver<I2C> {
+///     pub fn read_temperature(&mut self) -> Result<u8, I2C::Error> {
+///         let mut temp = [0];
+///         self.i2c.write_read(ADDR, &[TEMP_REGISTER], &mut temp)?;
+///         Ok(temp[0])
+///     }
+/// }
+/// ```
+///
+/// [`transaction`]: I2c::transaction
+pub trait I2c<A: AddressMode = SevenBitAddress> {
+    /// Error type
+    type Error: Error;
+
+    /// Execute the provided operations on the I2C bus.
+    ///
+    /// Transaction contract:
+    /// - Before executing the first operation an ST is sent automatically. This is followed by SAD+R/W as appropriate.
+    /// - Data from adjacent operations of the same type are sent after each other without an SP or SR.
+    /// - Between adjacent operations of a different type an SR and SAD+R/W is sent.
+    /// - After executing the last operation an SP is sent automatically.
+    /// - If the last operation is a `Read` the master does not send an acknowledge for the last byte.
+    ///
+    /// - `ST` = start condition
+    /// - `SAD+R/W` = slave address followed by bit 1 to indicate reading or 0 to indicate writing
+    /// - `SR` = repeated start condition
+    /// - `SP` = stop condition
+    fn transaction<'a>(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'a>],
+    ) -> Result<(), Self::Error>;
+
+    /// Reads enough bytes from slave with `address` to fill `buffer`
+    ///
+    /// # I2C Events (contract)
+    ///
+    /// Same as [`Read::read`]
+    fn read(&mut self, address: A, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.transaction(address, &mut [Operation::Read(buffer)])
+    }
+
+    /// Writes bytes to slave with address `address`
+    ///
+    /// # I2C Events (contract)
+    ///
+    /// Same as [`Write::write`]
+    fn write(&mut self, address: A, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.transaction(address, &mut [Operation::Write(bytes)])
+    }
+
+    /// Writes bytes to slave with address `address` and then reads enough bytes to fill `buffer` *in a
+    /// single transaction*
+    ///
+    /// # I2C Events (contract)
+    ///
+    /// Same as [`WriteRead::write_read`]
+    fn write_read(
+        &mut self,
+        address: A,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.transaction(
+            address,
+            &mut [Operation::Write(bytes), Operation::Read(buffer)],
+        )
+    }
+
+    /// Writes the concatenation of `bytes` to slave with address `address` and then reads
+    /// enough bytes to fill all of `buffers` in order, *in a single transaction*
+    ///
+    /// This avoids copying e.g. a register address and a payload into one temporary array.
+    ///
+    /// # I2C Events (contract)
+    ///
+    /// Same as [`WriteRead::write_read`]
+    ///
+    /// The default implementation executes a [`transaction`](I2c::transaction) with one
+    /// operation per buffer and returns [`VectoredError::TooManyBuffers`] if there are more
+    /// than [`MAX_VECTORED`] buffers in total. Implementations can override it to lift this
+    /// limit.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use embedded_hal::i2c::blocking::{I2c, VectoredError};
+    ///
+    /// /// Reads a sample header and its payload from a 16-bit FIFO register
+    /// fn read_sample<I2C: I2c>(
+    ///     i2c: &mut I2C,
+    ///     header: &mut [u8; 2],
+    ///     payload: &mut [u8],
+    /// ) -> Result<(), VectoredError<I2C::Error>> {
+    ///     let register = 0x0100u16.to_be_bytes();
+    ///     i2c.write_read_vectored(0x50, &[&register[..1], &register[1..]], &mut [header, payload])
+    /// }
+    /// ```
+    fn write_read_vectored(
+        &mut self,
+        address: A,
+        bytes: &[&[u8]],
+        buffers: &mut [&mut [u8]],
+    ) -> Result<(), VectoredError<Self::Error>> {
+        const EMPTY: Operation<'static> = Operation::Write(&[]);
+
+        if bytes.len() + buffers.len() > MAX_VECTORED {
+            return Err(VectoredError::TooManyBuffers);
+        }
+        // `[EMPTY; N]` would require Rust 1.50, as `Operation` is not `Copy`.
+        let mut operations: [Operation<'_>; MAX_VECTORED] = [
+            EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY,
+            EMPTY, EMPTY, EMPTY, EMPTY,
+        ];
+        let mut len = 0;
+        for bytes in bytes {
+            operations[len] = Operation::Write(bytes);
+            len += 1;
+        }
+        for buffer in buffers.iter_mut() {
+            operations[len] = Operation::Read(buffer);
+            len += 1;
+        }
+        self.transaction(address, &mut operations[..len])
+            .map_err(VectoredError::I2c)
+    }
+
+    /// Checks whether a device acknowledges `address`
+    ///
+    /// Returns `Ok(false)` if the address was not acknowledged, i.e. no device with this
+    /// address is present or it is not ready to process requests. Other errors are returned
+    /// as is.
+    ///
+    /// The default implementation performs a zero-length write, which is the least intrusive
+    /// way to probe most devices. Implementations which cannot issue zero-length transfers
+    /// should override this method, e.g. with a one byte read.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use embedded_hal::i2c::blocking::I2c;
+    ///
+    /// /// Returns a bitmap of the non-reserved 7-bit addresses which are present on the bus
+    /// fn scan<I2C: I2c>(i2c: &mut I2C) -> Result<u128, I2C::Error> {
+    ///     let mut present = 0;
+    ///     for address in 0x08..0x78 {
+    ///         if i2c.probe(address)? {
+    ///             present |= 1 << address;
+    ///         }
+    ///     }
+    ///     Ok(present)
+    /// }
+    /// ```
+    fn probe(&mut self, address: A) -> Result<bool, Self::Error> {
+        match self.write(address, &[]) {
+            Ok(()) => Ok(true),
+            Err(e) => match e.kind() {
+                ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)
+                | ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown) => Ok(false),
+                _ => Err(e),
+            },
+        }
+    }
+}
+
+impl<A: AddressMode, T: I2c<A>> I2c<A> for &mut T {
+    type Error = T::Error;
+
+    fn transaction<'a>(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'a>],
+    ) -> Result<(), Self::Error> {
+        T::transaction(self, address, operations)
+    }
+
+    fn read(&mut self, address: A, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        T::read(self, address, buffer)
+    }
+
+    fn write(&mut self, address: A, bytes: &[u8]) -> Result<(), Self::Error> {
+        T::write(self, address, bytes)
+    }
+
+    fn write_read(
+        &mut self,
+        address: A,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        T::write_read(self, address, bytes, buffer)
+    }
+
+    fn write_read_vectored(
+        &mut self,
+        address: A,
+        bytes: &[&[u8]],
+        buffers: &mut [&mut [u8]],
+    ) -> Result<(), VectoredError<Self::Error>> {
+        T::write_read_vectored(self, address, bytes, buffers)
+    }
+
+    fn probe(&mut self, address: A) -> Result<bool, Self::Error> {
+        T::probe(self, address)
+    }
+}
+
+/// Blocking I2C bus recovery
+///
+/// A target which got out of sync with the controller, e.g. due to a reset of the
+/// controller in the middle of a transfer, may hold SDA low indefinitely. This trait
+/// allows generic code to try to release the bus in that situation.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::i2c::blocking::{BusRecovery, I2c};
+/// use embedded_hal::i2c::{Error, ErrorKind};
+///
+/// fn write_or_recover<I2C, E>(i2c: &mut I2C, address: u8, bytes: &[u8]) -> Result<(), E>
+/// where
+///     I2C: I2c<Error = E> + BusRecovery<Error = E>,
+///     E: Error,
+/// {
+///     match i2c.write(address, bytes) {
+///         Err(e) if matches!(e.kind(), ErrorKind::Bus | ErrorKind::ArbitrationLoss) => {
+///             i2c.recover()?;
+///             i2c.write(address, bytes)
+///         }
+///         result => result,
+///     }
+/// }
+/// ```
+pub trait BusRecovery {
+    /// Error type
+    type Error: Error;
+
+    /// Attempts to release the bus
+    ///
+    /// Implementations clock out up to 9 SCL pulses, stopping as soon as SDA is released,
+    /// and then issue a STOP condition. The peripheral is left in a state where it can start
+    /// new transfers.
+    ///
+    /// An error is returned if SDA is still held low after the recovery sequence.
+    fn recover(&mut self) -> Result<(), Self::Error>;
+}
+
+impl<T: BusRecovery> BusRecovery for &mut T {
+    type Error = T::Error;
+
+    fn recover(&mut self) -> Result<(), Self::Error> {
+        T::recover(self)
+    }
+}
+
+/// Blocking general call (broadcast) write
+///
+/// The general call address `0x00` addresses every device on the bus which supports it.
+/// The meaning of the first data byte is defined by the I2C specification if its least
+/// significant bit is cleared, see [`software_reset`] and [`program_address`]. If it is
+/// set, the transfer is a *hardware general call*, see [`hardware_general_call`].
+///
+/// Many implementations reject `0x00` in the regular I2C traits, as it is a reserved
+/// address. Implementations which support general calls expose them through this trait.
+///
+/// [`software_reset`]: GeneralCall::software_reset
+/// [`program_address`]: GeneralCall::program_address
+/// [`hardware_general_call`]: GeneralCall::hardware_general_call
+pub trait GeneralCall {
+    /// Error type
+    type Error: Error;
+
+    /// Writes `bytes` to the general call address
+    ///
+    /// # I2C Events (contract)
+    ///
+    /// ``` text
+    /// Master: ST 0x00+W     B0     B1     ... BN     SP
+    /// Slave:            SAK    SAK    SAK ...    SAK
+    /// ```
+    fn general_call(&mut self, bytes: &[u8]) -> Result<(), Self::Error>;
+
+    /// Resets all devices supporting it and makes them latch the programmable part
+    /// of their address (second byte `0x06`)
+    fn software_reset(&mut self) -> Result<(), Self::Error> {
+        self.general_call(&[0x06])
+    }
+
+    /// Makes all devices supporting it latch the programmable part of their address
+    /// without resetting (second byte `0x04`)
+    fn program_address(&mut self) -> Result<(), Self::Error> {
+        self.general_call(&[0x04])
+    }
+
+    /// Sends a hardware general call with the 7-bit address of the sending controller
+    /// followed by `bytes`
+    ///
+    /// This is used by hardware masters, e.g. keyboard scanners, which cannot be programmed
+    /// with the address of the device they need to talk to.
+    fn hardware_general_call(
+        &mut self,
+        controller_address: SevenBitAddress,
+        bytes: &[u8],
+    ) -> Result<(), Self::Error>;
+}
+
+impl<T: GeneralCall> GeneralCall for &mut T {
+    type Error = T::Error;
+
+    fn general_call(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        T::general_call(self, bytes)
+    }
+
+    fn software_reset(&mut self) -> Result<(), Self::Error> {
+        T::software_reset(self)
+    }
+
+    fn program_address(&mut self) -> Result<(), Self::Error> {
+        T::program_address(self)
+    }
+
+    fn hardware_general_call(
+        &mut self,
+        controller_address: SevenBitAddress,
+        bytes: &[u8],
+    ) -> Result<(), Self::Error> {
+        T::hardware_general_call(self, controller_address, bytes)
+    }
+}
@@ -0,0 +1,133 @@
+//! I2C with owned DMA buffers
+//!
+//! These traits mirror [`blocking`](super::blocking), but take ownership of the
+//! buffers (see [`dma`](crate::dma)). Each method starts the transfer and returns a
+//! [`TransferHandle`] which owns the buffers until the transfer is complete, so the caller
+//! can do other work in the meantime. If the transfer fails, the buffers are returned along
+//! with the error.
+//!
+//! The handle doesn't borrow the I2C implementation. Starting a transfer while a previous
+//! one is still in progress waits for the previous transfer to complete first, its result
+//! is still returned by its own handle.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::dma::TransferHandle;
+//! use embedded_hal::i2c::dma::WriteRead;
+//!
+//! /// Reads a 32-byte FIFO into a buffer which stays with the driver
+//! struct Sensor<I2C> {
+//!     i2c: I2C,
+//!     fifo: Option<&'static mut [u8]>,
+//! }
+//!
+//! impl<I2C> Sensor<I2C>
+//! where
+//!     I2C: WriteRead<&'static [u8], &'static mut [u8]>,
+//! {
+//!     /// Reads the FIFO, running `work` while the transfer is in progress
+//!     pub fn read_fifo(&mut self, work: impl FnOnce()) -> Result<&[u8], I2C::Error> {
+//!         let fifo = self.fifo.take().unwrap();
+//!         let transfer = self.i2c.write_read(0x6A, &[0x3E], fifo);
+//!         work();
+//!         let (result, fifo) = match transfer.wait() {
+//!             Ok((_, fifo)) => (Ok(()), fifo),
+//!             Err((e, (_, fifo))) => (Err(e), fifo),
+//!         };
+//!         self.fifo = Some(fifo);
+//!         result?;
+//!         Ok(self.fifo.as_deref().unwrap())
+//!     }
+//! }
+//! ```
+
+use super::{AddressMode, Error, SevenBitAddress};
+use crate::dma::{ReadBuffer, TransferHandle, WriteBuffer};
+
+/// Read into an owned buffer
+pub trait Read<B: WriteBuffer<Word = u8>, A: AddressMode = SevenBitAddress> {
+    /// Error type
+    type Error: Error;
+
+    /// Handle of a read in progress
+    type Transfer: TransferHandle<Buffers = B, Error = Self::Error>;
+
+    /// Starts reading enough bytes from slave with `address` to fill `buffer`
+    ///
+    /// # I2C Events (contract)
+    ///
+    /// Same as [`blocking::Read::read`](super::blocking::Read::read)
+    fn read(&mut self, address: A, buffer: B) -> Self::Transfer;
+}
+
+impl<A: AddressMode, B: WriteBuffer<Word = u8>, T: Read<B, A>> Read<B, A> for &mut T {
+    type Error = T::Error;
+    type Transfer = T::Transfer;
+
+    fn read(&mut self, address: A, buffer: B) -> Self::Transfer {
+        T::read(self, address, buffer)
+    }
+}
+
+/// Write from an owned buffer
+pub trait Write<B: ReadBuffer<Word = u8>, A: AddressMode = SevenBitAddress> {
+    /// Error type
+    type Error: Error;
+
+    /// Handle of a write in progress
+    type Transfer: TransferHandle<Buffers = B, Error = Self::Error>;
+
+    /// Starts writing `bytes` to slave with address `address`
+    ///
+    /// # I2C Events (contract)
+    ///
+    /// Same as [`blocking::Write::write`](super::blocking::Write::write)
+    fn write(&mut self, address: A, bytes: B) -> Self::Transfer;
+}
+
+impl<A: AddressMode, B: ReadBuffer<Word = u8>, T: Write<B, A>> Write<B, A> for &mut T {
+    type Error = T::Error;
+    type Transfer = T::Transfer;
+
+    fn write(&mut self, address: A, bytes: B) -> Self::Transfer {
+        T::write(self, address, bytes)
+    }
+}
+
+/// Write and read with owned buffers
+pub trait WriteRead<W, R, A = SevenBitAddress>
+where
+    W: ReadBuffer<Word = u8>,
+    R: WriteBuffer<Word = u8>,
+    A: AddressMode,
+{
+    /// Error type
+    type Error: Error;
+
+    /// Handle of a write and read in progress
+    type Transfer: TransferHandle<Buffers = (W, R), Error = Self::Error>;
+
+    /// Starts writing `bytes` to slave with address `address` and then reading enough bytes
+    /// to fill `buffer` *in a single transaction*
+    ///
+    /// # I2C Events (contract)
+    ///
+    /// Same as [`blocking::WriteRead::write_read`](super::blocking::WriteRead::write_read)
+    fn write_read(&mut self, address: A, bytes: W, buffer: R) -> Self::Transfer;
+}
+
+impl<A, W, R, T> WriteRead<W, R, A> for &mut T
+where
+    A: AddressMode,
+    W: ReadBuffer<Word = u8>,
+    R: WriteBuffer<Word = u8>,
+    T: WriteRead<W, R, A>,
+{
+    type Error = T::Error;
+    type Transfer = T::Transfer;
+
+    fn write_read(&mut self, address: A, bytes: W, buffer: R) -> Self::Transfer {
+        T::write_read(self, address, bytes, buffer)
+    }
+}
@@ -0,0 +1,449 @@
+//! I2C traits
+//!
+//! This API supports 7-bit and 10-bit addresses. Traits feature an `AddressMode`
+//! marker type parameter. Two implementation of the `AddressMode` exist:
+//! `SevenBitAddress` and `TenBitAddress`.
+//!
+//! Through this marker types it is possible to implement each address mode for
+//! the traits independently in `embedded-hal` implementations and device drivers
+//! can depend only on the mode that they support.
+//!
+//! Additionally, the I2C 10-bit address mode has been developed to be fully
+//! backwards compatible with the 7-bit address mode. This allows for a
+//! software-emulated 10-bit addressing implementation if the address mode
+//! is not supported by the hardware, see [`ten_bit::TenBitAdapter`].
+//!
+//! Since 7-bit addressing is the mode of the majority of I2C devices,
+//! `SevenBitAddress` has been set as default mode and thus can be omitted if desired.
+//!
+//! ## Examples
+//!
+//! ### `embedded-hal` implementation for an MCU
+//! Here is an example of an embedded-hal implementation of the `Write` trait
+//! for both modes:
+//! ```
+//! # use embedded_hal::i2c::{ErrorKind, SevenBitAddress, TenBitAddress, blocking::Write};
+//! /// I2C0 hardware peripheral which supports both 7-bit and 10-bit addressing.
+//! pub struct I2c0;
+//!
+//! impl Write<SevenBitAddress> for I2c0
+//! {
+//! #   type Error = ErrorKind;
+//! #
+//!     fn write(&mut self, addr: u8, output: &[u8]) -> Result<(), Self::Error> {
+//!         // ...
+//! #       Ok(())
+//!     }
+//! }
+//!
+//! impl Write<TenBitAddress> for I2c0
+//! {
+//! #   type Error = ErrorKind;
+//! #
+//!     fn write(&mut self, addr: u16, output: &[u8]) -> Result<(), Self::Error> {
+//!         // ...
+//! #       Ok(())
+//!     }
+//! }
+//! ```
+//!
+//! ### Device driver compatible only with 7-bit addresses
+//!
+//! For demonstration purposes the address mode parameter has been omitted in this example.
+//!
+//! ```
+//! # use embedded_hal::i2c::{blocking::WriteRead, Error};
+//! const ADDR: u8  = 0x15;
+//! # const TEMP_REGISTER: u8 = 0x1;
+//! pub struct TemperatureSensorDriver<I2C> {
+//!     i2c: I2C,
+//! }
+//!
+//! impl<I2C, E: Error> TemperatureSensorDriver<I2C>
+//! where
+//!     I2C: WriteRead<Error = E>,
+//! {
+//!     pub fn read_temperature(&mut self) -> Result<u8, E> {
+//!         let mut temp = [0];
+//!         self.i2c
+//!             .write_read(ADDR, &[TEMP_REGISTER], &mut temp)
+//!             .and(Ok(temp[0]))
+//!     }
+//! }
+//! ```
+//!
+//! ### Device driver compatible only with 10-bit addresses
+//!
+//! ```
+//! # use embedded_hal::i2c::{Error, TenBitAddress, blocking::WriteRead};
+//! const ADDR: u16  = 0x158;
+//! # const TEMP_REGISTER: u8 = 0x1;
+//! pub struct TemperatureSensorDriver<I2C> {
+//!     i2c: I2C,
+//! }
+//!
+//! impl<I2C, E: Error> TemperatureSensorDriver<I2C>
+//! where
+//!     I2C: WriteRead<TenBitAddress, Error = E>,
+//! {
+//!     pub fn read_temperature(&mut self) -> Result<u8, E> {
+//!         let mut temp = [0];
+//!         self.i2c
+//!             .write_read(ADDR, &[TEMP_REGISTER], &mut temp)
+//!             .and(Ok(temp[0]))
+//!     }
+//! }
+//! ```
+//!
+//! ## Speed modes
+//!
+//! Implementations can state the speed mode their bus is configured for with the
+//! [`BusSpeed`] trait and one of the [`SpeedMode`] marker types. Drivers of devices
+//! which need a minimum clock rate, e.g. to read a FIFO fast enough, can then require
+//! it with [`AtLeast`] and a too slow bus is rejected at compile time.
+//!
+//! ```
+//! # use embedded_hal::i2c::{AtLeast, BusSpeed, FastMode, blocking::I2c};
+//! pub struct ImuDriver<I2C> {
+//!     i2c: I2C,
+//! }
+//!
+//! impl<I2C> ImuDriver<I2C>
+//! where
+//!     I2C: I2c + BusSpeed,
+//!     I2C::Mode: AtLeast<FastMode>,
+//! {
+//!     pub fn new(i2c: I2C) -> Self {
+//!         ImuDriver { i2c }
+//!     }
+//! }
+//! ```
+
+#[cfg(feature = "async")]
+pub mod asynch;
+pub mod bitbang;
+pub mod blocking;
+pub mod dma;
+pub mod mux;
+pub mod register;
+pub mod retry;
+pub mod shared;
+pub mod stats;
+pub mod target;
+pub mod ten_bit;
+#[cfg(feature = "async")]
+pub mod timeout;
+
+use crate::private;
+
+/// I2C error
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic I2C error kind
+    ///
+    /// By using this method, I2C errors freely defined by HAL implementations
+    /// can be converted to a set of generic I2C errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// I2C error kind
+///
+/// This represents a common set of I2C operation errors. HAL implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common I2C errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Bus error occurred. e.g. A START or a STOP condition is detected and is not
+    /// located after a multiple of 9 SCL clock pulses.
+    Bus,
+    /// The arbitration was lost, e.g. electrical problems with the clock signal
+    ArbitrationLoss,
+    /// A bus operation was not acknowledged, e.g. due to the addressed device not
+    /// being available on the bus or the device not being ready to process requests
+    /// at the moment
+    NoAcknowledge(NoAcknowledgeSource),
+    /// The peripheral receive buffer was overrun
+    Overrun,
+    /// The operation did not complete in time, e.g. because a target stretched
+    /// the clock for too long
+    ///
+    /// Implementations with hardware timeouts, such as the SMBus clock low timeout,
+    /// should report them with this kind.
+    Timeout,
+    /// A different error occurred. The original error may contain more information.
+    Other,
+}
+
+/// I2C no acknowledge error source
+///
+/// In cases where it is possible, a device should indicate if a no acknowledge
+/// response was received to an address versus a no acknowledge to a data byte.
+/// Where it is not possible to differentiate, `Unknown` should be indicated.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum NoAcknowledgeSource {
+    /// The device did not acknowledge its address. The device may be missing.
+    Address,
+    /// The device did not acknowledge the data. It may not be ready to process
+    /// requests at the moment.
+    Data,
+    /// Either the device did not acknowledge its address or the data, but it is
+    /// unknown which.
+    Unknown,
+}
+
+impl Error for ErrorKind {
+    fn kind(&self) -> ErrorKind {
+        *self
+    }
+}
+
+impl core::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Bus => write!(f, "Bus error occurred"),
+            Self::ArbitrationLoss => write!(f, "The arbitration was lost"),
+            Self::NoAcknowledge(s) => s.fmt(f),
+            Self::Overrun => write!(f, "The peripheral receive buffer was overrun"),
+            Self::Timeout => write!(f, "The operation did not complete in time"),
+            Self::Other => write!(
+                f,
+                "A different error occurred. The original error may contain more information"
+            ),
+        }
+    }
+}
+
+impl core::fmt::Display for NoAcknowledgeSource {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Address => write!(f, "The device did not acknowledge its address"),
+            Self::Data => write!(f, "The device did not acknowledge the data"),
+            Self::Unknown => write!(f, "The device did not acknowledge its address or the data"),
+        }
+    }
+}
+
+/// Address mode (7-bit / 10-bit)
+///
+/// Note: This trait is sealed and should not be implemented outside of this crate.
+pub trait AddressMode: private::Sealed {}
+
+/// 7-bit address mode type
+pub type SevenBitAddress = u8;
+
+/// 10-bit address mode type
+pub type TenBitAddress = u16;
+
+impl AddressMode for SevenBitAddress {}
+
+impl AddressMode for TenBitAddress {}
+
+/// Panics, or fails compilation if called in a const context
+///
+/// `panic!` cannot be used in const fns on the MSRV, but an out-of-bounds index
+/// fails const evaluation all the same.
+const fn invalid_address(address: usize) {
+    let invalid: [(); 0] = [];
+    invalid[address]
+}
+
+/// Validated 7-bit address
+///
+/// Addresses `0x00..=0x07` and `0x78..=0x7F` are reserved by the I2C specification
+/// (e.g. for the general call, CBUS, high-speed mode and 10-bit addressing) and are
+/// rejected, as are values which do not fit in 7 bits. Drivers can define their
+/// addresses as constants, so that a typo is caught at compile time:
+///
+/// ```
+/// use embedded_hal::i2c::Address7;
+///
+/// const ADDR: Address7 = Address7::new(0x76);
+/// assert_eq!(u8::from(ADDR), 0x76);
+/// ```
+///
+/// ```compile_fail
+/// use embedded_hal::i2c::Address7;
+///
+/// const ADDR: Address7 = Address7::new(0x7C);
+/// ```
+///
+/// The traits still take plain [`SevenBitAddress`]es, see [`Address7::get`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Address7(u8);
+
+impl Address7 {
+    /// Creates a new address
+    ///
+    /// # Panics
+    ///
+    /// Panics if `address` is reserved or does not fit in 7 bits. In a const context
+    /// this is a compile-time error.
+    pub const fn new(address: SevenBitAddress) -> Self {
+        match Self::try_new(address) {
+            Some(address) => address,
+            None => {
+                invalid_address(address as usize);
+                Address7(0)
+            }
+        }
+    }
+
+    /// Creates a new address, returning `None` if `address` is reserved or does not fit in 7 bits
+    pub const fn try_new(address: SevenBitAddress) -> Option<Self> {
+        match address {
+            0x08..=0x77 => Some(Address7(address)),
+            _ => None,
+        }
+    }
+
+    /// Returns the raw address
+    pub const fn get(self) -> SevenBitAddress {
+        self.0
+    }
+}
+
+impl From<Address7> for SevenBitAddress {
+    fn from(address: Address7) -> Self {
+        address.0
+    }
+}
+
+/// Validated 10-bit address
+///
+/// Values which do not fit in 10 bits are rejected. Like [`Address7`], it can be
+/// validated at compile time by defining it as a constant.
+///
+/// The traits still take plain [`TenBitAddress`]es, see [`Address10::get`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Address10(u16);
+
+impl Address10 {
+    /// Creates a new address
+    ///
+    /// # Panics
+    ///
+    /// Panics if `address` does not fit in 10 bits. In a const context this is a
+    /// compile-time error.
+    pub const fn new(address: TenBitAddress) -> Self {
+        match Self::try_new(address) {
+            Some(address) => address,
+            None => {
+                invalid_address(address as usize);
+                Address10(0)
+            }
+        }
+    }
+
+    /// Creates a new address, returning `None` if `address` does not fit in 10 bits
+    pub const fn try_new(address: TenBitAddress) -> Option<Self> {
+        if address <= 0x3FF {
+            Some(Address10(address))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the raw address
+    pub const fn get(self) -> TenBitAddress {
+        self.0
+    }
+}
+
+impl From<Address10> for TenBitAddress {
+    fn from(address: Address10) -> Self {
+        address.0
+    }
+}
+
+/// Speed mode (Standard-mode, Fast-mode, Fast-mode Plus, High-speed mode)
+///
+/// Note: This trait is sealed and should not be implemented outside of this crate.
+pub trait SpeedMode: private::Sealed {
+    /// Maximum clock frequency of this mode in Hz
+    const MAX_FREQUENCY_HZ: u32;
+}
+
+/// Standard-mode, up to 100 kHz
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct StandardMode;
+
+/// Fast-mode, up to 400 kHz
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct FastMode;
+
+/// Fast-mode Plus, up to 1 MHz
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct FastModePlus;
+
+/// High-speed mode, up to 3.4 MHz
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct HighSpeedMode;
+
+impl SpeedMode for StandardMode {
+    const MAX_FREQUENCY_HZ: u32 = 100_000;
+}
+
+impl SpeedMode for FastMode {
+    const MAX_FREQUENCY_HZ: u32 = 400_000;
+}
+
+impl SpeedMode for FastModePlus {
+    const MAX_FREQUENCY_HZ: u32 = 1_000_000;
+}
+
+impl SpeedMode for HighSpeedMode {
+    const MAX_FREQUENCY_HZ: u32 = 3_400_000;
+}
+
+/// Implemented by the speed modes which are at least as fast as `M`
+///
+/// Note: This trait is sealed and should not be implemented outside of this crate.
+pub trait AtLeast<M: SpeedMode>: SpeedMode {}
+
+macro_rules! at_least {
+    ($mode:ty: $($slower:ty),+) => {
+        $(impl AtLeast<$slower> for $mode {})+
+    };
+}
+
+at_least!(StandardMode: StandardMode);
+at_least!(FastMode: StandardMode, FastMode);
+at_least!(FastModePlus: StandardMode, FastMode, FastModePlus);
+at_least!(HighSpeedMode: StandardMode, FastMode, FastModePlus, HighSpeedMode);
+
+/// Speed mode of a bus
+pub trait BusSpeed {
+    /// Speed mode the bus is configured for
+    type Mode: SpeedMode;
+}
+
+impl<T: BusSpeed> BusSpeed for &mut T {
+    type Mode = T::Mode;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seven_bit_address_rejects_reserved_addresses() {
+        assert_eq!(Address7::try_new(0x07), None);
+        assert_eq!(Address7::try_new(0x08).map(Address7::get), Some(0x08));
+        assert_eq!(Address7::try_new(0x77).map(u8::from), Some(0x77));
+        assert_eq!(Address7::try_new(0x78), None);
+        assert_eq!(Address7::try_new(0x80), None);
+    }
+
+    #[test]
+    fn ten_bit_address_rejects_values_above_10_bits() {
+        assert_eq!(Address10::try_new(0x3FF).map(u16::from), Some(0x3FF));
+        assert_eq!(Address10::try_new(0x400), None);
+    }
+}
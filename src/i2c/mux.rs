@@ -0,0 +1,220 @@
+//! I2C multiplexers
+//!
+//! An I2C multiplexer (e.g. a TCA9548A) connects its upstream bus to one of
+//! several downstream bus segments. Devices with the same address can then be
+//! used on different segments.
+//!
+//! Multiplexer drivers implement [`SelectChannel`] as well as [`I2c`] for the
+//! upstream bus. [`Mux`] wraps such a driver and hands out a [`MuxChannel`] per
+//! downstream segment, which selects its segment before every call and implements
+//! [`I2c`] itself, so it can be passed to regular device drivers.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::i2c::blocking::I2c;
+//! use embedded_hal::i2c::mux::{Mux, SelectChannel};
+//!
+//! /// Reads the same register of two identical sensors on different segments
+//! fn read_both<M, E>(mux: M) -> Result<[u8; 2], E>
+//! where
+//!     M: I2c<Error = E> + SelectChannel<Error = E>,
+//! {
+//!     let mux = Mux::new(mux);
+//!     let mut left = mux.channel(0);
+//!     let mut right = mux.channel(1);
+//!
+//!     let mut values = [0; 2];
+//!     left.write_read(0x48, &[0x00], &mut values[..1])?;
+//!     right.write_read(0x48, &[0x00], &mut values[1..])?;
+//!     Ok(values)
+//! }
+//! ```
+
+use core::cell::RefCell;
+
+use super::blocking::{I2c, Operation};
+use super::{AddressMode, Error};
+
+/// Select the downstream segment of an I2C multiplexer
+pub trait SelectChannel {
+    /// Error type
+    type Error: Error;
+
+    /// Connects the upstream bus to the downstream segment `channel`
+    ///
+    /// Segments are numbered from 0. An error is returned if `channel` does not
+    /// exist on this multiplexer.
+    fn select_channel(&mut self, channel: u8) -> Result<(), Self::Error>;
+}
+
+impl<T: SelectChannel> SelectChannel for &mut T {
+    type Error = T::Error;
+
+    fn select_channel(&mut self, channel: u8) -> Result<(), Self::Error> {
+        T::select_channel(self, channel)
+    }
+}
+
+#[derive(Debug)]
+struct State<M> {
+    mux: M,
+    selected: Option<u8>,
+}
+
+/// Shared access to the downstream segments of a multiplexer
+///
+/// The selected segment is remembered, so the multiplexer is only reconfigured when
+/// a different [`MuxChannel`] is used than in the previous call.
+///
+/// # Panics
+///
+/// Like [`RefCellDevice`](super::shared::RefCellDevice), calling a method of a channel
+/// while another channel of the same multiplexer is executing one panics.
+#[derive(Debug)]
+pub struct Mux<M> {
+    state: RefCell<State<M>>,
+}
+
+impl<M> Mux<M> {
+    /// Creates a new multiplexer wrapper
+    ///
+    /// No segment is assumed to be selected initially.
+    pub fn new(mux: M) -> Self {
+        Mux {
+            state: RefCell::new(State {
+                mux,
+                selected: None,
+            }),
+        }
+    }
+
+    /// Returns a handle to the downstream segment `channel`
+    pub fn channel(&self, channel: u8) -> MuxChannel<'_, M> {
+        MuxChannel { mux: self, channel }
+    }
+
+    /// Releases the multiplexer driver
+    pub fn release(self) -> M {
+        self.state.into_inner().mux
+    }
+}
+
+/// Downstream segment of a [`Mux`]
+#[derive(Debug)]
+pub struct MuxChannel<'a, M> {
+    mux: &'a Mux<M>,
+    channel: u8,
+}
+
+impl<'a, M> MuxChannel<'a, M> {
+    fn run<R, E>(&mut self, f: impl FnOnce(&mut M) -> Result<R, E>) -> Result<R, E>
+    where
+        M: SelectChannel<Error = E>,
+    {
+        let mut state = self.mux.state.borrow_mut();
+        if state.selected != Some(self.channel) {
+            state.selected = None;
+            state.mux.select_channel(self.channel)?;
+            state.selected = Some(self.channel);
+        }
+        f(&mut state.mux)
+    }
+}
+
+impl<'a, A, M> I2c<A> for MuxChannel<'a, M>
+where
+    A: AddressMode,
+    M: I2c<A> + SelectChannel<Error = <M as I2c<A>>::Error>,
+{
+    type Error = <M as I2c<A>>::Error;
+
+    fn transaction<'b>(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'b>],
+    ) -> Result<(), Self::Error> {
+        self.run(|mux| mux.transaction(address, operations))
+    }
+
+    fn read(&mut self, address: A, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.run(|mux| mux.read(address, buffer))
+    }
+
+    fn write(&mut self, address: A, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.run(|mux| mux.write(address, bytes))
+    }
+
+    fn write_read(
+        &mut self,
+        address: A,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.run(|mux| mux.write_read(address, bytes, buffer))
+    }
+
+    fn probe(&mut self, address: A) -> Result<bool, Self::Error> {
+        self.run(|mux| mux.probe(address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c::ErrorKind;
+
+    #[derive(Default)]
+    struct Tca9548 {
+        selections: usize,
+        selected: u8,
+        last_write: Option<(u8, u8)>,
+    }
+
+    impl SelectChannel for Tca9548 {
+        type Error = ErrorKind;
+
+        fn select_channel(&mut self, channel: u8) -> Result<(), Self::Error> {
+            if channel >= 8 {
+                return Err(ErrorKind::Other);
+            }
+            self.selections += 1;
+            self.selected = channel;
+            Ok(())
+        }
+    }
+
+    impl I2c for Tca9548 {
+        type Error = ErrorKind;
+
+        fn transaction<'a>(
+            &mut self,
+            address: u8,
+            _operations: &mut [Operation<'a>],
+        ) -> Result<(), Self::Error> {
+            self.last_write = Some((self.selected, address));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn selects_channel_only_when_changed() {
+        let mux = Mux::new(Tca9548::default());
+        let mut a = mux.channel(2);
+        let mut b = mux.channel(5);
+
+        a.write(0x48, &[]).unwrap();
+        a.write(0x48, &[]).unwrap();
+        assert_eq!(mux.state.borrow().mux.last_write, Some((2, 0x48)));
+        b.write(0x49, &[]).unwrap();
+        assert_eq!(mux.state.borrow().mux.last_write, Some((5, 0x49)));
+
+        assert_eq!(mux.release().selections, 2);
+    }
+
+    #[test]
+    fn selection_error_is_returned() {
+        let mux = Mux::new(Tca9548::default());
+        assert_eq!(mux.channel(8).write(0x48, &[]), Err(ErrorKind::Other));
+    }
+}
@@ -0,0 +1,240 @@
+//! Register access
+//!
+//! Most I2C devices expose their functionality as a map of registers: a write
+//! transfer starting with the register address selects a register, further bytes
+//! are written to it, and a read following the register address reads it.
+//!
+//! [`RegisterRead`] and [`RegisterWrite`] capture this pattern for 8-bit and 16-bit
+//! register addresses and are implemented for every [`I2c`] implementation.
+//! 16-bit register addresses are sent most significant byte first.
+//!
+//! Multi-byte accesses rely on the device incrementing the register address after
+//! each byte ("auto-increment"). For devices without auto-increment, use the
+//! `_each` variants, which access every register in a separate transfer.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::i2c::register::{RegisterRead, RegisterWrite};
+//!
+//! const ADDR: u8 = 0x68;
+//! const PWR_MGMT: u8 = 0x6B;
+//! const ACCEL_OUT: u8 = 0x3B;
+//!
+//! fn read_acceleration<I2C, E>(i2c: &mut I2C) -> Result<[i16; 3], E>
+//! where
+//!     I2C: RegisterRead<Error = E> + RegisterWrite<Error = E>,
+//! {
+//!     i2c.write_register(ADDR, PWR_MGMT, 0x00)?;
+//!     let mut raw = [0; 6];
+//!     i2c.read_registers(ADDR, ACCEL_OUT, &mut raw)?;
+//!     Ok([
+//!         i16::from_be_bytes([raw[0], raw[1]]),
+//!         i16::from_be_bytes([raw[2], raw[3]]),
+//!         i16::from_be_bytes([raw[4], raw[5]]),
+//!     ])
+//! }
+//! ```
+
+use super::blocking::{I2c, Operation};
+use super::{AddressMode, Error, SevenBitAddress};
+use crate::private;
+
+/// Register address (8-bit or 16-bit)
+///
+/// Note: This trait is sealed and should not be implemented outside of this crate.
+pub trait RegisterAddress: private::Sealed + Copy {
+    /// Returns the bytes sent on the bus to select this register, followed by unused bytes
+    fn to_bytes(self) -> ([u8; 2], usize);
+
+    /// Returns the address of the register `offset` registers after this one
+    fn offset(self, offset: usize) -> Self;
+}
+
+impl RegisterAddress for u8 {
+    fn to_bytes(self) -> ([u8; 2], usize) {
+        ([self, 0], 1)
+    }
+
+    fn offset(self, offset: usize) -> Self {
+        self.wrapping_add(offset as u8)
+    }
+}
+
+impl RegisterAddress for u16 {
+    fn to_bytes(self) -> ([u8; 2], usize) {
+        (self.to_be_bytes(), 2)
+    }
+
+    fn offset(self, offset: usize) -> Self {
+        self.wrapping_add(offset as u16)
+    }
+}
+
+/// Read device registers
+pub trait RegisterRead<R: RegisterAddress = u8, A: AddressMode = SevenBitAddress> {
+    /// Error type
+    type Error: Error;
+
+    /// Reads consecutive registers starting at `register` into `buffer` in a single transfer
+    ///
+    /// The device must auto-increment the register address.
+    fn read_registers(
+        &mut self,
+        address: A,
+        register: R,
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error>;
+
+    /// Reads a single register
+    fn read_register(&mut self, address: A, register: R) -> Result<u8, Self::Error> {
+        let mut buffer = [0];
+        self.read_registers(address, register, &mut buffer)?;
+        Ok(buffer[0])
+    }
+
+    /// Reads consecutive registers starting at `register` into `buffer`, one transfer per register
+    fn read_registers_each(
+        &mut self,
+        address: A,
+        register: R,
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error>
+    where
+        A: Copy,
+    {
+        for (i, byte) in buffer.iter_mut().enumerate() {
+            *byte = self.read_register(address, register.offset(i))?;
+        }
+        Ok(())
+    }
+}
+
+impl<R: RegisterAddress, A: AddressMode, T: I2c<A>> RegisterRead<R, A> for T {
+    type Error = T::Error;
+
+    fn read_registers(
+        &mut self,
+        address: A,
+        register: R,
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let (bytes, len) = register.to_bytes();
+        self.write_read(address, &bytes[..len], buffer)
+    }
+}
+
+/// Write device registers
+pub trait RegisterWrite<R: RegisterAddress = u8, A: AddressMode = SevenBitAddress> {
+    /// Error type
+    type Error: Error;
+
+    /// Writes `bytes` to consecutive registers starting at `register` in a single transfer
+    ///
+    /// The device must auto-increment the register address.
+    fn write_registers(&mut self, address: A, register: R, bytes: &[u8])
+        -> Result<(), Self::Error>;
+
+    /// Writes a single register
+    fn write_register(&mut self, address: A, register: R, value: u8) -> Result<(), Self::Error> {
+        self.write_registers(address, register, &[value])
+    }
+
+    /// Writes `bytes` to consecutive registers starting at `register`, one transfer per register
+    fn write_registers_each(
+        &mut self,
+        address: A,
+        register: R,
+        bytes: &[u8],
+    ) -> Result<(), Self::Error>
+    where
+        A: Copy,
+    {
+        for (i, byte) in bytes.iter().enumerate() {
+            self.write_register(address, register.offset(i), *byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl<R: RegisterAddress, A: AddressMode, T: I2c<A>> RegisterWrite<R, A> for T {
+    type Error = T::Error;
+
+    fn write_registers(
+        &mut self,
+        address: A,
+        register: R,
+        bytes: &[u8],
+    ) -> Result<(), Self::Error> {
+        let (register, len) = register.to_bytes();
+        self.transaction(
+            address,
+            &mut [Operation::Write(&register[..len]), Operation::Write(bytes)],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c::ErrorKind;
+
+    /// Device with 16 registers and auto-increment
+    struct Device {
+        registers: [u8; 16],
+        transfers: usize,
+    }
+
+    impl I2c for Device {
+        type Error = ErrorKind;
+
+        fn transaction<'a>(
+            &mut self,
+            _address: u8,
+            operations: &mut [Operation<'a>],
+        ) -> Result<(), Self::Error> {
+            self.transfers += 1;
+            let mut pointer = None;
+            for operation in operations {
+                match operation {
+                    Operation::Write(bytes) => {
+                        for byte in bytes.iter() {
+                            match pointer {
+                                None => pointer = Some(*byte as usize),
+                                Some(p) => {
+                                    self.registers[p] = *byte;
+                                    pointer = Some(p + 1);
+                                }
+                            }
+                        }
+                    }
+                    Operation::Read(buffer) => {
+                        let p = pointer.unwrap();
+                        buffer.copy_from_slice(&self.registers[p..p + buffer.len()]);
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn burst_and_single_accesses() {
+        let mut device = Device {
+            registers: [0; 16],
+            transfers: 0,
+        };
+        device.write_registers(0x10, 4u8, &[1, 2, 3]).unwrap();
+        assert_eq!(device.read_register(0x10, 5u8), Ok(2));
+
+        let mut buffer = [0; 3];
+        device.read_registers_each(0x10, 4u8, &mut buffer).unwrap();
+        assert_eq!(buffer, [1, 2, 3]);
+        assert_eq!(device.transfers, 5);
+    }
+
+    #[test]
+    fn sixteen_bit_register_is_big_endian() {
+        assert_eq!(0x1234u16.to_bytes(), ([0x12, 0x34], 2));
+    }
+}
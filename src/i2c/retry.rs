@@ -0,0 +1,260 @@
+//! Automatic retries
+//!
+//! On multi-controller buses a transfer may lose arbitration, and some targets do
+//! not acknowledge their address while they are busy or waking up. [`Retry`] repeats
+//! calls which failed with such transient errors before reporting them.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::delay::blocking::DelayUs;
+//! use embedded_hal::i2c::blocking::I2c;
+//! use embedded_hal::i2c::retry::Retry;
+//!
+//! fn read_status<I2C: I2c, D: DelayUs>(i2c: I2C, delay: D) -> Option<u8> {
+//!     // Up to 3 retries, 500 us apart
+//!     let mut i2c = Retry::new(i2c, 3).with_delay(delay, 500);
+//!     let mut status = [0];
+//!     i2c.write_read(0x40, &[0x00], &mut status).ok()?;
+//!     Some(status[0])
+//! }
+//! ```
+
+use core::convert::Infallible;
+
+use super::blocking::{I2c, Operation};
+use super::{AddressMode, ErrorKind, NoAcknowledgeSource};
+use crate::delay::blocking::DelayUs;
+
+/// Default retry predicate of [`Retry`]
+///
+/// Returns `true` for arbitration losses and addresses which were not acknowledged.
+pub fn is_transient(kind: ErrorKind) -> bool {
+    matches!(
+        kind,
+        ErrorKind::ArbitrationLoss | ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)
+    )
+}
+
+/// Delay which does not wait, used by [`Retry`] unless a delay is configured
+#[derive(Debug, Default, Copy, Clone)]
+pub struct NoDelay;
+
+impl DelayUs for NoDelay {
+    type Error = Infallible;
+
+    fn delay_us(&mut self, _us: u32) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Error of a [`Retry`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error<E, DE> {
+    /// The inner I2C implementation returned an error, either one which should not be
+    /// retried or the error of the last attempt
+    I2c(E),
+    /// The delay returned an error
+    Delay(DE),
+}
+
+impl<E: super::Error, DE: core::fmt::Debug> super::Error for Error<E, DE> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::I2c(e) => e.kind(),
+            Self::Delay(_) => ErrorKind::Other,
+        }
+    }
+}
+
+/// I2C implementation retrying calls which failed with a transient error
+#[derive(Debug)]
+pub struct Retry<I2C, D = NoDelay, F = fn(ErrorKind) -> bool> {
+    i2c: I2C,
+    retries: usize,
+    predicate: F,
+    delay: D,
+    delay_us: u32,
+}
+
+impl<I2C> Retry<I2C> {
+    /// Creates a new I2C implementation which repeats failed calls up to `retries` times
+    ///
+    /// Errors are retried if [`is_transient`] returns `true`, without delay.
+    pub fn new(i2c: I2C, retries: usize) -> Self {
+        Retry {
+            i2c,
+            retries,
+            predicate: is_transient,
+            delay: NoDelay,
+            delay_us: 0,
+        }
+    }
+}
+
+impl<I2C, D, F> Retry<I2C, D, F> {
+    /// Waits `us` microseconds with `delay` before every retry
+    pub fn with_delay<D2: DelayUs>(self, delay: D2, us: u32) -> Retry<I2C, D2, F> {
+        Retry {
+            i2c: self.i2c,
+            retries: self.retries,
+            predicate: self.predicate,
+            delay,
+            delay_us: us,
+        }
+    }
+
+    /// Retries the errors for which `predicate` returns `true`
+    pub fn with_predicate<F2>(self, predicate: F2) -> Retry<I2C, D, F2>
+    where
+        F2: FnMut(ErrorKind) -> bool,
+    {
+        Retry {
+            i2c: self.i2c,
+            retries: self.retries,
+            predicate,
+            delay: self.delay,
+            delay_us: self.delay_us,
+        }
+    }
+
+    /// Releases the I2C implementation and the delay
+    pub fn release(self) -> (I2C, D) {
+        (self.i2c, self.delay)
+    }
+}
+
+impl<I2C, D, F> Retry<I2C, D, F>
+where
+    D: DelayUs,
+    F: FnMut(ErrorKind) -> bool,
+{
+    fn run<R, E: super::Error>(
+        &mut self,
+        mut f: impl FnMut(&mut I2C) -> Result<R, E>,
+    ) -> Result<R, Error<E, D::Error>> {
+        let mut retries = self.retries;
+        loop {
+            match f(&mut self.i2c) {
+                Err(e) if retries > 0 && (self.predicate)(e.kind()) => {
+                    retries -= 1;
+                    self.delay.delay_us(self.delay_us).map_err(Error::Delay)?;
+                }
+                result => return result.map_err(Error::I2c),
+            }
+        }
+    }
+}
+
+impl<A, I2C, D, F> I2c<A> for Retry<I2C, D, F>
+where
+    A: AddressMode + Copy,
+    I2C: I2c<A>,
+    D: DelayUs,
+    F: FnMut(ErrorKind) -> bool,
+{
+    type Error = Error<I2C::Error, D::Error>;
+
+    fn transaction<'a>(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'a>],
+    ) -> Result<(), Self::Error> {
+        self.run(|i2c| i2c.transaction(address, operations))
+    }
+
+    fn read(&mut self, address: A, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.run(|i2c| i2c.read(address, buffer))
+    }
+
+    fn write(&mut self, address: A, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.run(|i2c| i2c.write(address, bytes))
+    }
+
+    fn write_read(
+        &mut self,
+        address: A,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.run(|i2c| i2c.write_read(address, bytes, buffer))
+    }
+
+    fn probe(&mut self, address: A) -> Result<bool, Self::Error> {
+        self.run(|i2c| i2c.probe(address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fails with the given errors, then succeeds
+    struct Flaky<'a> {
+        errors: &'a [ErrorKind],
+        attempts: usize,
+    }
+
+    impl<'a> I2c for Flaky<'a> {
+        type Error = ErrorKind;
+
+        fn transaction<'b>(
+            &mut self,
+            _address: u8,
+            _operations: &mut [Operation<'b>],
+        ) -> Result<(), Self::Error> {
+            self.attempts += 1;
+            match self.errors.get(self.attempts - 1) {
+                Some(e) => Err(*e),
+                None => Ok(()),
+            }
+        }
+    }
+
+    struct Recorder(u32);
+
+    impl DelayUs for Recorder {
+        type Error = Infallible;
+
+        fn delay_us(&mut self, us: u32) -> Result<(), Self::Error> {
+            self.0 += us;
+            Ok(())
+        }
+    }
+
+    const ARB: ErrorKind = ErrorKind::ArbitrationLoss;
+
+    #[test]
+    fn retries_transient_errors_with_delay() {
+        let flaky = Flaky {
+            errors: &[ARB, ARB],
+            attempts: 0,
+        };
+        let mut i2c = Retry::new(flaky, 2).with_delay(Recorder(0), 100);
+        assert_eq!(i2c.write(0x40, &[1]), Ok(()));
+        let (flaky, delay) = i2c.release();
+        assert_eq!(flaky.attempts, 3);
+        assert_eq!(delay.0, 200);
+    }
+
+    #[test]
+    fn gives_up_after_retries() {
+        let flaky = Flaky {
+            errors: &[ARB, ARB, ARB],
+            attempts: 0,
+        };
+        let mut i2c = Retry::new(flaky, 1);
+        assert_eq!(i2c.write(0x40, &[1]), Err(Error::I2c(ARB)));
+        assert_eq!(i2c.release().0.attempts, 2);
+    }
+
+    #[test]
+    fn does_not_retry_other_errors() {
+        let flaky = Flaky {
+            errors: &[ErrorKind::Bus],
+            attempts: 0,
+        };
+        let mut i2c = Retry::new(flaky, 3).with_predicate(|kind| kind == ARB);
+        assert_eq!(i2c.write(0x40, &[1]), Err(Error::I2c(ErrorKind::Bus)));
+    }
+}
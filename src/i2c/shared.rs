@@ -0,0 +1,281 @@
+//! Shared I2C bus
+//!
+//! The types in this module wrap a single [`I2c`] implementation and hand out any
+//! number of devices which each implement [`I2c`] themselves, so that several
+//! drivers can share the same bus. Every call to the devices is forwarded as a
+//! whole, so transactions of different devices are never interleaved.
+//!
+//! The types differ in how the access to the bus is synchronized:
+//!
+//! - [`RefCellDevice`] uses a `RefCell`, it can only be used when all the devices
+//!   live in the same execution context (e.g. the same thread or interrupt priority).
+//! - [`CriticalSectionDevice`] uses a critical section from the [`critical-section`] crate.
+//!   Only available with the `critical-section` feature.
+//! - [`MutexDevice`] uses a `std::sync::Mutex`. Only available with the `std` feature.
+//!
+//! ## Example
+//!
+//! ```
+//! use core::cell::RefCell;
+//! use embedded_hal::i2c::blocking::I2c;
+//! use embedded_hal::i2c::shared::RefCellDevice;
+//!
+//! fn read_both<I2C: I2c>(i2c: I2C) -> Result<[u8; 2], I2C::Error> {
+//!     let bus = RefCell::new(i2c);
+//!     let mut sensor_a = RefCellDevice::new(&bus);
+//!     let mut sensor_b = RefCellDevice::new(&bus);
+//!
+//!     let mut values = [0; 2];
+//!     sensor_a.write_read(0x15, &[0x01], &mut values[..1])?;
+//!     sensor_b.write_read(0x16, &[0x01], &mut values[1..])?;
+//!     Ok(values)
+//! }
+//! ```
+//!
+//! [`critical-section`]: https://crates.io/crates/critical-section
+
+use core::cell::RefCell;
+
+use super::blocking::{BusRecovery, GeneralCall, I2c, Operation};
+use super::{AddressMode, SevenBitAddress};
+
+/// `RefCell`-based shared bus device
+///
+/// # Panics
+///
+/// Calling a method of this device while another device of the same bus is
+/// executing one (e.g. re-entrantly from an interrupt) panics.
+#[derive(Debug)]
+pub struct RefCellDevice<'a, T> {
+    bus: &'a RefCell<T>,
+}
+
+impl<'a, T> RefCellDevice<'a, T> {
+    /// Creates a new device on the shared `bus`
+    pub fn new(bus: &'a RefCell<T>) -> Self {
+        RefCellDevice { bus }
+    }
+}
+
+impl<'a, A: AddressMode, T: I2c<A>> I2c<A> for RefCellDevice<'a, T> {
+    type Error = T::Error;
+
+    fn transaction<'b>(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'b>],
+    ) -> Result<(), Self::Error> {
+        self.bus.borrow_mut().transaction(address, operations)
+    }
+
+    fn read(&mut self, address: A, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus.borrow_mut().read(address, buffer)
+    }
+
+    fn write(&mut self, address: A, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.bus.borrow_mut().write(address, bytes)
+    }
+
+    fn write_read(
+        &mut self,
+        address: A,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.bus.borrow_mut().write_read(address, bytes, buffer)
+    }
+
+    fn probe(&mut self, address: A) -> Result<bool, Self::Error> {
+        self.bus.borrow_mut().probe(address)
+    }
+}
+
+impl<'a, T: BusRecovery> BusRecovery for RefCellDevice<'a, T> {
+    type Error = T::Error;
+
+    fn recover(&mut self) -> Result<(), Self::Error> {
+        self.bus.borrow_mut().recover()
+    }
+}
+
+impl<'a, T: GeneralCall> GeneralCall for RefCellDevice<'a, T> {
+    type Error = T::Error;
+
+    fn general_call(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.bus.borrow_mut().general_call(bytes)
+    }
+
+    fn hardware_general_call(
+        &mut self,
+        controller_address: SevenBitAddress,
+        bytes: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.bus
+            .borrow_mut()
+            .hardware_general_call(controller_address, bytes)
+    }
+}
+
+/// Critical-section-based shared bus device
+///
+/// The bus is locked for the whole duration of each call, so interrupts are
+/// typically disabled while the I2C transfer is ongoing.
+#[cfg(feature = "critical-section")]
+#[derive(Debug)]
+pub struct CriticalSectionDevice<'a, T> {
+    bus: &'a critical_section::Mutex<RefCell<T>>,
+}
+
+#[cfg(feature = "critical-section")]
+impl<'a, T> CriticalSectionDevice<'a, T> {
+    /// Creates a new device on the shared `bus`
+    pub fn new(bus: &'a critical_section::Mutex<RefCell<T>>) -> Self {
+        CriticalSectionDevice { bus }
+    }
+
+    fn lock<R>(&mut self, f: impl FnOnce(&mut T) -> R) -> R {
+        critical_section::with(|cs| f(&mut self.bus.borrow_ref_mut(cs)))
+    }
+}
+
+#[cfg(feature = "critical-section")]
+impl<'a, A: AddressMode, T: I2c<A>> I2c<A> for CriticalSectionDevice<'a, T> {
+    type Error = T::Error;
+
+    fn transaction<'b>(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'b>],
+    ) -> Result<(), Self::Error> {
+        self.lock(|bus| bus.transaction(address, operations))
+    }
+
+    fn read(&mut self, address: A, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.lock(|bus| bus.read(address, buffer))
+    }
+
+    fn write(&mut self, address: A, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.lock(|bus| bus.write(address, bytes))
+    }
+
+    fn write_read(
+        &mut self,
+        address: A,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.lock(|bus| bus.write_read(address, bytes, buffer))
+    }
+
+    fn probe(&mut self, address: A) -> Result<bool, Self::Error> {
+        self.lock(|bus| bus.probe(address))
+    }
+}
+
+#[cfg(feature = "critical-section")]
+impl<'a, T: BusRecovery> BusRecovery for CriticalSectionDevice<'a, T> {
+    type Error = T::Error;
+
+    fn recover(&mut self) -> Result<(), Self::Error> {
+        self.lock(|bus| bus.recover())
+    }
+}
+
+#[cfg(feature = "critical-section")]
+impl<'a, T: GeneralCall> GeneralCall for CriticalSectionDevice<'a, T> {
+    type Error = T::Error;
+
+    fn general_call(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.lock(|bus| bus.general_call(bytes))
+    }
+
+    fn hardware_general_call(
+        &mut self,
+        controller_address: SevenBitAddress,
+        bytes: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.lock(|bus| bus.hardware_general_call(controller_address, bytes))
+    }
+}
+
+/// `std::sync::Mutex`-based shared bus device
+///
+/// # Panics
+///
+/// Panics if the mutex has been poisoned.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct MutexDevice<'a, T> {
+    bus: &'a std::sync::Mutex<T>,
+}
+
+#[cfg(feature = "std")]
+impl<'a, T> MutexDevice<'a, T> {
+    /// Creates a new device on the shared `bus`
+    pub fn new(bus: &'a std::sync::Mutex<T>) -> Self {
+        MutexDevice { bus }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, A: AddressMode, T: I2c<A>> I2c<A> for MutexDevice<'a, T> {
+    type Error = T::Error;
+
+    fn transaction<'b>(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'b>],
+    ) -> Result<(), Self::Error> {
+        self.bus.lock().unwrap().transaction(address, operations)
+    }
+
+    fn read(&mut self, address: A, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus.lock().unwrap().read(address, buffer)
+    }
+
+    fn write(&mut self, address: A, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.bus.lock().unwrap().write(address, bytes)
+    }
+
+    fn write_read(
+        &mut self,
+        address: A,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.bus.lock().unwrap().write_read(address, bytes, buffer)
+    }
+
+    fn probe(&mut self, address: A) -> Result<bool, Self::Error> {
+        self.bus.lock().unwrap().probe(address)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T: BusRecovery> BusRecovery for MutexDevice<'a, T> {
+    type Error = T::Error;
+
+    fn recover(&mut self) -> Result<(), Self::Error> {
+        self.bus.lock().unwrap().recover()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T: GeneralCall> GeneralCall for MutexDevice<'a, T> {
+    type Error = T::Error;
+
+    fn general_call(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.bus.lock().unwrap().general_call(bytes)
+    }
+
+    fn hardware_general_call(
+        &mut self,
+        controller_address: SevenBitAddress,
+        bytes: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.bus
+            .lock()
+            .unwrap()
+            .hardware_general_call(controller_address, bytes)
+    }
+}
@@ -0,0 +1,276 @@
+//! Bus statistics
+//!
+//! [`Statistics`] wraps an [`I2c`] implementation and counts the transactions,
+//! transferred bytes and errors per target address, e.g. for field diagnostics or
+//! soak tests.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::i2c::blocking::I2c;
+//! use embedded_hal::i2c::stats::Statistics;
+//!
+//! fn poll<I2C: I2c>(i2c: I2C) -> u32 {
+//!     let mut i2c = Statistics::new(i2c);
+//!     for _ in 0..100 {
+//!         let mut status = [0];
+//!         let _ = i2c.write_read(0x40, &[0x00], &mut status);
+//!     }
+//!     i2c.counters(0x40).nacks
+//! }
+//! ```
+
+use super::blocking::{I2c, Operation};
+use super::{AddressMode, Error, ErrorKind, SevenBitAddress};
+
+/// Number of addresses for which a [`Statistics`] keeps separate counters
+///
+/// Further addresses are only included in [`Statistics::total`].
+pub const MAX_ADDRESSES: usize = 16;
+
+/// Counters of a target address
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct Counters {
+    /// Number of calls, including failed ones
+    pub transactions: u32,
+    /// Number of bytes written in successful calls
+    pub bytes_written: u32,
+    /// Number of bytes read in successful calls
+    pub bytes_read: u32,
+    /// Number of calls which failed with [`ErrorKind::NoAcknowledge`], or probes which
+    /// found no device
+    pub nacks: u32,
+    /// Number of calls which failed with [`ErrorKind::ArbitrationLoss`]
+    pub arbitration_losses: u32,
+}
+
+impl Counters {
+    fn add(&mut self, other: &Counters) {
+        self.transactions = self.transactions.wrapping_add(other.transactions);
+        self.bytes_written = self.bytes_written.wrapping_add(other.bytes_written);
+        self.bytes_read = self.bytes_read.wrapping_add(other.bytes_read);
+        self.nacks = self.nacks.wrapping_add(other.nacks);
+        self.arbitration_losses = self
+            .arbitration_losses
+            .wrapping_add(other.arbitration_losses);
+    }
+}
+
+/// I2C implementation counting transactions, bytes and errors per address
+///
+/// Counters wrap around on overflow.
+#[derive(Debug)]
+pub struct Statistics<I2C, A = SevenBitAddress> {
+    i2c: I2C,
+    addresses: [Option<(A, Counters)>; MAX_ADDRESSES],
+    other: Counters,
+}
+
+impl<I2C, A: AddressMode + Copy + PartialEq> Statistics<I2C, A> {
+    /// Creates a new I2C implementation with all counters at zero
+    pub fn new(i2c: I2C) -> Self {
+        Statistics {
+            i2c,
+            addresses: [None; MAX_ADDRESSES],
+            other: Counters::default(),
+        }
+    }
+
+    /// Returns the counters of `address`
+    pub fn counters(&self, address: A) -> Counters {
+        self.addresses
+            .iter()
+            .flatten()
+            .find(|(a, _)| *a == address)
+            .map(|(_, counters)| *counters)
+            .unwrap_or_default()
+    }
+
+    /// Returns the sum of the counters of all addresses
+    pub fn total(&self) -> Counters {
+        let mut total = self.other;
+        for (_, counters) in self.addresses.iter().flatten() {
+            total.add(counters);
+        }
+        total
+    }
+
+    /// Sets all counters to zero
+    pub fn reset(&mut self) {
+        self.addresses = [None; MAX_ADDRESSES];
+        self.other = Counters::default();
+    }
+
+    /// Releases the I2C implementation
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+
+    fn record(&mut self, address: A, counters: Counters) {
+        let slot = match self
+            .addresses
+            .iter()
+            .position(|slot| matches!(slot, Some((a, _)) if *a == address))
+        {
+            Some(i) => Some(i),
+            None => self.addresses.iter().position(Option::is_none),
+        };
+        match slot {
+            Some(i) => {
+                self.addresses[i]
+                    .get_or_insert((address, Counters::default()))
+                    .1
+                    .add(&counters);
+            }
+            None => self.other.add(&counters),
+        }
+    }
+
+    fn run<R, E: Error>(
+        &mut self,
+        address: A,
+        written: usize,
+        read: usize,
+        f: impl FnOnce(&mut I2C) -> Result<R, E>,
+    ) -> Result<R, E> {
+        let result = f(&mut self.i2c);
+        let mut counters = Counters {
+            transactions: 1,
+            ..Counters::default()
+        };
+        match &result {
+            Ok(_) => {
+                counters.bytes_written = written as u32;
+                counters.bytes_read = read as u32;
+            }
+            Err(e) => match e.kind() {
+                ErrorKind::NoAcknowledge(_) => counters.nacks = 1,
+                ErrorKind::ArbitrationLoss => counters.arbitration_losses = 1,
+                _ => {}
+            },
+        }
+        self.record(address, counters);
+        result
+    }
+}
+
+impl<A, I2C> I2c<A> for Statistics<I2C, A>
+where
+    A: AddressMode + Copy + PartialEq,
+    I2C: I2c<A>,
+{
+    type Error = I2C::Error;
+
+    fn transaction<'a>(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'a>],
+    ) -> Result<(), Self::Error> {
+        let (mut written, mut read) = (0, 0);
+        for operation in operations.iter() {
+            match operation {
+                Operation::Read(buffer) => read += buffer.len(),
+                Operation::Write(bytes) => written += bytes.len(),
+            }
+        }
+        self.run(address, written, read, |i2c| {
+            i2c.transaction(address, operations)
+        })
+    }
+
+    fn read(&mut self, address: A, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.run(address, 0, buffer.len(), |i2c| i2c.read(address, buffer))
+    }
+
+    fn write(&mut self, address: A, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.run(address, bytes.len(), 0, |i2c| i2c.write(address, bytes))
+    }
+
+    fn write_read(
+        &mut self,
+        address: A,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let (written, read) = (bytes.len(), buffer.len());
+        self.run(address, written, read, |i2c| {
+            i2c.write_read(address, bytes, buffer)
+        })
+    }
+
+    fn probe(&mut self, address: A) -> Result<bool, Self::Error> {
+        let present = self.run(address, 0, 0, |i2c| i2c.probe(address))?;
+        if !present {
+            self.record(
+                address,
+                Counters {
+                    nacks: 1,
+                    ..Counters::default()
+                },
+            );
+        }
+        Ok(present)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c::NoAcknowledgeSource;
+
+    /// Only acknowledges address 0x10
+    struct Bus;
+
+    impl I2c for Bus {
+        type Error = ErrorKind;
+
+        fn transaction<'a>(
+            &mut self,
+            address: u8,
+            _operations: &mut [Operation<'a>],
+        ) -> Result<(), Self::Error> {
+            match address {
+                0x10 => Ok(()),
+                0x11 => Err(ErrorKind::ArbitrationLoss),
+                _ => Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+            }
+        }
+    }
+
+    #[test]
+    fn counts_per_address() {
+        let mut i2c = Statistics::new(Bus);
+        let mut buffer = [0; 4];
+        i2c.write_read(0x10, &[1, 2], &mut buffer).unwrap();
+        i2c.write(0x10, &[1]).unwrap();
+        i2c.read(0x11, &mut buffer).unwrap_err();
+        assert!(!i2c.probe(0x12).unwrap());
+
+        assert_eq!(
+            i2c.counters(0x10),
+            Counters {
+                transactions: 2,
+                bytes_written: 3,
+                bytes_read: 4,
+                nacks: 0,
+                arbitration_losses: 0,
+            }
+        );
+        assert_eq!(i2c.counters(0x11).arbitration_losses, 1);
+        assert_eq!(i2c.counters(0x12).nacks, 1);
+        assert_eq!(i2c.total().transactions, 4);
+    }
+
+    #[test]
+    fn addresses_beyond_capacity_count_towards_total() {
+        let mut i2c = Statistics::new(Bus);
+        for address in 0..=MAX_ADDRESSES as u8 {
+            let _ = i2c.write(0x20 + address, &[]);
+        }
+        assert_eq!(i2c.counters(0x20 + MAX_ADDRESSES as u8).transactions, 0);
+        assert_eq!(i2c.total().nacks, MAX_ADDRESSES as u32 + 1);
+
+        i2c.reset();
+        assert_eq!(i2c.total(), Counters::default());
+    }
+}
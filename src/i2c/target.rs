@@ -0,0 +1,129 @@
+//! I2C target (slave) mode API
+//!
+//! The traits in this module model the target side of an I2C bus, i.e. a
+//! peripheral which is addressed by an external controller. A HAL implementation
+//! reports bus activity as [`Event`]s through [`Listen`] and the data phase of
+//! each transfer is handled through [`Respond`].
+//!
+//! The address (or addresses) the peripheral answers to is configured when
+//! the HAL implementation is constructed and is out of scope for these traits.
+//!
+//! ## Example
+//!
+//! A generic register-map emulator answering `write_read` style accesses:
+//!
+//! ```
+//! use embedded_hal::i2c::target::{Event, Listen, Respond};
+//!
+//! pub fn serve<T, E>(target: &mut T, registers: &mut [u8; 16]) -> Result<(), E>
+//! where
+//!     T: Listen<Error = E> + Respond<Error = E>,
+//! {
+//!     let mut pointer = 0;
+//!     loop {
+//!         match target.listen()? {
+//!             Event::AddressMatch(_) => {}
+//!             Event::WriteReceived => {
+//!                 let mut buffer = [0; 2];
+//!                 let n = target.respond_to_write(&mut buffer)?;
+//!                 if n > 0 {
+//!                     pointer = buffer[0] as usize % registers.len();
+//!                 }
+//!                 if n > 1 {
+//!                     registers[pointer] = buffer[1];
+//!                 }
+//!             }
+//!             Event::ReadRequest => {
+//!                 target.respond_to_read(&registers[pointer..])?;
+//!             }
+//!             Event::Stop => return Ok(()),
+//!         }
+//!     }
+//! }
+//! ```
+
+use super::{AddressMode, Error, SevenBitAddress};
+
+/// I2C target event
+///
+/// Events are reported in bus order. A transfer starts with an
+/// [`AddressMatch`](Event::AddressMatch), followed by a [`ReadRequest`](Event::ReadRequest)
+/// or a [`WriteReceived`](Event::WriteReceived) for its data phase. A repeated start
+/// produces a new `AddressMatch` without an intermediate [`Stop`](Event::Stop).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Event<A: AddressMode = SevenBitAddress> {
+    /// A START or repeated START followed by one of the addresses of this
+    /// target was received. The matching address is provided.
+    AddressMatch(A),
+    /// The controller requests data from this target. The target must answer
+    /// with [`Respond::respond_to_read`].
+    ReadRequest,
+    /// The controller is writing data to this target. The data must be
+    /// collected with [`Respond::respond_to_write`].
+    WriteReceived,
+    /// A STOP condition ended the current transaction.
+    Stop,
+}
+
+/// Wait for I2C target events
+pub trait Listen<A: AddressMode = SevenBitAddress> {
+    /// Error type
+    type Error: Error;
+
+    /// Blocks until the next bus event concerning this target occurs
+    fn listen(&mut self) -> Result<Event<A>, Self::Error>;
+}
+
+impl<A: AddressMode, T: Listen<A>> Listen<A> for &mut T {
+    type Error = T::Error;
+
+    fn listen(&mut self) -> Result<Event<A>, Self::Error> {
+        T::listen(self)
+    }
+}
+
+/// Handle the data phase of an I2C target transfer
+pub trait Respond {
+    /// Error type
+    type Error: Error;
+
+    /// Answers a [`ReadRequest`](Event::ReadRequest) by sending `bytes` to the controller
+    ///
+    /// Returns the number of bytes that were clocked out by the controller. The controller
+    /// ends the transfer by not acknowledging a byte, so this may be less than `bytes.len()`.
+    /// If the controller keeps reading after `bytes` has been exhausted, the value of the
+    /// additional bytes is implementation-defined, typically `0xFF`.
+    ///
+    /// # I2C Events (contract)
+    ///
+    /// ``` text
+    /// Master: ST SAD+R        MAK    MAK ...    NMAK SP
+    /// Slave:           SAK B0     B1     ... BN
+    /// ```
+    fn respond_to_read(&mut self, bytes: &[u8]) -> Result<usize, Self::Error>;
+
+    /// Answers a [`WriteReceived`](Event::WriteReceived) by storing the written bytes in `buffer`
+    ///
+    /// Returns the number of bytes written by the controller. Bytes are acknowledged until
+    /// `buffer` is full, after which the target does not acknowledge further bytes.
+    ///
+    /// # I2C Events (contract)
+    ///
+    /// ``` text
+    /// Master: ST SAD+W     B0     B1     ... BN     SP
+    /// Slave:           SAK    SAK    SAK ...    SAK
+    /// ```
+    fn respond_to_write(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error>;
+}
+
+impl<T: Respond> Respond for &mut T {
+    type Error = T::Error;
+
+    fn respond_to_read(&mut self, bytes: &[u8]) -> Result<usize, Self::Error> {
+        T::respond_to_read(self, bytes)
+    }
+
+    fn respond_to_write(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+        T::respond_to_write(self, buffer)
+    }
+}
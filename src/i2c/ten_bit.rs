@@ -0,0 +1,314 @@
+//! Software 10-bit addressing
+//!
+//! A 10-bit address is transmitted as two bytes: `11110 A9 A8 R/W` followed by
+//! `A7..A0`. The first byte has the same shape as a 7-bit address in the reserved
+//! range `0x78..=0x7B`, so a controller which only supports 7-bit addressing can
+//! reach 10-bit targets by sending the second address byte as data.
+//!
+//! [`TenBitAdapter`] does exactly that and implements [`I2c<TenBitAddress>`] on top
+//! of any [`I2c<SevenBitAddress>`] implementation, along with the [`Read`], [`Write`],
+//! [`WriteRead`] and [`Transactional`] traits for 10-bit addresses. The iterator-based
+//! traits are not implemented, as [`I2c`] has no iterator-based transfers to build them on.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::i2c::blocking::I2c;
+//! use embedded_hal::i2c::ten_bit::{Error, TenBitAdapter};
+//! use embedded_hal::i2c::TenBitAddress;
+//!
+//! const ADDR: TenBitAddress = 0x158;
+//!
+//! fn read_id<I2C: I2c>(i2c: I2C) -> Result<u8, Error<I2C::Error>> {
+//!     let mut i2c = TenBitAdapter::new(i2c);
+//!     let mut id = [0];
+//!     i2c.write_read(ADDR, &[0x0F], &mut id)?;
+//!     Ok(id[0])
+//! }
+//! ```
+
+use super::blocking::{I2c, Operation, Read, Transactional, Write, WriteRead};
+use super::{Address10, ErrorKind, SevenBitAddress, TenBitAddress};
+
+/// Maximum number of operations in a transaction executed through a [`TenBitAdapter`]
+pub const MAX_OPERATIONS: usize = 16;
+
+/// Error of a [`TenBitAdapter`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error<E> {
+    /// The inner I2C implementation returned an error
+    I2c(E),
+    /// The address does not fit in 10 bits
+    InvalidAddress,
+    /// The transaction contains more than [`MAX_OPERATIONS`] operations
+    TooManyOperations,
+}
+
+impl<E: super::Error> super::Error for Error<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::I2c(e) => e.kind(),
+            Self::InvalidAddress | Self::TooManyOperations => ErrorKind::Other,
+        }
+    }
+}
+
+/// 10-bit addressing on top of a 7-bit I2C implementation
+#[derive(Debug)]
+pub struct TenBitAdapter<I2C> {
+    i2c: I2C,
+}
+
+impl<I2C> TenBitAdapter<I2C> {
+    /// Creates a new adapter
+    pub fn new(i2c: I2C) -> Self {
+        TenBitAdapter { i2c }
+    }
+
+    /// Releases the inner I2C implementation
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+}
+
+/// Splits a 10-bit address into the 7-bit header address and the second address byte
+fn split<E>(address: TenBitAddress) -> Result<(SevenBitAddress, u8), Error<E>> {
+    let address = Address10::try_new(address)
+        .ok_or(Error::InvalidAddress)?
+        .get();
+    Ok((0x78 | (address >> 8) as u8, address as u8))
+}
+
+impl<I2C: I2c<SevenBitAddress>> I2c<TenBitAddress> for TenBitAdapter<I2C> {
+    type Error = Error<I2C::Error>;
+
+    fn transaction<'a>(
+        &mut self,
+        address: TenBitAddress,
+        operations: &mut [Operation<'a>],
+    ) -> Result<(), Self::Error> {
+        const EMPTY: Operation<'static> = Operation::Write(&[]);
+
+        let (header, low) = split(address)?;
+        let low = [low];
+        if operations.len() > MAX_OPERATIONS {
+            return Err(Error::TooManyOperations);
+        }
+
+        // The second address byte is sent before the first operation and before
+        // every write which follows a read, i.e. whenever the controller sends
+        // a header with the R/W bit cleared. Reads after an SR only need the header.
+        //
+        // `[EMPTY; N]` would require Rust 1.50, as `Operation` is not `Copy`.
+        let mut framed: [Operation<'_>; 2 * MAX_OPERATIONS] = [
+            EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY,
+            EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY,
+            EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY, EMPTY,
+        ];
+        let mut len = 0;
+        let mut previous_read = true;
+        for operation in operations.iter_mut() {
+            let is_read = matches!(operation, Operation::Read(_));
+            if previous_read && !is_read || len == 0 {
+                framed[len] = Operation::Write(&low);
+                len += 1;
+            }
+            framed[len] = match operation {
+                Operation::Read(buffer) => Operation::Read(buffer),
+                Operation::Write(bytes) => Operation::Write(bytes),
+            };
+            len += 1;
+            previous_read = is_read;
+        }
+        if len == 0 {
+            framed[0] = Operation::Write(&low);
+            len = 1;
+        }
+
+        self.i2c
+            .transaction(header, &mut framed[..len])
+            .map_err(Error::I2c)
+    }
+
+    fn read(&mut self, address: TenBitAddress, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        let (header, low) = split(address)?;
+        self.i2c
+            .transaction(
+                header,
+                &mut [Operation::Write(&[low]), Operation::Read(buffer)],
+            )
+            .map_err(Error::I2c)
+    }
+
+    fn write(&mut self, address: TenBitAddress, bytes: &[u8]) -> Result<(), Self::Error> {
+        let (header, low) = split(address)?;
+        self.i2c
+            .transaction(
+                header,
+                &mut [Operation::Write(&[low]), Operation::Write(bytes)],
+            )
+            .map_err(Error::I2c)
+    }
+
+    fn write_read(
+        &mut self,
+        address: TenBitAddress,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let (header, low) = split(address)?;
+        self.i2c
+            .transaction(
+                header,
+                &mut [
+                    Operation::Write(&[low]),
+                    Operation::Write(bytes),
+                    Operation::Read(buffer),
+                ],
+            )
+            .map_err(Error::I2c)
+    }
+}
+
+impl<I2C: I2c<SevenBitAddress>> Read<TenBitAddress> for TenBitAdapter<I2C> {
+    type Error = Error<I2C::Error>;
+
+    fn read(&mut self, address: TenBitAddress, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        I2c::read(self, address, buffer)
+    }
+}
+
+impl<I2C: I2c<SevenBitAddress>> Write<TenBitAddress> for TenBitAdapter<I2C> {
+    type Error = Error<I2C::Error>;
+
+    fn write(&mut self, address: TenBitAddress, bytes: &[u8]) -> Result<(), Self::Error> {
+        I2c::write(self, address, bytes)
+    }
+}
+
+impl<I2C: I2c<SevenBitAddress>> WriteRead<TenBitAddress> for TenBitAdapter<I2C> {
+    type Error = Error<I2C::Error>;
+
+    fn write_read(
+        &mut self,
+        address: TenBitAddress,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        I2c::write_read(self, address, bytes, buffer)
+    }
+}
+
+impl<I2C: I2c<SevenBitAddress>> Transactional<TenBitAddress> for TenBitAdapter<I2C> {
+    type Error = Error<I2C::Error>;
+
+    fn exec<'a>(
+        &mut self,
+        address: TenBitAddress,
+        operations: &mut [Operation<'a>],
+    ) -> Result<(), Self::Error> {
+        I2c::transaction(self, address, operations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records the header address and the kinds and lengths of the operations
+    #[derive(Default)]
+    struct Recorder {
+        header: u8,
+        operations: [(bool, usize); 8],
+        len: usize,
+    }
+
+    impl I2c for Recorder {
+        type Error = ErrorKind;
+
+        fn transaction<'a>(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'a>],
+        ) -> Result<(), Self::Error> {
+            self.header = address;
+            self.len = operations.len();
+            for (record, operation) in self.operations.iter_mut().zip(operations.iter()) {
+                *record = match operation {
+                    Operation::Read(buffer) => (true, buffer.len()),
+                    Operation::Write(bytes) => (false, bytes.len()),
+                };
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn header_contains_upper_address_bits() {
+        let mut i2c = TenBitAdapter::new(Recorder::default());
+        I2c::write(&mut i2c, 0x358, &[1, 2]).unwrap();
+        let recorder = i2c.release();
+        assert_eq!(recorder.header, 0x7B);
+        assert_eq!(
+            &recorder.operations[..recorder.len],
+            &[(false, 1), (false, 2)]
+        );
+    }
+
+    #[test]
+    fn low_byte_is_resent_for_writes_after_reads() {
+        let mut i2c = TenBitAdapter::new(Recorder::default());
+        let mut a = [0; 3];
+        let mut b = [0; 4];
+        i2c.transaction(
+            0x158,
+            &mut [
+                Operation::Read(&mut a),
+                Operation::Write(&[1, 2]),
+                Operation::Read(&mut b),
+            ],
+        )
+        .unwrap();
+        let recorder = i2c.release();
+        assert_eq!(recorder.header, 0x79);
+        assert_eq!(
+            &recorder.operations[..recorder.len],
+            &[(false, 1), (true, 3), (false, 1), (false, 2), (true, 4)]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_address() {
+        let mut i2c = TenBitAdapter::new(Recorder::default());
+        assert_eq!(I2c::write(&mut i2c, 0x400, &[]), Err(Error::InvalidAddress));
+    }
+
+    #[test]
+    fn limits_operations_not_framed_length() {
+        let mut i2c = TenBitAdapter::new(Recorder::default());
+        let mut operations = [
+            Operation::Write(&[1]),
+            Operation::Write(&[2]),
+            Operation::Write(&[3]),
+            Operation::Write(&[4]),
+            Operation::Write(&[5]),
+            Operation::Write(&[6]),
+            Operation::Write(&[7]),
+            Operation::Write(&[8]),
+            Operation::Write(&[9]),
+            Operation::Write(&[10]),
+            Operation::Write(&[11]),
+            Operation::Write(&[12]),
+            Operation::Write(&[13]),
+            Operation::Write(&[14]),
+            Operation::Write(&[15]),
+            Operation::Write(&[16]),
+            Operation::Write(&[17]),
+        ];
+        assert_eq!(
+            i2c.exec(0x158, &mut operations),
+            Err(Error::TooManyOperations)
+        );
+        assert_eq!(i2c.exec(0x158, &mut operations[..MAX_OPERATIONS]), Ok(()));
+    }
+}
@@ -0,0 +1,226 @@
+//! I2C transfers with a deadline
+//!
+//! A target stretching the clock forever hangs every transfer on the bus. [`TimeoutI2c`]
+//! combines an async [`I2c`] implementation with an async delay and races every transfer
+//! against the delay, aborting it with [`ErrorKind::Timeout`] once the deadline has passed,
+//! similarly to the clock low timeout of SMBus.
+//!
+//! A transfer is aborted by dropping its future, so the state of the bus afterwards depends
+//! on the implementation. A target may still be holding SDA low, in which case
+//! [`BusRecovery`](super::blocking::BusRecovery) releases it.
+//!
+//! Blocking calls can't be interrupted from the context which made them. Blocking
+//! implementations which can hang on a misbehaving target should map their hardware timeouts
+//! to [`ErrorKind::Timeout`] instead.
+//!
+//! Only available with the `async` feature.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::delay::asynch::DelayUs;
+//! use embedded_hal::i2c::asynch::I2c;
+//! use embedded_hal::i2c::timeout::TimeoutI2c;
+//!
+//! /// Reads a sensor register, giving up after 10 ms
+//! async fn read_sensor<I2C: I2c, D: DelayUs>(i2c: I2C, delay: D) -> Option<u8> {
+//!     let mut i2c = TimeoutI2c::new(i2c, delay, 10_000);
+//!     let mut value = [0];
+//!     i2c.write_read(0x15, &[0x01], &mut value).await.ok()?;
+//!     Some(value[0])
+//! }
+//! ```
+
+use core::future::{poll_fn, Future};
+use core::pin::pin;
+use core::task::Poll;
+
+use super::asynch::{I2c, Operation};
+use super::{AddressMode, ErrorKind};
+use crate::delay::asynch::DelayUs;
+
+/// Error of a [`TimeoutI2c`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error<E, DE> {
+    /// The inner I2C implementation returned an error
+    I2c(E),
+    /// The delay returned an error
+    Delay(DE),
+    /// The transfer did not complete before the deadline and was aborted
+    Timeout,
+}
+
+impl<E: super::Error, DE: core::fmt::Debug> super::Error for Error<E, DE> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::I2c(e) => e.kind(),
+            Self::Delay(_) => ErrorKind::Other,
+            Self::Timeout => ErrorKind::Timeout,
+        }
+    }
+}
+
+/// Async I2C implementation aborting transfers which exceed a deadline
+#[derive(Debug)]
+pub struct TimeoutI2c<I2C, D> {
+    i2c: I2C,
+    delay: D,
+    timeout_us: u32,
+}
+
+impl<I2C, D> TimeoutI2c<I2C, D> {
+    /// Creates a new I2C implementation which aborts transfers taking longer than
+    /// `timeout_us` microseconds
+    pub fn new(i2c: I2C, delay: D, timeout_us: u32) -> Self {
+        TimeoutI2c {
+            i2c,
+            delay,
+            timeout_us,
+        }
+    }
+
+    /// Releases the I2C implementation and the delay
+    pub fn release(self) -> (I2C, D) {
+        (self.i2c, self.delay)
+    }
+}
+
+/// Runs `transfer`, dropping it if the delay completes first
+///
+/// If both complete in the same poll, the transfer wins, so a transfer which has succeeded
+/// is never reported as timed out.
+async fn run<F, E, D>(transfer: F, delay: &mut D, timeout_us: u32) -> Result<(), Error<E, D::Error>>
+where
+    F: Future<Output = Result<(), E>>,
+    D: DelayUs,
+{
+    let mut transfer = pin!(transfer);
+    let mut timeout = pin!(delay.delay_us(timeout_us));
+    poll_fn(|cx| {
+        if let Poll::Ready(result) = transfer.as_mut().poll(cx) {
+            return Poll::Ready(result.map_err(Error::I2c));
+        }
+        timeout.as_mut().poll(cx).map(|result| match result {
+            Ok(()) => Err(Error::Timeout),
+            Err(e) => Err(Error::Delay(e)),
+        })
+    })
+    .await
+}
+
+impl<A, I2C, D> I2c<A> for TimeoutI2c<I2C, D>
+where
+    A: AddressMode,
+    I2C: I2c<A>,
+    D: DelayUs,
+{
+    type Error = Error<I2C::Error, D::Error>;
+
+    async fn transaction<'a>(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'a>],
+    ) -> Result<(), Self::Error> {
+        let transfer = self.i2c.transaction(address, operations);
+        run(transfer, &mut self.delay, self.timeout_us).await
+    }
+
+    async fn read(&mut self, address: A, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        let transfer = self.i2c.read(address, buffer);
+        run(transfer, &mut self.delay, self.timeout_us).await
+    }
+
+    async fn write(&mut self, address: A, bytes: &[u8]) -> Result<(), Self::Error> {
+        let transfer = self.i2c.write(address, bytes);
+        run(transfer, &mut self.delay, self.timeout_us).await
+    }
+
+    async fn write_read(
+        &mut self,
+        address: A,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let transfer = self.i2c.write_read(address, bytes, buffer);
+        run(transfer, &mut self.delay, self.timeout_us).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::{BlockOn, Spin};
+    use crate::i2c::Error as _;
+    use core::cell::Cell;
+    use core::convert::Infallible;
+
+    /// Clock advancing by 1 µs per poll of a pending delay
+    struct Clock<'a>(&'a Cell<u32>);
+
+    impl DelayUs for Clock<'_> {
+        type Error = Infallible;
+
+        async fn delay_us(&mut self, us: u32) -> Result<(), Self::Error> {
+            let end = self.0.get() + us;
+            poll_fn(|_| {
+                if self.0.get() >= end {
+                    return Poll::Ready(Ok(()));
+                }
+                self.0.set(self.0.get() + 1);
+                Poll::Pending
+            })
+            .await
+        }
+    }
+
+    /// Bus on which every transaction takes `duration` µs, or hangs if it is `None`
+    struct Bus<'a> {
+        now: &'a Cell<u32>,
+        duration: Option<u32>,
+    }
+
+    impl I2c for Bus<'_> {
+        type Error = ErrorKind;
+
+        async fn transaction<'b>(
+            &mut self,
+            _address: u8,
+            operations: &mut [Operation<'b>],
+        ) -> Result<(), Self::Error> {
+            let end = self.duration.map(|duration| self.now.get() + duration);
+            poll_fn(|_| match end {
+                Some(end) if self.now.get() >= end => Poll::Ready(()),
+                _ => Poll::Pending,
+            })
+            .await;
+            for operation in operations {
+                if let Operation::Read(buffer) = operation {
+                    buffer.iter_mut().for_each(|byte| *byte = 0x42);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn i2c(now: &Cell<u32>, duration: Option<u32>) -> TimeoutI2c<Bus<'_>, Clock<'_>> {
+        TimeoutI2c::new(Bus { now, duration }, Clock(now), 100)
+    }
+
+    #[test]
+    fn slow_transfer_completes_before_deadline() {
+        let now = Cell::new(0);
+        let mut buffer = [0];
+        let result = Spin.block_on(i2c(&now, Some(99)).write_read(0x15, &[1], &mut buffer));
+        assert_eq!(result, Ok(()));
+        assert_eq!(buffer, [0x42]);
+    }
+
+    #[test]
+    fn aborts_hung_transfer() {
+        let now = Cell::new(0);
+        let result = Spin.block_on(i2c(&now, None).write(0x15, &[1]));
+        assert_eq!(result, Err(Error::Timeout));
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::Timeout);
+        assert_eq!(now.get(), 100);
+    }
+}
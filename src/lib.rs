@@ -404,26 +404,45 @@
 
 #![deny(missing_docs)]
 #![no_std]
+#![cfg_attr(feature = "async", allow(async_fn_in_trait))]
+
+#[cfg(feature = "mock")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 pub mod fmt;
 pub use nb;
+#[cfg(feature = "async")]
+pub mod adapter;
 pub mod adc;
 pub mod can;
 pub mod capture;
 pub mod delay;
 pub mod digital;
+pub mod dma;
 pub mod i2c;
+#[cfg(feature = "mock")]
+pub mod mock;
 pub mod pwm;
 pub mod qei;
 pub mod serial;
+pub mod smbus;
 pub mod spi;
 pub mod timer;
 pub mod watchdog;
 
 mod private {
-    use crate::i2c::{SevenBitAddress, TenBitAddress};
+    use crate::i2c::{
+        FastMode, FastModePlus, HighSpeedMode, SevenBitAddress, StandardMode, TenBitAddress,
+    };
     pub trait Sealed {}
 
     impl Sealed for SevenBitAddress {}
     impl Sealed for TenBitAddress {}
+    impl Sealed for u32 {}
+    impl Sealed for StandardMode {}
+    impl Sealed for FastMode {}
+    impl Sealed for FastModePlus {}
+    impl Sealed for HighSpeedMode {}
 }
@@ -0,0 +1,476 @@
+//! Mock digital pin implementation
+//!
+//! [`Mock`] implements the blocking digital input and output traits, and the async `Wait`
+//! trait if the `async` feature is enabled. Inputs return the levels programmed by the test,
+//! and outputs are checked against the expected levels, so handshaking sequences of drivers
+//! can be tested on the host. A single mock can serve as input and output, e.g. for a bit
+//! banged open-drain line.
+//!
+//! [`wire`] instead connects an output to an input, so that two drivers, or a driver and a
+//! model of a device, can be wired together. Levels set on the output are read on the input,
+//! and wake tasks waiting for them.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::digital::blocking::{InputPin, OutputPin};
+//! use embedded_hal::digital::PinState;
+//! use embedded_hal::mock::digital::{Mock, Transaction};
+//!
+//! let mut reset = Mock::new([Transaction::set(PinState::Low), Transaction::set(PinState::High)]);
+//! let mut busy = Mock::new([Transaction::get(PinState::High), Transaction::get(PinState::Low)]);
+//!
+//! reset.set_low().unwrap();
+//! reset.set_high().unwrap();
+//! while busy.is_high().unwrap() {}
+//!
+//! reset.done();
+//! busy.done();
+//! ```
+
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+#[cfg(feature = "async")]
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::convert::Infallible;
+
+use crate::digital::blocking::{InputPin, OutputPin, StatefulOutputPin, ToggleableOutputPin};
+#[cfg(feature = "async")]
+use crate::digital::interrupt::Edge;
+use crate::digital::interrupt::Trigger;
+use crate::digital::PinState;
+
+/// Expected interaction with a pin
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Transaction {
+    /// Reading the input level, answered with the contained state
+    Get(PinState),
+    /// Setting the output to the contained state
+    Set(PinState),
+    /// Reading the output state, answered with the contained state
+    GetState(PinState),
+    /// Toggling the output
+    Toggle,
+    /// Waiting for the contained condition, which is assumed to occur right away
+    Wait(Trigger),
+}
+
+impl Transaction {
+    /// Expects the input level to be read, answered with `state`
+    pub fn get(state: PinState) -> Self {
+        Transaction::Get(state)
+    }
+
+    /// Expects the output to be set to `state`
+    pub fn set(state: PinState) -> Self {
+        Transaction::Set(state)
+    }
+
+    /// Expects the output state to be read, answered with `state`
+    pub fn get_state(state: PinState) -> Self {
+        Transaction::GetState(state)
+    }
+
+    /// Expects the output to be toggled
+    pub fn toggle() -> Self {
+        Transaction::Toggle
+    }
+
+    /// Expects a wait for `trigger`
+    ///
+    /// [`Trigger::Level`] matches `wait_for_high` and `wait_for_low`, [`Trigger::Edge`] the
+    /// waits for edges.
+    pub fn wait(trigger: Trigger) -> Self {
+        Transaction::Wait(trigger)
+    }
+}
+
+/// Mock pin
+///
+/// # Panics
+///
+/// Every call panics if it does not match the next expected [`Transaction`].
+#[derive(Debug, Clone)]
+pub struct Mock {
+    expected: Rc<RefCell<VecDeque<Transaction>>>,
+}
+
+impl Mock {
+    /// Creates a mock expecting `transactions` in order
+    pub fn new(transactions: impl IntoIterator<Item = Transaction>) -> Self {
+        Mock {
+            expected: Rc::new(RefCell::new(transactions.into_iter().collect())),
+        }
+    }
+
+    /// Appends `transactions` to the expected transactions
+    pub fn expect(&mut self, transactions: impl IntoIterator<Item = Transaction>) {
+        self.expected.borrow_mut().extend(transactions);
+    }
+
+    /// Asserts that all expected transactions have been performed
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are expected transactions left.
+    pub fn done(&mut self) {
+        let expected = self.expected.borrow();
+        assert!(
+            expected.is_empty(),
+            "digital mock: {} expected transaction(s) not performed, next: {:?}",
+            expected.len(),
+            expected.front()
+        );
+    }
+
+    fn next(&self, call: &str) -> Transaction {
+        self.expected
+            .borrow_mut()
+            .pop_front()
+            .unwrap_or_else(|| panic!("digital mock: unexpected {}", call))
+    }
+
+    fn get(&self) -> PinState {
+        match self.next("input read") {
+            Transaction::Get(state) => state,
+            other => panic!("digital mock: expected {:?}, got input read", other),
+        }
+    }
+
+    fn set(&mut self, state: PinState) -> Result<(), Infallible> {
+        match self.next("output set") {
+            Transaction::Set(expected) => {
+                assert_eq!(state, expected, "digital mock: wrong output state");
+                Ok(())
+            }
+            other => panic!("digital mock: expected {:?}, got output set", other),
+        }
+    }
+
+    fn get_state(&self) -> PinState {
+        match self.next("output state read") {
+            Transaction::GetState(state) => state,
+            other => panic!("digital mock: expected {:?}, got output state read", other),
+        }
+    }
+
+    fn toggle(&mut self) -> Result<(), Infallible> {
+        match self.next("toggle") {
+            Transaction::Toggle => Ok(()),
+            other => panic!("digital mock: expected {:?}, got toggle", other),
+        }
+    }
+
+    #[cfg(feature = "async")]
+    fn wait(&mut self, trigger: Trigger) -> Result<(), Infallible> {
+        match self.next("wait") {
+            Transaction::Wait(expected) => {
+                assert_eq!(trigger, expected, "digital mock: wrong wait condition");
+                Ok(())
+            }
+            other => panic!("digital mock: expected {:?}, got wait", other),
+        }
+    }
+}
+
+impl InputPin for Mock {
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.get() == PinState::High)
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        Ok(self.get() == PinState::Low)
+    }
+}
+
+impl OutputPin for Mock {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.set(PinState::Low)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.set(PinState::High)
+    }
+
+    fn set_state(&mut self, state: PinState) -> Result<(), Self::Error> {
+        self.set(state)
+    }
+}
+
+impl StatefulOutputPin for Mock {
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.get_state() == PinState::High)
+    }
+
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        Ok(self.get_state() == PinState::Low)
+    }
+
+    /// Matches [`Transaction::Toggle`]
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        Mock::toggle(self)
+    }
+}
+
+impl ToggleableOutputPin for Mock {
+    type Error = Infallible;
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        Mock::toggle(self)
+    }
+}
+
+#[cfg(feature = "async")]
+impl crate::digital::asynch::Wait for Mock {
+    type Error = Infallible;
+
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        self.wait(Trigger::Level(PinState::High))
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        self.wait(Trigger::Level(PinState::Low))
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait(Trigger::Edge(Edge::Rising))
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait(Trigger::Edge(Edge::Falling))
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait(Trigger::Edge(Edge::Any))
+    }
+}
+
+#[derive(Debug)]
+struct Line {
+    level: PinState,
+    rising_edges: u32,
+    falling_edges: u32,
+    #[cfg(feature = "async")]
+    wakers: Vec<core::task::Waker>,
+}
+
+/// Creates a connected output and input, with the line initially low
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::digital::blocking::{InputPin, OutputPin};
+/// use embedded_hal::mock::digital::wire;
+///
+/// let (mut interrupt, line) = wire();
+/// assert!(line.is_low().unwrap());
+/// interrupt.set_high().unwrap();
+/// assert!(line.is_high().unwrap());
+/// ```
+pub fn wire() -> (WireOutput, WireInput) {
+    let line = Rc::new(RefCell::new(Line {
+        level: PinState::Low,
+        rising_edges: 0,
+        falling_edges: 0,
+        #[cfg(feature = "async")]
+        wakers: Vec::new(),
+    }));
+    (WireOutput { line: line.clone() }, WireInput { line })
+}
+
+/// Output end of a [`wire`]
+#[derive(Debug)]
+pub struct WireOutput {
+    line: Rc<RefCell<Line>>,
+}
+
+impl WireOutput {
+    fn set(&mut self, state: PinState) -> Result<(), Infallible> {
+        let mut line = self.line.borrow_mut();
+        match (line.level, state) {
+            (PinState::Low, PinState::High) => line.rising_edges += 1,
+            (PinState::High, PinState::Low) => line.falling_edges += 1,
+            _ => return Ok(()),
+        }
+        line.level = state;
+        #[cfg(feature = "async")]
+        for waker in line.wakers.drain(..) {
+            waker.wake();
+        }
+        Ok(())
+    }
+}
+
+impl OutputPin for WireOutput {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.set(PinState::Low)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.set(PinState::High)
+    }
+}
+
+impl StatefulOutputPin for WireOutput {
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.line.borrow().level == PinState::High)
+    }
+
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        Ok(self.line.borrow().level == PinState::Low)
+    }
+}
+
+/// Input end of a [`wire`]
+///
+/// Clones read the same line, so several drivers can listen to one output.
+#[derive(Debug, Clone)]
+pub struct WireInput {
+    line: Rc<RefCell<Line>>,
+}
+
+impl InputPin for WireInput {
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.line.borrow().level == PinState::High)
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        Ok(self.line.borrow().level == PinState::Low)
+    }
+}
+
+#[cfg(feature = "async")]
+impl WireInput {
+    /// Waits until `ready` returns `true` for the line
+    async fn wait(&mut self, ready: impl Fn(&Line) -> bool) -> Result<(), Infallible> {
+        core::future::poll_fn(|cx| {
+            let mut line = self.line.borrow_mut();
+            if ready(&line) {
+                return core::task::Poll::Ready(Ok(()));
+            }
+            if !line.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                line.wakers.push(cx.waker().clone());
+            }
+            core::task::Poll::Pending
+        })
+        .await
+    }
+}
+
+#[cfg(feature = "async")]
+impl crate::digital::asynch::Wait for WireInput {
+    type Error = Infallible;
+
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        self.wait(|line| line.level == PinState::High).await
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        self.wait(|line| line.level == PinState::Low).await
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        let edges = self.line.borrow().rising_edges;
+        self.wait(|line| line.rising_edges != edges).await
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        let edges = self.line.borrow().falling_edges;
+        self.wait(|line| line.falling_edges != edges).await
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        let edges = {
+            let line = self.line.borrow();
+            (line.rising_edges, line.falling_edges)
+        };
+        self.wait(|line| (line.rising_edges, line.falling_edges) != edges)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_sequence() {
+        let mut pin = Mock::new([
+            Transaction::set(PinState::Low),
+            Transaction::get_state(PinState::Low),
+            Transaction::toggle(),
+            Transaction::get(PinState::High),
+        ]);
+        pin.set_state(PinState::Low).unwrap();
+        assert_eq!(pin.is_set_low(), Ok(true));
+        ToggleableOutputPin::toggle(&mut pin).unwrap();
+        assert_eq!(pin.is_low(), Ok(false));
+        pin.done();
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn matches_waits() {
+        use crate::adapter::{BlockOn, Spin};
+        use crate::digital::asynch::Wait;
+
+        let mut pin = Mock::new([
+            Transaction::wait(Trigger::Level(PinState::High)),
+            Transaction::wait(Trigger::Edge(Edge::Falling)),
+        ]);
+        Spin.block_on(pin.wait_for_high()).unwrap();
+        Spin.block_on(pin.wait_for_falling_edge()).unwrap();
+        pin.done();
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn wire_wakes_waiting_tasks() {
+        use crate::digital::asynch::Wait;
+        use alloc::sync::Arc;
+        use alloc::task::Wake;
+        use core::future::Future;
+        use core::sync::atomic::{AtomicU32, Ordering};
+        use core::task::{Context, Poll, Waker};
+
+        struct Counter(AtomicU32);
+
+        impl Wake for Counter {
+            fn wake(self: Arc<Self>) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let counter = Arc::new(Counter(AtomicU32::new(0)));
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+        let (mut output, mut input) = wire();
+        output.set_high().unwrap();
+
+        let mut edge = core::pin::pin!(input.wait_for_rising_edge());
+        assert_eq!(edge.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(edge.as_mut().poll(&mut cx), Poll::Pending);
+        output.set_low().unwrap();
+        assert_eq!(counter.0.load(Ordering::Relaxed), 1);
+        assert_eq!(edge.as_mut().poll(&mut cx), Poll::Pending);
+        output.toggle().unwrap();
+        assert_eq!(counter.0.load(Ordering::Relaxed), 2);
+        assert_eq!(edge.as_mut().poll(&mut cx), Poll::Ready(Ok(())));
+    }
+
+    #[test]
+    #[should_panic(expected = "wrong output state")]
+    fn panics_on_wrong_state() {
+        let mut pin = Mock::new([Transaction::set(PinState::High)]);
+        let _ = pin.set_low();
+    }
+}
@@ -0,0 +1,204 @@
+//! Fault injection for I2C
+//!
+//! [`FaultInjector`] wraps an I2C implementation, e.g. a [`Mock`](super::i2c::Mock),
+//! and makes selected calls fail or return damaged data, so the error handling of a
+//! driver can be tested on the host.
+//!
+//! Calls are numbered from 0 in the order they are made on the injector; every
+//! method of [`I2c`] counts as one call.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::i2c::blocking::I2c;
+//! use embedded_hal::i2c::ErrorKind;
+//! use embedded_hal::mock::fault::{Error, Fault, FaultInjector};
+//! use embedded_hal::mock::i2c::{Mock, Transaction};
+//!
+//! let mock = Mock::new([Transaction::write_read(0x76, vec![0xD0], vec![0x60])]);
+//! let mut i2c = FaultInjector::new(mock);
+//! i2c.inject(0, Fault::Error(ErrorKind::ArbitrationLoss));
+//! i2c.inject(1, Fault::Corrupt { index: 0, mask: 0x01 });
+//!
+//! let mut id = [0];
+//! assert_eq!(
+//!     i2c.write_read(0x76, &[0xD0], &mut id),
+//!     Err(Error::Injected(ErrorKind::ArbitrationLoss))
+//! );
+//! i2c.write_read(0x76, &[0xD0], &mut id).unwrap();
+//! assert_eq!(id, [0x61]);
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::i2c::blocking::{I2c, Operation};
+use crate::i2c::{AddressMode, ErrorKind};
+
+/// Fault injected into a call
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Fault {
+    /// The call fails with the given error without reaching the inner implementation
+    Error(ErrorKind),
+    /// Only the first `len` bytes are read, the following ones read as `0xFF` like
+    /// an idle bus
+    TruncateRead(usize),
+    /// The byte read at `index` is XORed with `mask`
+    ///
+    /// `index` counts the bytes of all the read operations of the call.
+    Corrupt {
+        /// Index of the byte
+        index: usize,
+        /// Bits to flip
+        mask: u8,
+    },
+}
+
+/// Error of a [`FaultInjector`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error<E> {
+    /// The inner I2C implementation returned an error
+    I2c(E),
+    /// An error injected with [`Fault::Error`]
+    Injected(ErrorKind),
+}
+
+impl<E: crate::i2c::Error> crate::i2c::Error for Error<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::I2c(e) => e.kind(),
+            Self::Injected(kind) => *kind,
+        }
+    }
+}
+
+/// I2C implementation injecting faults into selected calls
+#[derive(Debug)]
+pub struct FaultInjector<I2C> {
+    i2c: I2C,
+    faults: Vec<(usize, Fault)>,
+    calls: usize,
+}
+
+impl<I2C> FaultInjector<I2C> {
+    /// Creates a new injector without any faults
+    pub fn new(i2c: I2C) -> Self {
+        FaultInjector {
+            i2c,
+            faults: Vec::new(),
+            calls: 0,
+        }
+    }
+
+    /// Injects `fault` into the call with number `call`
+    ///
+    /// Several faults can be injected into the same call.
+    pub fn inject(&mut self, call: usize, fault: Fault) {
+        self.faults.push((call, fault));
+    }
+
+    /// Returns the number of calls made so far
+    pub fn calls(&self) -> usize {
+        self.calls
+    }
+
+    /// Releases the inner I2C implementation
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+}
+
+impl<A: AddressMode, I2C: I2c<A>> I2c<A> for FaultInjector<I2C> {
+    type Error = Error<I2C::Error>;
+
+    fn transaction<'a>(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'a>],
+    ) -> Result<(), Self::Error> {
+        let call = self.calls;
+        self.calls += 1;
+        let faults = self.faults.iter().filter(|(c, _)| *c == call);
+
+        for (_, fault) in faults.clone() {
+            if let Fault::Error(kind) = fault {
+                return Err(Error::Injected(*kind));
+            }
+        }
+
+        self.i2c
+            .transaction(address, operations)
+            .map_err(Error::I2c)?;
+
+        let read = operations.iter_mut().flat_map(|operation| match operation {
+            Operation::Read(buffer) => buffer.iter_mut(),
+            Operation::Write(_) => [].iter_mut(),
+        });
+        for (i, byte) in read.enumerate() {
+            for (_, fault) in faults.clone() {
+                match *fault {
+                    Fault::TruncateRead(len) if i >= len => *byte = 0xFF,
+                    Fault::Corrupt { index, mask } if i == index => *byte ^= mask,
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::i2c::{ExpectedOperation, Mock, Transaction};
+    use alloc::vec;
+
+    #[test]
+    fn faults_only_affect_their_call() {
+        let mock = Mock::new([
+            Transaction::read(0x10, vec![1, 2, 3]),
+            Transaction::read(0x10, vec![1, 2, 3]),
+        ]);
+        let mut i2c = FaultInjector::new(mock.clone());
+        i2c.inject(1, Fault::Error(ErrorKind::Bus));
+        i2c.inject(2, Fault::TruncateRead(1));
+
+        let mut buffer = [0; 3];
+        i2c.read(0x10, &mut buffer).unwrap();
+        assert_eq!(buffer, [1, 2, 3]);
+        assert_eq!(
+            i2c.read(0x10, &mut buffer),
+            Err(Error::Injected(ErrorKind::Bus))
+        );
+        i2c.read(0x10, &mut buffer).unwrap();
+        assert_eq!(buffer, [1, 0xFF, 0xFF]);
+        assert_eq!(i2c.calls(), 3);
+        i2c.release().done();
+    }
+
+    #[test]
+    fn corrupt_counts_bytes_across_reads() {
+        let mock = Mock::new([Transaction::new(
+            0x10,
+            vec![
+                ExpectedOperation::Read(vec![0; 2]),
+                ExpectedOperation::Read(vec![0; 2]),
+            ],
+        )]);
+        let mut i2c = FaultInjector::new(mock);
+        i2c.inject(
+            0,
+            Fault::Corrupt {
+                index: 2,
+                mask: 0x80,
+            },
+        );
+
+        let (mut a, mut b) = ([0; 2], [0; 2]);
+        i2c.transaction(
+            0x10,
+            &mut [Operation::Read(&mut a), Operation::Read(&mut b)],
+        )
+        .unwrap();
+        assert_eq!((a, b), ([0, 0], [0x80, 0]));
+    }
+}
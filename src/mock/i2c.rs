@@ -0,0 +1,374 @@
+//! Mock I2C implementation
+//!
+//! [`Mock`] implements the blocking I2C traits for 7-bit addresses, and the async
+//! `I2c` trait if the `async` feature is enabled.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::i2c::blocking::I2c;
+//! use embedded_hal::i2c::ErrorKind;
+//! use embedded_hal::mock::i2c::{Mock, Transaction};
+//!
+//! let mut i2c = Mock::new([
+//!     Transaction::write_read(0x76, vec![0xD0], vec![0x60]),
+//!     Transaction::write(0x76, vec![0xE0, 0xB6]).with_error(ErrorKind::ArbitrationLoss),
+//! ]);
+//!
+//! let mut id = [0];
+//! i2c.write_read(0x76, &[0xD0], &mut id).unwrap();
+//! assert_eq!(id, [0x60]);
+//! assert_eq!(i2c.write(0x76, &[0xE0, 0xB6]), Err(ErrorKind::ArbitrationLoss));
+//!
+//! i2c.done();
+//! ```
+
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use crate::i2c::blocking::{self, Operation};
+use crate::i2c::{ErrorKind, SevenBitAddress};
+
+/// Expected operation of a [`Transaction`]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ExpectedOperation {
+    /// A read, answered with the contained bytes
+    Read(Vec<u8>),
+    /// A write of exactly the contained bytes
+    Write(Vec<u8>),
+}
+
+/// Expected I2C transaction
+///
+/// A transaction is matched by any method call performing the same operations,
+/// e.g. [`Transaction::write_read`] matches `write_read` as well as a
+/// `transaction` with a write and a read operation.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Transaction {
+    address: SevenBitAddress,
+    operations: Vec<ExpectedOperation>,
+    error: Option<ErrorKind>,
+}
+
+impl Transaction {
+    /// Expects a transaction with the given operations
+    pub fn new(address: SevenBitAddress, operations: Vec<ExpectedOperation>) -> Self {
+        Transaction {
+            address,
+            operations,
+            error: None,
+        }
+    }
+
+    /// Expects a read, answered with `response`
+    pub fn read(address: SevenBitAddress, response: Vec<u8>) -> Self {
+        Self::new(address, alloc::vec![ExpectedOperation::Read(response)])
+    }
+
+    /// Expects a write of `expected`
+    pub fn write(address: SevenBitAddress, expected: Vec<u8>) -> Self {
+        Self::new(address, alloc::vec![ExpectedOperation::Write(expected)])
+    }
+
+    /// Expects a write of `expected` followed by a read, answered with `response`
+    pub fn write_read(address: SevenBitAddress, expected: Vec<u8>, response: Vec<u8>) -> Self {
+        Self::new(
+            address,
+            alloc::vec![
+                ExpectedOperation::Write(expected),
+                ExpectedOperation::Read(response),
+            ],
+        )
+    }
+
+    /// Makes the transaction fail with `error` after the operations have been checked
+    ///
+    /// Read responses are still copied to the buffers.
+    pub fn with_error(mut self, error: ErrorKind) -> Self {
+        self.error = Some(error);
+        self
+    }
+}
+
+/// Mock I2C implementation
+///
+/// # Panics
+///
+/// Every call panics if it does not match the next expected [`Transaction`].
+#[derive(Debug, Clone)]
+pub struct Mock {
+    expected: Rc<RefCell<VecDeque<Transaction>>>,
+}
+
+impl Mock {
+    /// Creates a mock expecting `transactions` in order
+    pub fn new(transactions: impl IntoIterator<Item = Transaction>) -> Self {
+        Mock {
+            expected: Rc::new(RefCell::new(transactions.into_iter().collect())),
+        }
+    }
+
+    /// Appends `transactions` to the expected transactions
+    pub fn expect(&mut self, transactions: impl IntoIterator<Item = Transaction>) {
+        self.expected.borrow_mut().extend(transactions);
+    }
+
+    /// Asserts that all expected transactions have been performed
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are expected transactions left.
+    pub fn done(&mut self) {
+        let expected = self.expected.borrow();
+        assert!(
+            expected.is_empty(),
+            "i2c mock: {} expected transaction(s) not performed, next: {:?}",
+            expected.len(),
+            expected.front()
+        );
+    }
+
+    fn execute(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), ErrorKind> {
+        let transaction = self
+            .expected
+            .borrow_mut()
+            .pop_front()
+            .unwrap_or_else(|| panic!("i2c mock: unexpected transaction to {:#x}", address));
+        assert_eq!(address, transaction.address, "i2c mock: wrong address");
+        assert_eq!(
+            operations.len(),
+            transaction.operations.len(),
+            "i2c mock: wrong number of operations to {:#x}",
+            address
+        );
+        for (operation, expected) in operations.iter_mut().zip(transaction.operations.iter()) {
+            match (operation, expected) {
+                (Operation::Read(buffer), ExpectedOperation::Read(response)) => {
+                    assert_eq!(
+                        buffer.len(),
+                        response.len(),
+                        "i2c mock: wrong read length from {:#x}",
+                        address
+                    );
+                    buffer.copy_from_slice(response);
+                }
+                (Operation::Write(bytes), ExpectedOperation::Write(expected)) => {
+                    assert_eq!(
+                        *bytes,
+                        &expected[..],
+                        "i2c mock: wrong bytes written to {:#x}",
+                        address
+                    );
+                }
+                (operation, expected) => panic!(
+                    "i2c mock: expected {:?} at {:#x}, got {:?}",
+                    expected, address, operation
+                ),
+            }
+        }
+        match transaction.error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+impl blocking::Read for Mock {
+    type Error = ErrorKind;
+
+    fn read(&mut self, address: SevenBitAddress, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.execute(address, &mut [Operation::Read(buffer)])
+    }
+}
+
+impl blocking::Write for Mock {
+    type Error = ErrorKind;
+
+    fn write(&mut self, address: SevenBitAddress, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.execute(address, &mut [Operation::Write(bytes)])
+    }
+}
+
+impl blocking::WriteIter for Mock {
+    type Error = ErrorKind;
+
+    fn write_iter<B>(&mut self, address: SevenBitAddress, bytes: B) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        let bytes: Vec<u8> = bytes.into_iter().collect();
+        self.execute(address, &mut [Operation::Write(&bytes)])
+    }
+}
+
+impl blocking::WriteRead for Mock {
+    type Error = ErrorKind;
+
+    fn write_read(
+        &mut self,
+        address: SevenBitAddress,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.execute(
+            address,
+            &mut [Operation::Write(bytes), Operation::Read(buffer)],
+        )
+    }
+}
+
+impl blocking::WriteIterRead for Mock {
+    type Error = ErrorKind;
+
+    fn write_iter_read<B>(
+        &mut self,
+        address: SevenBitAddress,
+        bytes: B,
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        let bytes: Vec<u8> = bytes.into_iter().collect();
+        self.execute(
+            address,
+            &mut [Operation::Write(&bytes), Operation::Read(buffer)],
+        )
+    }
+}
+
+impl blocking::Transactional for Mock {
+    type Error = ErrorKind;
+
+    fn exec<'a>(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'a>],
+    ) -> Result<(), Self::Error> {
+        self.execute(address, operations)
+    }
+}
+
+impl blocking::TransactionalIter for Mock {
+    type Error = ErrorKind;
+
+    fn exec_iter<'a, O>(
+        &mut self,
+        address: SevenBitAddress,
+        operations: O,
+    ) -> Result<(), Self::Error>
+    where
+        O: IntoIterator<Item = Operation<'a>>,
+    {
+        let mut operations: Vec<Operation<'a>> = operations.into_iter().collect();
+        self.execute(address, &mut operations)
+    }
+}
+
+impl blocking::I2c for Mock {
+    type Error = ErrorKind;
+
+    fn transaction<'a>(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'a>],
+    ) -> Result<(), Self::Error> {
+        self.execute(address, operations)
+    }
+}
+
+#[cfg(feature = "async")]
+impl crate::i2c::asynch::I2c for Mock {
+    type Error = ErrorKind;
+
+    async fn transaction<'a>(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'a>],
+    ) -> Result<(), Self::Error> {
+        self.execute(address, operations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c::blocking::{I2c, Transactional, VectoredError, WriteIter};
+    use alloc::vec;
+
+    #[test]
+    fn matches_equivalent_calls() {
+        let mut i2c = Mock::new([
+            Transaction::write(0x10, vec![1, 2]),
+            Transaction::write(0x10, vec![3]),
+            Transaction::new(
+                0x10,
+                vec![
+                    ExpectedOperation::Write(vec![4]),
+                    ExpectedOperation::Read(vec![5, 6]),
+                ],
+            ),
+        ]);
+        I2c::write(&mut i2c, 0x10, &[1, 2]).unwrap();
+        i2c.write_iter(0x10, [3]).unwrap();
+        let mut buffer = [0; 2];
+        i2c.exec(
+            0x10,
+            &mut [Operation::Write(&[4]), Operation::Read(&mut buffer)],
+        )
+        .unwrap();
+        assert_eq!(buffer, [5, 6]);
+        i2c.done();
+    }
+
+    #[test]
+    fn vectored_write_read_is_one_transaction() {
+        let mut i2c = Mock::new([Transaction::new(
+            0x10,
+            vec![
+                ExpectedOperation::Write(vec![1]),
+                ExpectedOperation::Write(vec![2, 3]),
+                ExpectedOperation::Read(vec![4]),
+                ExpectedOperation::Read(vec![5, 6]),
+            ],
+        )]);
+        let (mut a, mut b) = ([0; 1], [0; 2]);
+        i2c.write_read_vectored(0x10, &[&[1], &[2, 3]], &mut [&mut a, &mut b])
+            .unwrap();
+        assert_eq!((a, b), ([4], [5, 6]));
+        let bytes: &[&[u8]] = &[&[][..]; 17];
+        assert_eq!(
+            i2c.write_read_vectored(0x10, bytes, &mut []),
+            Err(VectoredError::TooManyBuffers)
+        );
+        i2c.done();
+    }
+
+    #[test]
+    fn clones_share_expectations() {
+        let mut i2c = Mock::new([Transaction::read(0x10, vec![1])]);
+        let mut driver = i2c.clone();
+        let mut buffer = [0];
+        I2c::read(&mut driver, 0x10, &mut buffer).unwrap();
+        i2c.done();
+    }
+
+    #[test]
+    #[should_panic(expected = "wrong bytes written")]
+    fn panics_on_wrong_data() {
+        let mut i2c = Mock::new([Transaction::write(0x10, vec![1])]);
+        let _ = I2c::write(&mut i2c, 0x10, &[2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "not performed")]
+    fn done_panics_on_remaining_transactions() {
+        Mock::new([Transaction::write(0x10, vec![1])]).done();
+    }
+}
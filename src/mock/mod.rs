@@ -0,0 +1,16 @@
+//! Mock implementations for testing drivers
+//!
+//! The mocks are configured with the interactions the driver under test is expected
+//! to perform and panic as soon as it deviates from them, which fails the test.
+//! Clones of a mock share their expectations, so a clone can be kept by the test to
+//! call `done` after the driver has been dropped or has taken ownership of the mock.
+//!
+//! [`fault`] contains a wrapper for injecting bus errors and damaged data.
+//!
+//! Only available with the `mock` feature, which requires `alloc`.
+
+pub mod digital;
+pub mod fault;
+pub mod i2c;
+pub mod serial;
+pub mod spi;
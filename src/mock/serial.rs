@@ -0,0 +1,299 @@
+//! Loopback serial implementation
+//!
+//! [`Loopback`] implements the serial traits for bytes by returning every written byte to the
+//! reader, like a serial port whose TX and RX pins are connected. Protocol state machines can
+//! be tested on the host by writing a response before the state machine reads it, or by
+//! checking their own output. Clones share the same FIFO, so a test can keep a clone to feed
+//! and inspect the port used by the code under test.
+//!
+//! The FIFO can be bounded, in which case writes only accept as many bytes as fit. A latency
+//! makes newly written bytes invisible to the reader for a number of polls, so that code
+//! handling not-yet-available data is exercised as well.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::mock::serial::Loopback;
+//! use embedded_hal::serial::blocking::{Read, Write};
+//! use embedded_hal::serial::ReadReady;
+//!
+//! let mut serial = Loopback::new().with_latency(1);
+//! serial.write_all(b"OK\r\n").unwrap();
+//! assert!(!serial.read_ready().unwrap());
+//!
+//! let mut line = [0; 4];
+//! serial.read_exact(&mut line).unwrap();
+//! assert_eq!(&line, b"OK\r\n");
+//! ```
+
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use core::cell::RefCell;
+
+use crate::serial::{blocking, nb, ErrorKind, ReadReady, WriteReady};
+
+#[derive(Debug)]
+struct State {
+    fifo: VecDeque<u8>,
+    capacity: Option<usize>,
+    latency: u32,
+    /// Polls left until the last written bytes become readable
+    delay: u32,
+}
+
+impl State {
+    /// Counts down the latency, returning `true` if bytes are readable
+    fn poll_read(&mut self) -> bool {
+        if self.delay > 0 {
+            self.delay -= 1;
+            false
+        } else {
+            !self.fifo.is_empty()
+        }
+    }
+
+    fn free(&self) -> usize {
+        self.capacity
+            .map_or(usize::MAX, |capacity| capacity - self.fifo.len())
+    }
+
+    fn write(&mut self, words: &[u8]) -> usize {
+        let n = words.len().min(self.free());
+        self.fifo.extend(&words[..n]);
+        if n > 0 {
+            self.delay = self.latency;
+        }
+        n
+    }
+
+    fn read(&mut self, buffer: &mut [u8]) -> usize {
+        let n = buffer.len().min(self.fifo.len());
+        for (word, byte) in buffer.iter_mut().zip(self.fifo.drain(..n)) {
+            *word = byte;
+        }
+        n
+    }
+}
+
+/// Serial port reading back the bytes written to it
+///
+/// # Panics
+///
+/// Blocking and async reads, and waiting for received words, panic if the FIFO is empty, and
+/// writes panic if it is full, as they would never complete.
+#[derive(Debug, Clone)]
+pub struct Loopback {
+    state: Rc<RefCell<State>>,
+}
+
+impl Loopback {
+    /// Creates a new loopback with an unbounded FIFO and no latency
+    pub fn new() -> Self {
+        Loopback {
+            state: Rc::new(RefCell::new(State {
+                fifo: VecDeque::new(),
+                capacity: None,
+                latency: 0,
+                delay: 0,
+            })),
+        }
+    }
+
+    /// Bounds the FIFO to `capacity` bytes
+    pub fn with_capacity(self, capacity: usize) -> Self {
+        self.state.borrow_mut().capacity = Some(capacity);
+        self
+    }
+
+    /// Makes written bytes readable only after `polls` unsuccessful read attempts
+    ///
+    /// Read attempts are non-blocking reads and [`ReadReady::read_ready`] calls. Blocking and
+    /// async reads count down the latency before returning.
+    pub fn with_latency(self, polls: u32) -> Self {
+        self.state.borrow_mut().latency = polls;
+        self
+    }
+
+    /// Returns the number of bytes in the FIFO
+    pub fn len(&self) -> usize {
+        self.state.borrow().fifo.len()
+    }
+
+    /// Returns `true` if the FIFO is empty
+    pub fn is_empty(&self) -> bool {
+        self.state.borrow().fifo.is_empty()
+    }
+
+    fn read(&mut self, buffer: &mut [u8]) -> usize {
+        if buffer.is_empty() {
+            return 0;
+        }
+        let mut state = self.state.borrow_mut();
+        assert!(
+            !state.fifo.is_empty(),
+            "serial loopback: read from empty FIFO would never complete"
+        );
+        state.delay = 0;
+        state.read(buffer)
+    }
+
+    fn write(&mut self, words: &[u8]) -> usize {
+        let n = self.state.borrow_mut().write(words);
+        assert!(
+            n > 0 || words.is_empty(),
+            "serial loopback: write to full FIFO would never complete"
+        );
+        n
+    }
+}
+
+impl Default for Loopback {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl blocking::Read for Loopback {
+    type Error = ErrorKind;
+
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+        Ok(Loopback::read(self, buffer))
+    }
+}
+
+impl blocking::Write for Loopback {
+    type Error = ErrorKind;
+
+    fn write(&mut self, buffer: &[u8]) -> Result<usize, Self::Error> {
+        Ok(Loopback::write(self, buffer))
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl nb::Read for Loopback {
+    type Error = ErrorKind;
+
+    fn read(&mut self) -> ::nb::Result<u8, Self::Error> {
+        let mut state = self.state.borrow_mut();
+        if state.poll_read() {
+            Ok(state.fifo.pop_front().unwrap())
+        } else {
+            Err(::nb::Error::WouldBlock)
+        }
+    }
+}
+
+impl nb::Write for Loopback {
+    type Error = ErrorKind;
+
+    fn write(&mut self, word: u8) -> ::nb::Result<(), Self::Error> {
+        match self.state.borrow_mut().write(&[word]) {
+            0 => Err(::nb::Error::WouldBlock),
+            _ => Ok(()),
+        }
+    }
+
+    fn flush(&mut self) -> ::nb::Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl ReadReady for Loopback {
+    type Error = ErrorKind;
+
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.state.borrow_mut().poll_read())
+    }
+}
+
+impl WriteReady for Loopback {
+    type Error = ErrorKind;
+
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.state.borrow().free() > 0)
+    }
+}
+
+#[cfg(feature = "async")]
+impl crate::serial::asynch::Read for Loopback {
+    type Error = ErrorKind;
+
+    async fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+        Ok(Loopback::read(self, buffer))
+    }
+}
+
+#[cfg(feature = "async")]
+impl crate::serial::asynch::ReadReady for Loopback {
+    type Error = ErrorKind;
+
+    async fn wait_read_ready(&mut self) -> Result<(), Self::Error> {
+        let mut state = self.state.borrow_mut();
+        assert!(
+            !state.fifo.is_empty(),
+            "serial loopback: waiting on empty FIFO would never complete"
+        );
+        state.delay = 0;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl crate::serial::asynch::Write for Loopback {
+    type Error = ErrorKind;
+
+    async fn write(&mut self, buffer: &[u8]) -> Result<usize, Self::Error> {
+        Ok(Loopback::write(self, buffer))
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::blocking::{Read, Write};
+
+    #[test]
+    fn bounded_fifo_with_latency() {
+        let mut serial = Loopback::new().with_capacity(3).with_latency(2);
+        let mut feeder = serial.clone();
+        assert_eq!(Write::write(&mut feeder, &[1, 2, 3, 4]), Ok(3));
+        assert_eq!(serial.write_ready(), Ok(false));
+        assert_eq!(
+            nb::Write::write(&mut serial, 5),
+            Err(::nb::Error::WouldBlock)
+        );
+        assert_eq!(nb::Read::read(&mut serial), Err(::nb::Error::WouldBlock));
+        assert_eq!(serial.read_ready(), Ok(false));
+        assert_eq!(nb::Read::read(&mut serial), Ok(1));
+        let mut buffer = [0; 4];
+        assert_eq!(Read::read(&mut serial, &mut buffer), Ok(2));
+        assert_eq!(&buffer[..2], &[2, 3]);
+        assert!(feeder.is_empty());
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn wait_read_ready_keeps_words() {
+        use crate::adapter::{BlockOn, Spin};
+        use crate::serial::asynch::ReadReady as _;
+
+        let mut serial = Loopback::new().with_latency(3);
+        Write::write_all(&mut serial, b"A").unwrap();
+        Spin.block_on(serial.wait_read_ready()).unwrap();
+        assert_eq!(serial.len(), 1);
+        assert_eq!(nb::Read::read(&mut serial), Ok(b'A'));
+    }
+
+    #[test]
+    #[should_panic(expected = "would never complete")]
+    fn read_from_empty_fifo_panics() {
+        let _ = Read::read(&mut Loopback::new(), &mut [0]);
+    }
+}
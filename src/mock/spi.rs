@@ -0,0 +1,302 @@
+//! Mock SPI implementation
+//!
+//! [`Mock`] implements the blocking `SpiDevice` and `SpiDeviceHalfDuplex` traits for 8-bit
+//! words, and the async `SpiDevice` trait if the `async` feature is enabled. Each expected
+//! [`Transaction`] corresponds to one CS assertion.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::mock::spi::{Mock, Transaction};
+//! use embedded_hal::spi::blocking::{Operation, SpiDevice};
+//! use embedded_hal::spi::ErrorKind;
+//!
+//! let mut spi = Mock::new([
+//!     Transaction::transfer_in_place(vec![0x9F, 0, 0], vec![0xFF, 0xEF, 0x40]),
+//!     Transaction::write(vec![0x06]).with_error(ErrorKind::ModeFault),
+//! ]);
+//!
+//! let mut id = [0x9F, 0, 0];
+//! spi.transfer_in_place(&mut id).unwrap();
+//! assert_eq!(id, [0xFF, 0xEF, 0x40]);
+//! assert_eq!(spi.write(&[0x06]), Err(ErrorKind::ModeFault));
+//!
+//! spi.done();
+//! ```
+
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use crate::spi::blocking::{self, Operation};
+use crate::spi::ErrorKind;
+
+/// Expected operation of a [`Transaction`]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ExpectedOperation {
+    /// A read, answered with the contained words
+    Read(Vec<u8>),
+    /// A write of exactly the contained words
+    Write(Vec<u8>),
+    /// A transfer writing `expected` and answered with `response`
+    Transfer {
+        /// Expected written words
+        expected: Vec<u8>,
+        /// Words returned to the read buffer
+        response: Vec<u8>,
+    },
+    /// An in-place transfer writing `expected` and answered with `response`
+    TransferInPlace {
+        /// Expected written words
+        expected: Vec<u8>,
+        /// Words returned in the buffer
+        response: Vec<u8>,
+    },
+    /// A delay of exactly the contained number of microseconds
+    DelayUs(u32),
+}
+
+/// Expected SPI transaction
+///
+/// A transaction is matched by any method call performing the same operations, e.g.
+/// [`Transaction::transfer`] matches `transfer` as well as a `transaction` with a single
+/// transfer operation.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Transaction {
+    operations: Vec<ExpectedOperation>,
+    error: Option<ErrorKind>,
+}
+
+impl Transaction {
+    /// Expects a transaction with the given operations
+    pub fn new(operations: Vec<ExpectedOperation>) -> Self {
+        Transaction {
+            operations,
+            error: None,
+        }
+    }
+
+    /// Expects a read, answered with `response`
+    pub fn read(response: Vec<u8>) -> Self {
+        Self::new(alloc::vec![ExpectedOperation::Read(response)])
+    }
+
+    /// Expects a write of `expected`
+    pub fn write(expected: Vec<u8>) -> Self {
+        Self::new(alloc::vec![ExpectedOperation::Write(expected)])
+    }
+
+    /// Expects a transfer writing `expected`, answered with `response`
+    pub fn transfer(expected: Vec<u8>, response: Vec<u8>) -> Self {
+        Self::new(alloc::vec![ExpectedOperation::Transfer {
+            expected,
+            response
+        }])
+    }
+
+    /// Expects an in-place transfer writing `expected`, answered with `response`
+    pub fn transfer_in_place(expected: Vec<u8>, response: Vec<u8>) -> Self {
+        Self::new(alloc::vec![ExpectedOperation::TransferInPlace {
+            expected,
+            response
+        }])
+    }
+
+    /// Makes the transaction fail with `error` after the operations have been checked
+    ///
+    /// Read responses are still copied to the buffers.
+    pub fn with_error(mut self, error: ErrorKind) -> Self {
+        self.error = Some(error);
+        self
+    }
+}
+
+/// Mock SPI device
+///
+/// # Panics
+///
+/// Every call panics if it does not match the next expected [`Transaction`].
+#[derive(Debug, Clone)]
+pub struct Mock {
+    expected: Rc<RefCell<VecDeque<Transaction>>>,
+}
+
+impl Mock {
+    /// Creates a mock expecting `transactions` in order
+    pub fn new(transactions: impl IntoIterator<Item = Transaction>) -> Self {
+        Mock {
+            expected: Rc::new(RefCell::new(transactions.into_iter().collect())),
+        }
+    }
+
+    /// Appends `transactions` to the expected transactions
+    pub fn expect(&mut self, transactions: impl IntoIterator<Item = Transaction>) {
+        self.expected.borrow_mut().extend(transactions);
+    }
+
+    /// Asserts that all expected transactions have been performed
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are expected transactions left.
+    pub fn done(&mut self) {
+        let expected = self.expected.borrow();
+        assert!(
+            expected.is_empty(),
+            "spi mock: {} expected transaction(s) not performed, next: {:?}",
+            expected.len(),
+            expected.front()
+        );
+    }
+
+    fn execute(&mut self, operations: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        let transaction = self
+            .expected
+            .borrow_mut()
+            .pop_front()
+            .unwrap_or_else(|| panic!("spi mock: unexpected transaction {:?}", operations));
+        assert_eq!(
+            operations.len(),
+            transaction.operations.len(),
+            "spi mock: wrong number of operations"
+        );
+        for (operation, expected) in operations.iter_mut().zip(transaction.operations.iter()) {
+            match (operation, expected) {
+                (Operation::Read(buffer), ExpectedOperation::Read(response)) => {
+                    assert_eq!(buffer.len(), response.len(), "spi mock: wrong read length");
+                    buffer.copy_from_slice(response);
+                }
+                (Operation::Write(words), ExpectedOperation::Write(expected)) => {
+                    assert_eq!(*words, &expected[..], "spi mock: wrong words written");
+                }
+                (
+                    Operation::Transfer(read, write),
+                    ExpectedOperation::Transfer { expected, response },
+                ) => {
+                    assert_eq!(*write, &expected[..], "spi mock: wrong words written");
+                    assert_eq!(read.len(), response.len(), "spi mock: wrong read length");
+                    read.copy_from_slice(response);
+                }
+                (
+                    Operation::TransferInPlace(words),
+                    ExpectedOperation::TransferInPlace { expected, response },
+                ) => {
+                    assert_eq!(&words[..], &expected[..], "spi mock: wrong words written");
+                    assert_eq!(words.len(), response.len(), "spi mock: wrong read length");
+                    words.copy_from_slice(response);
+                }
+                (Operation::DelayUs(us), ExpectedOperation::DelayUs(expected)) => {
+                    assert_eq!(us, expected, "spi mock: wrong delay");
+                }
+                (operation, expected) => {
+                    panic!("spi mock: expected {:?}, got {:?}", expected, operation)
+                }
+            }
+        }
+        match transaction.error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+impl blocking::SpiDevice for Mock {
+    type Error = ErrorKind;
+
+    fn transaction<'a>(&mut self, operations: &mut [Operation<'a>]) -> Result<(), Self::Error> {
+        self.execute(operations)
+    }
+}
+
+impl blocking::SpiDeviceHalfDuplex for Mock {
+    type Error = ErrorKind;
+
+    /// Matches a transaction with a write and a read operation
+    fn write_then_read(&mut self, write: &[u8], read: &mut [u8]) -> Result<(), Self::Error> {
+        self.execute(&mut [Operation::Write(write), Operation::Read(read)])
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.execute(&mut [Operation::Write(words)])
+    }
+
+    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        self.execute(&mut [Operation::Read(words)])
+    }
+}
+
+#[cfg(feature = "async")]
+impl crate::spi::asynch::SpiDevice for Mock {
+    type Error = ErrorKind;
+
+    async fn transaction<'a>(
+        &mut self,
+        operations: &mut [Operation<'a>],
+    ) -> Result<(), Self::Error> {
+        self.execute(operations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spi::blocking::{SpiDevice, SpiDeviceHalfDuplex};
+    use alloc::vec;
+
+    #[test]
+    fn matches_equivalent_calls() {
+        let mut spi = Mock::new([
+            Transaction::transfer(vec![1, 2], vec![3, 4]),
+            Transaction::new(vec![
+                ExpectedOperation::Write(vec![5]),
+                ExpectedOperation::DelayUs(10),
+                ExpectedOperation::Read(vec![6]),
+            ]),
+            Transaction::new(vec![
+                ExpectedOperation::Write(vec![7]),
+                ExpectedOperation::Read(vec![8]),
+            ]),
+        ]);
+        let mut buffer = [0; 2];
+        SpiDevice::transfer(&mut spi, &mut buffer, &[1, 2]).unwrap();
+        assert_eq!(buffer, [3, 4]);
+        let mut buffer = [0];
+        spi.transaction(&mut [
+            Operation::Write(&[5]),
+            Operation::DelayUs(10),
+            Operation::Read(&mut buffer),
+        ])
+        .unwrap();
+        assert_eq!(buffer, [6]);
+        spi.write_then_read(&[7], &mut buffer).unwrap();
+        assert_eq!(buffer, [8]);
+        spi.done();
+    }
+
+    #[test]
+    fn reports_errors() {
+        let mut spi = Mock::new([Transaction::read(vec![1]).with_error(ErrorKind::Overrun)]);
+        let mut buffer = [0];
+        assert_eq!(
+            SpiDevice::read(&mut spi, &mut buffer),
+            Err(ErrorKind::Overrun)
+        );
+        assert_eq!(buffer, [1]);
+        spi.done();
+    }
+
+    #[test]
+    #[should_panic(expected = "wrong words written")]
+    fn panics_on_wrong_data() {
+        let mut spi = Mock::new([Transaction::transfer_in_place(vec![1], vec![2])]);
+        let _ = spi.transfer_in_place(&mut [3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected Write")]
+    fn panics_on_wrong_operation() {
+        let mut spi = Mock::new([Transaction::write(vec![1])]);
+        let _ = SpiDevice::read(&mut spi, &mut [0]);
+    }
+}
@@ -1,7 +1,468 @@
 //! Pulse Width Modulation
 
+use crate::digital::PinState;
+
+/// Single PWM channel with an integer duty cycle
+///
+/// Unlike [`blocking::PwmPin`], the duty cycle type is fixed, so drivers can compute duty
+/// cycles without knowing the implementation. Only the resolution, given by
+/// [`max_duty_cycle`](SetDutyCycle::max_duty_cycle), depends on the timer.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::pwm::{FractionError, SetDutyCycle};
+///
+/// /// Sets the brightness of an LED with gamma correction
+/// fn set_brightness<P: SetDutyCycle>(
+///     led: &mut P,
+///     percent: u8,
+/// ) -> Result<(), FractionError<P::Error>> {
+///     let percent = u16::from(percent.min(100));
+///     led.set_duty_cycle_fraction(percent * percent, 100 * 100)
+/// }
+/// ```
+pub trait SetDutyCycle {
+    /// Error type
+    type Error: core::fmt::Debug;
+
+    /// Returns the duty cycle which keeps the output active for the whole period
+    ///
+    /// This is usually the period of the timer in timer ticks.
+    fn max_duty_cycle(&self) -> u16;
+
+    /// Sets the duty cycle, from 0 (always inactive) to
+    /// [`max_duty_cycle`](SetDutyCycle::max_duty_cycle) (always active)
+    ///
+    /// Implementations should clamp larger values to the maximum. The new duty cycle may only
+    /// take effect at the start of the next period.
+    fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error>;
+
+    /// Sets the duty cycle to `numerator / denominator` of the period
+    ///
+    /// The duty cycle is rounded down to the resolution of the channel. Fractions larger than
+    /// one are clamped to [`max_duty_cycle`](SetDutyCycle::max_duty_cycle), a zero
+    /// `denominator` returns [`FractionError::ZeroDenominator`].
+    fn set_duty_cycle_fraction(
+        &mut self,
+        numerator: u16,
+        denominator: u16,
+    ) -> Result<(), FractionError<Self::Error>> {
+        if denominator == 0 {
+            return Err(FractionError::ZeroDenominator);
+        }
+        let duty = fraction(self.max_duty_cycle(), numerator, denominator);
+        self.set_duty_cycle(duty).map_err(FractionError::Pwm)
+    }
+
+    /// Sets the duty cycle to `percent` percent of the period
+    ///
+    /// The duty cycle is rounded down to the resolution of the channel. Percentages larger
+    /// than 100 are clamped to [`max_duty_cycle`](SetDutyCycle::max_duty_cycle).
+    fn set_duty_cycle_percent(&mut self, percent: u8) -> Result<(), Self::Error> {
+        let duty = fraction(self.max_duty_cycle(), u16::from(percent), 100);
+        self.set_duty_cycle(duty)
+    }
+}
+
+/// Returns `numerator / denominator` of `max`, rounded down and clamped to `max`
+fn fraction(max: u16, numerator: u16, denominator: u16) -> u16 {
+    let numerator = numerator.min(denominator);
+    (u32::from(numerator) * u32::from(max) / u32::from(denominator)) as u16
+}
+
+/// Error of [`SetDutyCycle::set_duty_cycle_fraction`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FractionError<E> {
+    /// The denominator of the fraction is zero
+    ZeroDenominator,
+    /// Setting the duty cycle failed
+    Pwm(E),
+}
+
+impl<T: SetDutyCycle> SetDutyCycle for &mut T {
+    type Error = T::Error;
+
+    fn max_duty_cycle(&self) -> u16 {
+        T::max_duty_cycle(self)
+    }
+
+    fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+        T::set_duty_cycle(self, duty)
+    }
+
+    fn set_duty_cycle_fraction(
+        &mut self,
+        numerator: u16,
+        denominator: u16,
+    ) -> Result<(), FractionError<Self::Error>> {
+        T::set_duty_cycle_fraction(self, numerator, denominator)
+    }
+
+    fn set_duty_cycle_percent(&mut self, percent: u8) -> Result<(), Self::Error> {
+        T::set_duty_cycle_percent(self, percent)
+    }
+}
+
+/// Several PWM channels of a timer updated at the same time
+///
+/// Setting the channels of a timer one after the other can let a period start with some
+/// channels at their new duty cycle and others at their old one, which shows as a flicker of
+/// RGB LEDs or a torque ripple of motors. Implementations write all compare registers while
+/// the update is held back, e.g. with preloaded registers latched on the update event, so
+/// the new duty cycles all start with the same period.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::pwm::SetDutyCycles;
+///
+/// /// Sets the color of an RGB LED on channels 0 to 2
+/// fn set_color<P: SetDutyCycles>(led: &mut P, [r, g, b]: [u8; 3]) -> Result<(), P::Error> {
+///     let max = u32::from(led.max_duty_cycle());
+///     let duty = |value: u8| (u32::from(value) * max / 255) as u16;
+///     led.set_duty_cycles(&[duty(r), duty(g), duty(b)])
+/// }
+/// ```
+pub trait SetDutyCycles {
+    /// Error type
+    type Error: core::fmt::Debug;
+
+    /// Returns the number of channels
+    fn channels(&self) -> usize;
+
+    /// Returns the duty cycle which keeps an output active for the whole period, the same
+    /// for all channels
+    fn max_duty_cycle(&self) -> u16;
+
+    /// Sets the duty cycle of channel `n` to `duties[n]`, taking effect at the start of the
+    /// same period for all channels
+    ///
+    /// Channels beyond the end of `duties` keep their duty cycle. Duty cycles range from 0 to
+    /// [`max_duty_cycle`](SetDutyCycles::max_duty_cycle), as for [`SetDutyCycle`].
+    ///
+    /// # Panics
+    ///
+    /// Implementations may panic if `duties` has more elements than there are channels.
+    fn set_duty_cycles(&mut self, duties: &[u16]) -> Result<(), Self::Error>;
+}
+
+impl<T: SetDutyCycles> SetDutyCycles for &mut T {
+    type Error = T::Error;
+
+    fn channels(&self) -> usize {
+        T::channels(self)
+    }
+
+    fn max_duty_cycle(&self) -> u16 {
+        T::max_duty_cycle(self)
+    }
+
+    fn set_duty_cycles(&mut self, duties: &[u16]) -> Result<(), Self::Error> {
+        T::set_duty_cycles(self, duties)
+    }
+}
+
+/// PWM channel or timer whose frequency can be changed at runtime
+///
+/// Buzzers play notes by changing the frequency, and switching regulators and motor drivers
+/// adapt it to the load. Timers derive the frequency from their clock with a prescaler and a
+/// period, so only some frequencies can be reached exactly. Changing the frequency usually
+/// changes the resolution of the duty cycle as well, so [`SetDutyCycle`] users must set the
+/// duty cycle again afterwards.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::pwm::{SetDutyCycle, SetFrequency};
+///
+/// /// Plays a note on a piezo buzzer, or silences it if `hz` is zero
+/// fn play<B>(buzzer: &mut B, hz: u32) -> Result<(), <B as SetFrequency>::Error>
+/// where
+///     B: SetFrequency + SetDutyCycle<Error = <B as SetFrequency>::Error>,
+/// {
+///     if hz == 0 {
+///         return buzzer.set_duty_cycle(0);
+///     }
+///     buzzer.set_frequency_hz(hz)?;
+///     buzzer.set_duty_cycle_percent(50)
+/// }
+/// ```
+pub trait SetFrequency {
+    /// Error type
+    type Error: core::fmt::Debug;
+
+    /// Sets the frequency to the one closest to `hz` the timer can generate, returning it
+    ///
+    /// Returns an error if `hz` is out of the range of the timer. Callers requiring a
+    /// certain accuracy compare the returned frequency with the requested one.
+    fn set_frequency_hz(&mut self, hz: u32) -> Result<u32, Self::Error>;
+
+    /// Returns the current frequency in Hz
+    fn frequency_hz(&self) -> u32;
+}
+
+impl<T: SetFrequency> SetFrequency for &mut T {
+    type Error = T::Error;
+
+    fn set_frequency_hz(&mut self, hz: u32) -> Result<u32, Self::Error> {
+        T::set_frequency_hz(self, hz)
+    }
+
+    fn frequency_hz(&self) -> u32 {
+        T::frequency_hz(self)
+    }
+}
+
+/// Output polarity of a PWM channel
+///
+/// The duty cycle is the fraction of the period in which the output is at its active level.
+///
+/// ```
+/// use embedded_hal::digital::PinState;
+/// use embedded_hal::pwm::Polarity;
+///
+/// assert_eq!(Polarity::ActiveLow.inactive_level(), PinState::High);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Polarity {
+    /// The output is high while active
+    ActiveHigh,
+    /// The output is low while active
+    ActiveLow,
+}
+
+impl Polarity {
+    /// Returns the level of the output while active
+    pub fn active_level(self) -> PinState {
+        match self {
+            Polarity::ActiveHigh => PinState::High,
+            Polarity::ActiveLow => PinState::Low,
+        }
+    }
+
+    /// Returns the level of the output while inactive or disabled
+    pub fn inactive_level(self) -> PinState {
+        !self.active_level()
+    }
+}
+
+/// Output polarity selection of a PWM channel
+pub trait SetPolarity {
+    /// Error type
+    type Error: core::fmt::Debug;
+
+    /// Sets the polarity of the output
+    ///
+    /// Takes effect immediately, including on a disabled output.
+    fn set_polarity(&mut self, polarity: Polarity) -> Result<(), Self::Error>;
+}
+
+impl<T: SetPolarity> SetPolarity for &mut T {
+    type Error = T::Error;
+
+    fn set_polarity(&mut self, polarity: Polarity) -> Result<(), Self::Error> {
+        T::set_polarity(self, polarity)
+    }
+}
+
+/// Enabling and disabling of a PWM channel
+///
+/// A disabled output is driven to the inactive level of its [`Polarity`], so that e.g. a gate
+/// driver is kept off while the timer is being configured.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::pwm::{EnableOutput, Polarity, SetDutyCycle, SetPolarity};
+///
+/// /// Starts driving a gate driver with an active low input, keeping it off until the duty
+/// /// cycle is set
+/// fn start<P, E>(gate: &mut P, duty: u16) -> Result<(), E>
+/// where
+///     P: EnableOutput<Error = E> + SetPolarity<Error = E> + SetDutyCycle<Error = E>,
+///     E: core::fmt::Debug,
+/// {
+///     gate.disable()?;
+///     gate.set_polarity(Polarity::ActiveLow)?;
+///     gate.set_duty_cycle(duty)?;
+///     gate.enable()
+/// }
+/// ```
+pub trait EnableOutput {
+    /// Error type
+    type Error: core::fmt::Debug;
+
+    /// Enables the output, starting the PWM signal
+    fn enable(&mut self) -> Result<(), Self::Error>;
+
+    /// Disables the output, driving it to its inactive level
+    ///
+    /// Returns once the output is at its inactive level, without waiting for the end of the
+    /// period.
+    fn disable(&mut self) -> Result<(), Self::Error>;
+
+    /// Returns whether the output is enabled
+    fn is_enabled(&self) -> bool;
+}
+
+impl<T: EnableOutput> EnableOutput for &mut T {
+    type Error = T::Error;
+
+    fn enable(&mut self) -> Result<(), Self::Error> {
+        T::enable(self)
+    }
+
+    fn disable(&mut self) -> Result<(), Self::Error> {
+        T::disable(self)
+    }
+
+    fn is_enabled(&self) -> bool {
+        T::is_enabled(self)
+    }
+}
+
+/// Break input configuration of a [`ComplementaryPwm`] timer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BreakConfig {
+    /// Level of the break input signalling a fault
+    pub active: PinState,
+    /// Whether the outputs are enabled again at the start of the first period after the
+    /// break input has become inactive, instead of waiting for
+    /// [`resume`](ComplementaryPwm::resume)
+    pub automatic_resume: bool,
+}
+
+impl BreakConfig {
+    /// Creates a new configuration for a break input which is active at `active`, without
+    /// automatic resume
+    pub const fn new(active: PinState) -> Self {
+        BreakConfig {
+            active,
+            automatic_resume: false,
+        }
+    }
+
+    /// Sets whether the outputs are enabled again once the break input is inactive
+    pub const fn with_automatic_resume(mut self, automatic_resume: bool) -> Self {
+        self.automatic_resume = automatic_resume;
+        self
+    }
+}
+
+/// Timer driving complementary output pairs, e.g. the high and low side switches of half
+/// bridges
+///
+/// The complementary output is active while the main output is inactive, except for a dead
+/// time after each edge in which both are inactive, so that the switches of a half bridge
+/// never conduct at the same time. The duty cycles of the main outputs are set with
+/// [`SetDutyCycle`] or [`SetDutyCycles`].
+///
+/// A break input, e.g. from an overcurrent comparator, disables all outputs in hardware
+/// within nanoseconds, without waiting for the software to react.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::digital::PinState;
+/// use embedded_hal::pwm::{BreakConfig, ComplementaryPwm};
+///
+/// /// Configures a half bridge whose gate driver needs 500 ns of dead time, with an
+/// /// overcurrent comparator pulling the break input low
+/// fn configure<P: ComplementaryPwm>(bridge: &mut P) -> Result<(), P::Error> {
+///     let dead_time_ns = bridge.set_dead_time_ns(500)?;
+///     assert!(dead_time_ns < 1_000);
+///     bridge.set_break_input(Some(BreakConfig::new(PinState::Low)))
+/// }
+/// ```
+pub trait ComplementaryPwm {
+    /// Error type
+    type Error: core::fmt::Debug;
+
+    /// Sets the dead time to at least `ns` nanoseconds, returning the actual dead time
+    ///
+    /// The dead time is rounded up to the resolution of the timer. Returns an error if it
+    /// exceeds the maximum dead time of the timer.
+    fn set_dead_time_ns(&mut self, ns: u32) -> Result<u32, Self::Error>;
+
+    /// Configures the break input, or disables it if `config` is `None`
+    ///
+    /// While a break is active, all outputs are driven to their inactive levels.
+    fn set_break_input(&mut self, config: Option<BreakConfig>) -> Result<(), Self::Error>;
+
+    /// Returns whether the outputs are disabled by a break
+    ///
+    /// The outputs stay disabled after the break input has become inactive, until
+    /// [`resume`](ComplementaryPwm::resume) is called, unless automatic resume is enabled.
+    fn is_break_active(&self) -> Result<bool, Self::Error>;
+
+    /// Enables the outputs again after a break
+    ///
+    /// Returns an error if the break input is still active.
+    fn resume(&mut self) -> Result<(), Self::Error>;
+}
+
+impl<T: ComplementaryPwm> ComplementaryPwm for &mut T {
+    type Error = T::Error;
+
+    fn set_dead_time_ns(&mut self, ns: u32) -> Result<u32, Self::Error> {
+        T::set_dead_time_ns(self, ns)
+    }
+
+    fn set_break_input(&mut self, config: Option<BreakConfig>) -> Result<(), Self::Error> {
+        T::set_break_input(self, config)
+    }
+
+    fn is_break_active(&self) -> Result<bool, Self::Error> {
+        T::is_break_active(self)
+    }
+
+    fn resume(&mut self) -> Result<(), Self::Error> {
+        T::resume(self)
+    }
+}
+
+/// Period and pulse width of a measured PWM signal
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Measurement {
+    /// Time between two rising edges in nanoseconds
+    pub period_ns: u32,
+    /// Time between a rising and the following falling edge in nanoseconds
+    pub high_ns: u32,
+}
+
+impl Measurement {
+    /// Returns the frequency in Hz, rounded down, or 0 if the period is 0
+    pub fn frequency_hz(&self) -> u32 {
+        1_000_000_000u32.checked_div(self.period_ns).unwrap_or(0)
+    }
+
+    /// Returns the time between a falling and the following rising edge in nanoseconds
+    pub fn low_ns(&self) -> u32 {
+        self.period_ns.saturating_sub(self.high_ns)
+    }
+
+    /// Returns the duty cycle scaled to `0..=max`, rounded down, or 0 if the period is 0
+    ///
+    /// Passing the [`max_duty_cycle`](SetDutyCycle::max_duty_cycle) of a channel gives the
+    /// duty cycle to reproduce the signal with that channel.
+    pub fn duty_cycle(&self, max: u16) -> u16 {
+        let high = u64::from(self.high_ns.min(self.period_ns));
+        (high * u64::from(max))
+            .checked_div(u64::from(self.period_ns))
+            .unwrap_or(0) as u16
+    }
+
+    /// Returns the duty cycle in percent, rounded down, or 0 if the period is 0
+    pub fn duty_cycle_percent(&self) -> u8 {
+        self.duty_cycle(100) as u8
+    }
+}
+
 /// Blocking pulse width modulation traits
 pub mod blocking {
+    use super::Measurement;
+
     /// Pulse Width Modulation
     ///
     /// # Examples
@@ -204,4 +665,147 @@ pub mod blocking {
             T::set_duty(self, duty)
         }
     }
+
+    /// Measurement of an external PWM signal, e.g. from a fan tachometer or an RC receiver
+    ///
+    /// Usually implemented with a timer capturing the counter on both edges of its input.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use embedded_hal::pwm::blocking::MeasurePwm;
+    ///
+    /// /// Returns the speed of a fan whose tachometer pulses twice per revolution
+    /// fn fan_rpm<M: MeasurePwm>(tachometer: &mut M) -> Result<u32, M::Error> {
+    ///     let measurement = tachometer.measure()?;
+    ///     Ok(measurement.frequency_hz() * 60 / 2)
+    /// }
+    /// ```
+    pub trait MeasurePwm {
+        /// Error type
+        ///
+        /// Possible errors:
+        ///
+        /// - the counter overflowed before a full period was captured, e.g. because the signal
+        ///   is constant or slower than the timer can measure
+        type Error: core::fmt::Debug;
+
+        /// Blocks until a full period of the signal has been captured and returns it
+        fn measure(&mut self) -> Result<Measurement, Self::Error>;
+    }
+
+    impl<T: MeasurePwm> MeasurePwm for &mut T {
+        type Error = T::Error;
+
+        fn measure(&mut self) -> Result<Measurement, Self::Error> {
+            T::measure(self)
+        }
+    }
+}
+
+/// Async pulse width modulation traits
+///
+/// Only available with the `async` feature.
+#[cfg(feature = "async")]
+pub mod asynch {
+    use super::Measurement;
+
+    /// Measurement of an external PWM signal (async variant)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use embedded_hal::pwm::asynch::MeasurePwm;
+    ///
+    /// /// Returns the throttle of an RC receiver channel in percent, for pulses from 1 to 2 ms
+    /// async fn throttle<M: MeasurePwm>(receiver: &mut M) -> Result<u8, M::Error> {
+    ///     let pulse_us = receiver.measure().await?.high_ns / 1_000;
+    ///     Ok((pulse_us.max(1_000).min(2_000) / 10 - 100) as u8)
+    /// }
+    /// ```
+    pub trait MeasurePwm {
+        /// Error type
+        type Error: core::fmt::Debug;
+
+        /// Waits until a full period of the signal has been captured and returns it
+        ///
+        /// Same contract as [`blocking::MeasurePwm::measure`](super::blocking::MeasurePwm::measure).
+        async fn measure(&mut self) -> Result<Measurement, Self::Error>;
+    }
+
+    impl<T: MeasurePwm> MeasurePwm for &mut T {
+        type Error = T::Error;
+
+        async fn measure(&mut self) -> Result<Measurement, Self::Error> {
+            T::measure(self).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+
+    /// Channel of a timer counting to 999
+    struct Channel(u16);
+
+    impl SetDutyCycle for Channel {
+        type Error = Infallible;
+
+        fn max_duty_cycle(&self) -> u16 {
+            999
+        }
+
+        fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+            self.0 = duty;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn fractions_round_down() {
+        let mut channel = Channel(0);
+        channel.set_duty_cycle_percent(100).unwrap();
+        assert_eq!(channel.0, 999);
+        channel.set_duty_cycle_percent(50).unwrap();
+        assert_eq!(channel.0, 499);
+        channel.set_duty_cycle_fraction(1, 3).unwrap();
+        assert_eq!(channel.0, 333);
+        channel.set_duty_cycle_fraction(u16::MAX, u16::MAX).unwrap();
+        assert_eq!(channel.0, 999);
+    }
+
+    #[test]
+    fn invalid_fractions() {
+        let mut channel = Channel(0);
+        channel.set_duty_cycle_fraction(3, 2).unwrap();
+        assert_eq!(channel.0, 999);
+        channel.set_duty_cycle_percent(0).unwrap();
+        channel.set_duty_cycle_percent(u8::MAX).unwrap();
+        assert_eq!(channel.0, 999);
+        assert_eq!(
+            channel.set_duty_cycle_fraction(1, 0),
+            Err(FractionError::ZeroDenominator)
+        );
+        assert_eq!(channel.0, 999);
+    }
+
+    #[test]
+    fn measurement() {
+        let measurement = Measurement {
+            period_ns: 40_000,
+            high_ns: 10_000,
+        };
+        assert_eq!(measurement.frequency_hz(), 25_000);
+        assert_eq!(measurement.low_ns(), 30_000);
+        assert_eq!(measurement.duty_cycle_percent(), 25);
+        assert_eq!(measurement.duty_cycle(999), 249);
+        let constant = Measurement {
+            period_ns: 0,
+            high_ns: 0,
+        };
+        assert_eq!(constant.frequency_hz(), 0);
+        assert_eq!(constant.duty_cycle(u16::MAX), 0);
+    }
 }
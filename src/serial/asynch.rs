@@ -0,0 +1,304 @@
+//! Async serial API
+//!
+//! The async counterparts of the [`blocking`](super::blocking) traits, with the same
+//! contracts. The methods return once at least one word has been transferred instead of
+//! blocking the caller.
+//!
+//! Only available with the `async` feature.
+
+/// Read half of a serial interface (async variant)
+pub trait Read<Word = u8> {
+    /// The type of error that can occur when reading
+    type Error: crate::serial::Error;
+
+    /// Reads words into `buffer`, returning once at least one word has been read
+    ///
+    /// Same contract as [`blocking::Read::read`](super::blocking::Read::read).
+    async fn read(&mut self, buffer: &mut [Word]) -> Result<usize, Self::Error>;
+
+    /// Reads enough words to fill `buffer`
+    ///
+    /// If an error occurs or the future is dropped, the number of words already read is
+    /// unspecified.
+    async fn read_exact(&mut self, mut buffer: &mut [Word]) -> Result<(), Self::Error> {
+        while !buffer.is_empty() {
+            let n = self.read(buffer).await?;
+            buffer = &mut buffer[n..];
+        }
+        Ok(())
+    }
+}
+
+impl<T: Read<Word>, Word> Read<Word> for &mut T {
+    type Error = T::Error;
+
+    async fn read(&mut self, buffer: &mut [Word]) -> Result<usize, Self::Error> {
+        T::read(self, buffer).await
+    }
+
+    async fn read_exact(&mut self, buffer: &mut [Word]) -> Result<(), Self::Error> {
+        T::read_exact(self, buffer).await
+    }
+}
+
+/// Waiting for received words without reading them
+///
+/// A task serving several serial interfaces can wait for all of them at once with a `select`
+/// combinator and then read from the one which is ready, without words being consumed by the
+/// futures of the others.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::serial::asynch::{Read, ReadReady};
+///
+/// /// Receives a command, waiting for it to start without holding a buffer
+/// async fn receive<S>(serial: &mut S, command: &mut [u8; 8]) -> Result<(), <S as Read>::Error>
+/// where
+///     S: Read + ReadReady<Error = <S as Read>::Error>,
+/// {
+///     serial.wait_read_ready().await?;
+///     serial.read_exact(command).await
+/// }
+/// ```
+pub trait ReadReady {
+    /// The type of error that can occur when waiting
+    type Error: crate::serial::Error;
+
+    /// Returns once at least one word has been received, so that a read returns right away
+    ///
+    /// No word is consumed, so the future can be dropped at any time without losing data.
+    async fn wait_read_ready(&mut self) -> Result<(), Self::Error>;
+}
+
+impl<T: ReadReady> ReadReady for &mut T {
+    type Error = T::Error;
+
+    async fn wait_read_ready(&mut self) -> Result<(), Self::Error> {
+        T::wait_read_ready(self).await
+    }
+}
+
+/// Reading of packets delimited by idle line gaps
+///
+/// Packetized protocols such as Modbus RTU separate their messages by a gap in which the line
+/// stays idle. UART peripherals with idle line detection can read such a packet in one call.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::serial::asynch::ReadUntilIdle;
+///
+/// /// Receives a Modbus RTU frame, returning its length
+/// async fn receive_frame<S: ReadUntilIdle>(
+///     serial: &mut S,
+///     frame: &mut [u8; 256],
+/// ) -> Result<usize, S::Error> {
+///     loop {
+///         let n = serial.read_until_idle(frame).await?;
+///         // Frames are at least 4 bytes long, anything shorter is noise
+///         if n >= 4 {
+///             return Ok(n);
+///         }
+///     }
+/// }
+/// ```
+pub trait ReadUntilIdle<Word = u8> {
+    /// The type of error that can occur when reading
+    type Error: crate::serial::Error;
+
+    /// Reads words into `buffer`, returning once it is full or the line has been idle for at
+    /// least one frame after at least one word has been received
+    ///
+    /// Returns the number of words read. The next call starts reading with the first word
+    /// after the gap, or after the words which didn't fit into `buffer`.
+    async fn read_until_idle(&mut self, buffer: &mut [Word]) -> Result<usize, Self::Error>;
+}
+
+impl<T: ReadUntilIdle<Word>, Word> ReadUntilIdle<Word> for &mut T {
+    type Error = T::Error;
+
+    async fn read_until_idle(&mut self, buffer: &mut [Word]) -> Result<usize, Self::Error> {
+        T::read_until_idle(self, buffer).await
+    }
+}
+
+/// Write half of a serial interface (async variant)
+pub trait Write<Word = u8> {
+    /// The type of error that can occur when writing
+    type Error: crate::serial::Error;
+
+    /// Writes words from `buffer`, returning once at least one word has been accepted
+    ///
+    /// Same contract as [`blocking::Write::write`](super::blocking::Write::write).
+    async fn write(&mut self, buffer: &[Word]) -> Result<usize, Self::Error>;
+
+    /// Writes a slice, returning once everything has been accepted
+    ///
+    /// If an error occurs or the future is dropped, the number of words already written is
+    /// unspecified.
+    async fn write_all(&mut self, mut buffer: &[Word]) -> Result<(), Self::Error> {
+        while !buffer.is_empty() {
+            let n = self.write(buffer).await?;
+            buffer = &buffer[n..];
+        }
+        Ok(())
+    }
+
+    /// Waits until the serial interface has sent all buffered words
+    async fn flush(&mut self) -> Result<(), Self::Error>;
+}
+
+impl<T: Write<Word>, Word> Write<Word> for &mut T {
+    type Error = T::Error;
+
+    async fn write(&mut self, buffer: &[Word]) -> Result<usize, Self::Error> {
+        T::write(self, buffer).await
+    }
+
+    async fn write_all(&mut self, buffer: &[Word]) -> Result<(), Self::Error> {
+        T::write_all(self, buffer).await
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        T::flush(self).await
+    }
+}
+
+/// Error of [`WriteFmt::write_fmt`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum WriteFmtError<E> {
+    /// The formatted string didn't fit into the buffer
+    BufferTooSmall,
+    /// Writing the formatted string failed
+    Serial(E),
+}
+
+impl<E: crate::serial::Error> crate::serial::Error for WriteFmtError<E> {
+    fn kind(&self) -> crate::serial::ErrorKind {
+        match self {
+            Self::BufferTooSmall => crate::serial::ErrorKind::Other,
+            Self::Serial(e) => e.kind(),
+        }
+    }
+}
+
+/// Formatted writes, implemented for all [`Write`] implementations
+///
+/// As formatting can't wait for the writer, the string is formatted into a buffer first.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::serial::asynch::{WriteFmt, WriteFmtError};
+///
+/// /// Sends a measurement as a line of text
+/// async fn report<S: WriteFmt>(serial: &mut S, millivolts: u32) -> Result<(), WriteFmtError<S::Error>> {
+///     let mut buffer = [0; 32];
+///     serial
+///         .write_fmt(&mut buffer, format_args!("{}.{:03} V\r\n", millivolts / 1000, millivolts % 1000))
+///         .await
+/// }
+/// ```
+pub trait WriteFmt: Write<u8> {
+    /// Formats `args` into `buffer` and writes the result
+    ///
+    /// Nothing is written if the string doesn't fit into `buffer`. If an error occurs or the
+    /// future is dropped while writing, the number of bytes already written is unspecified.
+    async fn write_fmt(
+        &mut self,
+        buffer: &mut [u8],
+        args: core::fmt::Arguments<'_>,
+    ) -> Result<(), WriteFmtError<Self::Error>> {
+        let mut cursor = Cursor { buffer, len: 0 };
+        core::fmt::write(&mut cursor, args).map_err(|_| WriteFmtError::BufferTooSmall)?;
+        let len = cursor.len;
+        self.write_all(&cursor.buffer[..len])
+            .await
+            .map_err(WriteFmtError::Serial)
+    }
+}
+
+impl<T: Write<u8>> WriteFmt for T {}
+
+/// `core::fmt::Write` implementation filling a buffer
+struct Cursor<'a> {
+    buffer: &'a mut [u8],
+    len: usize,
+}
+
+impl core::fmt::Write for Cursor<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let end = self.len + s.len();
+        if end > self.buffer.len() {
+            return Err(core::fmt::Error);
+        }
+        self.buffer[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// Sending of break conditions (async variant)
+pub trait SendBreak {
+    /// The type of error that can occur when sending the break
+    type Error: crate::serial::Error;
+
+    /// Sends a break after all previously written words, returning once it has been sent
+    ///
+    /// Same contract as [`blocking::SendBreak::send_break`](super::blocking::SendBreak::send_break).
+    async fn send_break(&mut self) -> Result<(), Self::Error>;
+}
+
+impl<T: SendBreak> SendBreak for &mut T {
+    type Error = T::Error;
+
+    async fn send_break(&mut self) -> Result<(), Self::Error> {
+        T::send_break(self).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::{BlockOn, Spin};
+    use crate::serial::ErrorKind;
+
+    /// Port accepting at most three words per call
+    struct Port {
+        sent: [u8; 16],
+        len: usize,
+    }
+
+    impl Write for Port {
+        type Error = ErrorKind;
+
+        async fn write(&mut self, buffer: &[u8]) -> Result<usize, Self::Error> {
+            let n = buffer.len().min(3);
+            self.sent[self.len..self.len + n].copy_from_slice(&buffer[..n]);
+            self.len += n;
+            Ok(n)
+        }
+
+        async fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_fmt() {
+        let mut port = Port {
+            sent: [0; 16],
+            len: 0,
+        };
+        let mut buffer = [0; 7];
+        let value = 1234;
+        Spin.block_on(port.write_fmt(&mut buffer, format_args!("v={}", value)))
+            .unwrap();
+        assert_eq!(
+            Spin.block_on(port.write_fmt(&mut buffer, format_args!("{}{}", value, value))),
+            Err(WriteFmtError::BufferTooSmall)
+        );
+        assert_eq!(&port.sent[..port.len], b"v=1234");
+    }
+}
@@ -0,0 +1,480 @@
+//! Bit-banged UART
+//!
+//! [`Bitbang`] sends and receives 8N1 frames (one start bit, 8 data bits LSB first, no parity,
+//! one stop bit) with plain GPIO pins and a delay, for devices with more serial links than
+//! UART peripherals. It also serves as a reference for the timing of a frame:
+//!
+//! - The idle line is high.
+//! - A frame starts with the start bit, driving the line low for one bit time.
+//! - Each data bit follows for one bit time, least significant bit first.
+//! - The stop bit drives the line high for at least one bit time.
+//! - A receiver samples each bit in its middle, timed from the falling edge of the start bit.
+//!
+//! As the time spent accessing the pins adds to the delays, only low baud rates work reliably,
+//! depending on the speed of the core. [`Bitbang`] receives by polling the RX pin four times
+//! per bit time while blocking the caller, so frames arriving while it isn't reading are lost.
+//!
+//! With the `async` feature, [`Receiver`] receives with an RX pin implementing
+//! [`Wait`](crate::digital::asynch::Wait) instead: it waits for the falling edge of the start
+//! bit, takes its timestamp from a [`Clock`](crate::delay::Clock) and samples every bit
+//! at its offset from that timestamp, so the time spent by the executor doesn't accumulate
+//! over the frame.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::delay::blocking::DelayUs;
+//! use embedded_hal::digital::blocking::OutputPin;
+//! use embedded_hal::serial::bitbang::{Bitbang, Error};
+//! use embedded_hal::serial::blocking::Write;
+//!
+//! /// Sends a command to a GPS module at 9600 baud
+//! fn configure<TX: OutputPin, D: DelayUs>(tx: TX, delay: D) -> Result<(), Error<TX::Error, D::Error>> {
+//!     let mut serial = Bitbang::new(tx, (), delay, 9600);
+//!     serial.write_all(b"$PMTK220,1000*1F\r\n")
+//! }
+//! ```
+
+use super::blocking::{Read, Write};
+use super::ErrorKind;
+use crate::delay::blocking::DelayUs;
+#[cfg(feature = "async")]
+use crate::delay::Clock;
+use crate::digital::blocking::{InputPin, OutputPin};
+
+/// Bit-banged UART error
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error<P, D> {
+    /// Setting or reading a pin failed
+    Pin(P),
+    /// The delay failed
+    Delay(D),
+    /// The stop bit of a received frame was low
+    FrameFormat,
+    /// A break was received, i.e. a frame whose data and stop bits were all low
+    Break,
+}
+
+impl<P: core::fmt::Debug, D: core::fmt::Debug> super::Error for Error<P, D> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Pin(_) | Self::Delay(_) => ErrorKind::Other,
+            Self::FrameFormat => ErrorKind::FrameFormat,
+            Self::Break => ErrorKind::BreakDetected,
+        }
+    }
+}
+
+/// UART driving GPIO pins
+///
+/// For a transmit- or receive-only UART, `()` can be passed for the unused pin.
+#[derive(Debug)]
+pub struct Bitbang<TX, RX, D> {
+    tx: TX,
+    rx: RX,
+    delay: D,
+    bit_us: u32,
+}
+
+impl<TX, RX, D> Bitbang<TX, RX, D> {
+    /// Creates a new UART with the given baud rate
+    ///
+    /// `tx` must already be high, as a low line is a start bit to the receiver.
+    pub fn new(tx: TX, rx: RX, delay: D, baud_rate: u32) -> Self {
+        Bitbang {
+            tx,
+            rx,
+            delay,
+            bit_us: bit_us(baud_rate),
+        }
+    }
+
+    /// Releases the pins and the delay
+    pub fn release(self) -> (TX, RX, D) {
+        (self.tx, self.rx, self.delay)
+    }
+}
+
+impl<TX: OutputPin, RX, D: DelayUs> Bitbang<TX, RX, D> {
+    fn send_bit(&mut self, high: bool) -> Result<(), Error<TX::Error, D::Error>> {
+        if high {
+            self.tx.set_high().map_err(Error::Pin)?;
+        } else {
+            self.tx.set_low().map_err(Error::Pin)?;
+        }
+        self.delay.delay_us(self.bit_us).map_err(Error::Delay)
+    }
+
+    fn send(&mut self, word: u8) -> Result<(), Error<TX::Error, D::Error>> {
+        self.send_bit(false)?;
+        for bit in 0..8 {
+            self.send_bit(word & (1 << bit) != 0)?;
+        }
+        self.send_bit(true)
+    }
+}
+
+impl<TX: OutputPin, RX, D: DelayUs> Write for Bitbang<TX, RX, D> {
+    type Error = Error<TX::Error, D::Error>;
+
+    /// Sends all words, returning once the stop bit of the last one has been sent
+    fn write(&mut self, buffer: &[u8]) -> Result<usize, Self::Error> {
+        for &word in buffer {
+            self.send(word)?;
+        }
+        Ok(buffer.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<TX, RX: InputPin, D: DelayUs> Bitbang<TX, RX, D> {
+    fn wait(&mut self, us: u32) -> Result<(), Error<RX::Error, D::Error>> {
+        self.delay.delay_us(us.max(1)).map_err(Error::Delay)
+    }
+
+    fn receive(&mut self) -> Result<u8, Error<RX::Error, D::Error>> {
+        // Wait for the falling edge of a start bit, which is still low in its middle
+        loop {
+            while self.rx.is_high().map_err(Error::Pin)? {
+                self.wait(self.bit_us / 4)?;
+            }
+            self.wait(self.bit_us / 2)?;
+            if self.rx.is_low().map_err(Error::Pin)? {
+                break;
+            }
+        }
+        let mut word = 0;
+        for bit in 0..8 {
+            self.wait(self.bit_us)?;
+            if self.rx.is_high().map_err(Error::Pin)? {
+                word |= 1 << bit;
+            }
+        }
+        self.wait(self.bit_us)?;
+        decode(word, self.rx.is_high().map_err(Error::Pin)?)
+    }
+}
+
+/// Returns the bit time in microseconds for `baud_rate`, rounded to the nearest microsecond
+fn bit_us(baud_rate: u32) -> u32 {
+    (1_000_000 + baud_rate / 2) / baud_rate.max(1)
+}
+
+/// Decodes the data bits and the stop bit of a frame
+fn decode<P, D>(word: u8, stop: bool) -> Result<u8, Error<P, D>> {
+    if stop {
+        Ok(word)
+    } else if word == 0 {
+        Err(Error::Break)
+    } else {
+        Err(Error::FrameFormat)
+    }
+}
+
+impl<TX, RX: InputPin, D: DelayUs> Read for Bitbang<TX, RX, D> {
+    type Error = Error<RX::Error, D::Error>;
+
+    /// Receives a single word, returning in the middle of its stop bit
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+        match buffer.first_mut() {
+            Some(word) => {
+                *word = self.receive()?;
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+}
+
+/// Receiver waiting for frames on a [`Wait`](crate::digital::asynch::Wait) pin
+///
+/// Only available with the `async` feature.
+#[cfg(feature = "async")]
+#[derive(Debug)]
+pub struct Receiver<RX, D, C> {
+    rx: RX,
+    delay: D,
+    clock: C,
+    bit_us: u32,
+}
+
+#[cfg(feature = "async")]
+impl<RX, D, C> Receiver<RX, D, C> {
+    /// Creates a new receiver with the given baud rate
+    ///
+    /// `clock` must count microseconds, its timestamps may wrap around.
+    pub fn new(rx: RX, delay: D, clock: C, baud_rate: u32) -> Self {
+        Receiver {
+            rx,
+            delay,
+            clock,
+            bit_us: bit_us(baud_rate),
+        }
+    }
+
+    /// Releases the pin, the delay and the clock
+    pub fn release(self) -> (RX, D, C) {
+        (self.rx, self.delay, self.clock)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<RX, D, C> Receiver<RX, D, C>
+where
+    RX: InputPin + crate::digital::asynch::Wait<Error = <RX as InputPin>::Error>,
+    D: crate::delay::asynch::DelayUs,
+    C: Clock,
+{
+    /// Waits until `us` microseconds after `start`, unless that time has already passed
+    async fn wait_until(
+        &mut self,
+        start: u32,
+        us: u32,
+    ) -> Result<(), Error<<RX as InputPin>::Error, D::Error>> {
+        let remaining = start.wrapping_add(us).wrapping_sub(self.clock.now_us());
+        if remaining > 0 && remaining <= us {
+            self.delay.delay_us(remaining).await.map_err(Error::Delay)?;
+        }
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> Result<u8, Error<<RX as InputPin>::Error, D::Error>> {
+        // Wait for the falling edge of a start bit, which is still low in its middle
+        let start = loop {
+            self.rx.wait_for_falling_edge().await.map_err(Error::Pin)?;
+            let start = self.clock.now_us();
+            self.wait_until(start, self.bit_us / 2).await?;
+            if self.rx.is_low().map_err(Error::Pin)? {
+                break start;
+            }
+        };
+        let mut word = 0;
+        for bit in 0..8 {
+            self.wait_until(start, self.bit_us * (2 * bit + 3) / 2)
+                .await?;
+            if self.rx.is_high().map_err(Error::Pin)? {
+                word |= 1 << bit;
+            }
+        }
+        self.wait_until(start, self.bit_us * 19 / 2).await?;
+        decode(word, self.rx.is_high().map_err(Error::Pin)?)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<RX, D, C> super::asynch::Read for Receiver<RX, D, C>
+where
+    RX: InputPin + crate::digital::asynch::Wait<Error = <RX as InputPin>::Error>,
+    D: crate::delay::asynch::DelayUs,
+    C: Clock,
+{
+    type Error = Error<<RX as InputPin>::Error, D::Error>;
+
+    /// Receives a single word, returning in the middle of its stop bit
+    async fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+        match buffer.first_mut() {
+            Some(word) => {
+                *word = self.receive().await?;
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::{Cell, RefCell};
+    use core::convert::Infallible;
+
+    /// Line recording the levels set by TX at the time of a shared clock
+    struct Wire {
+        now: Cell<u32>,
+        edges: RefCell<([(u32, bool); 24], usize)>,
+    }
+
+    impl Wire {
+        fn level(&self) -> bool {
+            let (edges, len) = *self.edges.borrow();
+            let mut level = true;
+            for &(time, edge) in &edges[..len] {
+                if time <= self.now.get() {
+                    level = edge;
+                }
+            }
+            level
+        }
+
+        /// Advances the clock to the next change of the level to `level`, if the level isn't
+        /// `level` yet or `edge` is set
+        #[cfg(feature = "async")]
+        fn wait_for(&self, level: bool, edge: bool) {
+            if !edge && self.level() == level {
+                return;
+            }
+            let (edges, len) = *self.edges.borrow();
+            let mut previous = true;
+            for &(time, next) in &edges[..len] {
+                if time > self.now.get() && previous != next && next == level {
+                    self.now.set(time);
+                    return;
+                }
+                previous = next;
+            }
+            panic!("wire: waiting for an edge which never occurs");
+        }
+
+        fn set(&self, level: bool) -> Result<(), Infallible> {
+            let (edges, len) = &mut *self.edges.borrow_mut();
+            edges[*len] = (self.now.get(), level);
+            *len += 1;
+            Ok(())
+        }
+    }
+
+    struct Pin<'a>(&'a Wire);
+
+    impl OutputPin for Pin<'_> {
+        type Error = Infallible;
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.0.set(false)
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.0.set(true)
+        }
+    }
+
+    impl InputPin for Pin<'_> {
+        type Error = Infallible;
+
+        fn is_high(&self) -> Result<bool, Self::Error> {
+            Ok(self.0.level())
+        }
+
+        fn is_low(&self) -> Result<bool, Self::Error> {
+            Ok(!self.0.level())
+        }
+    }
+
+    impl DelayUs for Pin<'_> {
+        type Error = Infallible;
+
+        fn delay_us(&mut self, us: u32) -> Result<(), Self::Error> {
+            self.0.now.set(self.0.now.get() + us);
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl crate::delay::asynch::DelayUs for Pin<'_> {
+        type Error = Infallible;
+
+        async fn delay_us(&mut self, us: u32) -> Result<(), Self::Error> {
+            DelayUs::delay_us(self, us)
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl crate::digital::asynch::Wait for Pin<'_> {
+        type Error = Infallible;
+
+        async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+            self.0.wait_for(true, false);
+            Ok(())
+        }
+
+        async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+            self.0.wait_for(false, false);
+            Ok(())
+        }
+
+        async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+            self.0.wait_for(true, true);
+            Ok(())
+        }
+
+        async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+            self.0.wait_for(false, true);
+            Ok(())
+        }
+
+        async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+            let level = self.0.level();
+            self.0.wait_for(!level, true);
+            Ok(())
+        }
+    }
+
+    fn idle() -> Wire {
+        Wire {
+            now: Cell::new(1000),
+            edges: RefCell::new(([(0, true); 24], 0)),
+        }
+    }
+
+    #[test]
+    fn frame_timing() {
+        let wire = idle();
+        let mut serial = Bitbang::new(Pin(&wire), (), Pin(&wire), 9600);
+        serial.write_all(&[0x35, 0xca]).unwrap();
+        let (edges, len) = *wire.edges.borrow();
+        assert_eq!(len, 20);
+        let expected = [
+            false, true, false, true, false, true, true, false, false, true,
+        ];
+        for (i, (&(time, level), &expected)) in edges.iter().zip(&expected).enumerate() {
+            assert_eq!(time, 1000 + 104 * i as u32);
+            assert_eq!(level, expected);
+        }
+        assert_eq!(wire.now.get(), 1000 + 20 * 104);
+
+        // Receive the frames sent above, starting while the line is idle
+        wire.now.set(0);
+        let mut serial = Bitbang::new((), Pin(&wire), Pin(&wire), 9600);
+        let mut buffer = [0; 2];
+        serial.read_exact(&mut buffer).unwrap();
+        assert_eq!(buffer, [0x35, 0xca]);
+    }
+
+    #[test]
+    fn break_and_frame_errors() {
+        let wire = idle();
+        wire.set(false).unwrap();
+        let mut serial = Bitbang::new((), Pin(&wire), Pin(&wire), 9600);
+        assert_eq!(serial.read(&mut [0]), Err(Error::Break));
+
+        let wire = idle();
+        wire.set(false).unwrap();
+        wire.now.set(1000 + 104);
+        wire.set(true).unwrap();
+        wire.now.set(1000 + 2 * 104);
+        wire.set(false).unwrap();
+        wire.now.set(0);
+        let mut serial = Bitbang::new((), Pin(&wire), Pin(&wire), 9600);
+        assert_eq!(serial.read(&mut [0]), Err(Error::FrameFormat));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn receiver_samples_from_start_edge() {
+        use crate::adapter::{BlockOn, Spin};
+        use crate::serial::asynch::Read as _;
+
+        let wire = idle();
+        let mut serial = Bitbang::new(Pin(&wire), (), Pin(&wire), 9600);
+        serial.write_all(&[0x35, 0xca]).unwrap();
+
+        wire.now.set(0);
+        let mut receiver = Receiver::new(Pin(&wire), Pin(&wire), || wire.now.get(), 9600);
+        let mut buffer = [0; 2];
+        Spin.block_on(receiver.read_exact(&mut buffer)).unwrap();
+        assert_eq!(buffer, [0x35, 0xca]);
+        assert_eq!(wire.now.get(), 1000 + 104 * 10 + 104 * 19 / 2);
+    }
+}
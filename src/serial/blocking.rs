@@ -1,19 +1,72 @@
 //! Blocking serial API
+//!
+//! Like the `embedded-io` traits, [`Read::read`] and [`Write::write`] transfer as many words
+//! as are available or can be accepted right away and return their number, so buffered
+//! drivers don't have to transfer single words in a loop. [`Read::read_exact`] and
+//! [`Write::write_all`] provide all-or-nothing transfers on top.
+
+/// Read half of a serial interface (blocking variant)
+pub trait Read<Word = u8> {
+    /// The type of error that can occur when reading
+    type Error: crate::serial::Error;
+
+    /// Reads words into `buffer`, blocking until at least one word has been read
+    ///
+    /// Returns the number of words read, which is only zero if `buffer` is empty. Words which
+    /// have already been received are returned without waiting for more, so the buffer is
+    /// usually not filled completely.
+    fn read(&mut self, buffer: &mut [Word]) -> Result<usize, Self::Error>;
+
+    /// Reads enough words to fill `buffer`, blocking until all have been read
+    ///
+    /// If an error occurs, the number of words already read is unspecified.
+    fn read_exact(&mut self, mut buffer: &mut [Word]) -> Result<(), Self::Error> {
+        while !buffer.is_empty() {
+            let n = self.read(buffer)?;
+            buffer = &mut buffer[n..];
+        }
+        Ok(())
+    }
+}
+
+impl<T: Read<Word>, Word> Read<Word> for &mut T {
+    type Error = T::Error;
+
+    fn read(&mut self, buffer: &mut [Word]) -> Result<usize, Self::Error> {
+        T::read(self, buffer)
+    }
+
+    fn read_exact(&mut self, buffer: &mut [Word]) -> Result<(), Self::Error> {
+        T::read_exact(self, buffer)
+    }
+}
 
 /// Write half of a serial interface (blocking variant)
 pub trait Write<Word = u8> {
     /// The type of error that can occur when writing
     type Error: crate::serial::Error;
 
-    /// Writes a slice, blocking until everything has been written
+    /// Writes words from `buffer`, blocking until at least one word has been accepted
+    ///
+    /// Returns the number of words accepted, which is only zero if `buffer` is empty.
     ///
-    /// An implementation can choose to buffer the write, returning `Ok(())`
-    /// after the complete slice has been written to a buffer, but before all
-    /// words have been sent via the serial interface. To make sure that
-    /// everything has been sent, call [`flush`] after this function returns.
+    /// An implementation can choose to buffer the write, returning before the accepted words
+    /// have been sent via the serial interface. To make sure that everything has been sent,
+    /// call [`flush`] after this function returns.
     ///
     /// [`flush`]: #tymethod.flush
-    fn write(&mut self, buffer: &[Word]) -> Result<(), Self::Error>;
+    fn write(&mut self, buffer: &[Word]) -> Result<usize, Self::Error>;
+
+    /// Writes a slice, blocking until everything has been accepted
+    ///
+    /// If an error occurs, the number of words already written is unspecified.
+    fn write_all(&mut self, mut buffer: &[Word]) -> Result<(), Self::Error> {
+        while !buffer.is_empty() {
+            let n = self.write(buffer)?;
+            buffer = &buffer[n..];
+        }
+        Ok(())
+    }
 
     /// Block until the serial interface has sent all buffered words
     fn flush(&mut self) -> Result<(), Self::Error>;
@@ -22,11 +75,92 @@ pub trait Write<Word = u8> {
 impl<T: Write<Word>, Word> Write<Word> for &mut T {
     type Error = T::Error;
 
-    fn write(&mut self, buffer: &[Word]) -> Result<(), Self::Error> {
+    fn write(&mut self, buffer: &[Word]) -> Result<usize, Self::Error> {
         T::write(self, buffer)
     }
 
+    fn write_all(&mut self, buffer: &[Word]) -> Result<(), Self::Error> {
+        T::write_all(self, buffer)
+    }
+
     fn flush(&mut self) -> Result<(), Self::Error> {
         T::flush(self)
     }
 }
+
+/// Sending of break conditions (blocking variant)
+///
+/// A break holds the line low for longer than a frame, which receivers report as
+/// [`ErrorKind::BreakDetected`](crate::serial::ErrorKind::BreakDetected). Protocols such as LIN
+/// and DMX-512 use it to mark the start of a message.
+pub trait SendBreak {
+    /// The type of error that can occur when sending the break
+    type Error: crate::serial::Error;
+
+    /// Sends a break after all previously written words, blocking until it has been sent
+    ///
+    /// The line is held low for at least one frame including the stop bits. Implementations
+    /// should document the actual duration, e.g. LIN requires at least 13 bit times.
+    fn send_break(&mut self) -> Result<(), Self::Error>;
+}
+
+impl<T: SendBreak> SendBreak for &mut T {
+    type Error = T::Error;
+
+    fn send_break(&mut self) -> Result<(), Self::Error> {
+        T::send_break(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::ErrorKind;
+
+    /// Port transferring at most two words per call
+    struct Port {
+        sent: [u8; 8],
+        len: usize,
+    }
+
+    impl Read for Port {
+        type Error = ErrorKind;
+
+        fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+            let n = buffer.len().min(2);
+            for word in &mut buffer[..n] {
+                *word = self.len as u8;
+                self.len += 1;
+            }
+            Ok(n)
+        }
+    }
+
+    impl Write for Port {
+        type Error = ErrorKind;
+
+        fn write(&mut self, buffer: &[u8]) -> Result<usize, Self::Error> {
+            let n = buffer.len().min(2);
+            self.sent[self.len..self.len + n].copy_from_slice(&buffer[..n]);
+            self.len += n;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn transfers_all_words() {
+        let mut port = Port {
+            sent: [0; 8],
+            len: 0,
+        };
+        port.write_all(&[1, 2, 3, 4, 5]).unwrap();
+        assert_eq!(&port.sent[..port.len], &[1, 2, 3, 4, 5]);
+        let mut buffer = [0; 3];
+        port.read_exact(&mut buffer).unwrap();
+        assert_eq!(buffer, [5, 6, 7]);
+    }
+}
@@ -0,0 +1,100 @@
+//! Adapters from `futures::io` to the async serial traits
+//!
+//! [`FromFutures`] implements the [async serial traits](super::asynch) for an
+//! `AsyncRead`/`AsyncWrite` implementation of the `futures` ecosystem, e.g. a TCP stream of a
+//! serial server or a pipe feeding a simulated device, so async drivers can be reused on the
+//! host unchanged.
+//!
+//! The opposite direction isn't provided: the futures returned by the async serial traits
+//! can't be stored across calls to `poll_read` without boxing them, and recreating them on
+//! every call could lose words.
+//!
+//! Only available with the `futures` feature.
+
+use core::future::poll_fn;
+use core::pin::Pin;
+use std::io;
+
+use futures_io::{AsyncRead, AsyncWrite};
+
+use super::asynch::{Read, Write};
+
+/// Async serial interface backed by a `futures::io` reader or writer
+///
+/// Reads fail with [`io::ErrorKind::UnexpectedEof`] at the end of the input, as a serial read
+/// waits until at least one word has been received. Interrupted calls are retried.
+#[derive(Debug)]
+pub struct FromFutures<T> {
+    inner: T,
+}
+
+impl<T> FromFutures<T> {
+    /// Creates a new serial interface using `inner`
+    pub fn new(inner: T) -> Self {
+        FromFutures { inner }
+    }
+
+    /// Releases the reader or writer
+    pub fn release(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: AsyncRead + Unpin> Read for FromFutures<T> {
+    type Error = io::Error;
+
+    async fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+        if buffer.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            match poll_fn(|cx| Pin::new(&mut self.inner).poll_read(cx, buffer)).await {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                result => return result,
+            }
+        }
+    }
+}
+
+impl<T: AsyncWrite + Unpin> Write for FromFutures<T> {
+    type Error = io::Error;
+
+    async fn write(&mut self, buffer: &[u8]) -> Result<usize, Self::Error> {
+        if buffer.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            match poll_fn(|cx| Pin::new(&mut self.inner).poll_write(cx, buffer)).await {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                result => return result,
+            }
+        }
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        poll_fn(|cx| Pin::new(&mut self.inner).poll_flush(cx)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::{BlockOn, Spin};
+
+    #[test]
+    fn reads_and_writes() {
+        let mut serial = FromFutures::new(&b"ping"[..]);
+        let mut buffer = [0; 4];
+        Spin.block_on(serial.read_exact(&mut buffer)).unwrap();
+        assert_eq!(&buffer, b"ping");
+        let e = Spin.block_on(serial.read(&mut buffer)).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+
+        let mut serial = FromFutures::new(std::vec::Vec::new());
+        Spin.block_on(serial.write_all(b"pong")).unwrap();
+        Spin.block_on(serial.flush()).unwrap();
+        assert_eq!(serial.release(), b"pong");
+    }
+}
@@ -0,0 +1,98 @@
+//! Single-wire half-duplex operation
+//!
+//! Smartcards, servo buses and some sensors use a single line for both directions. In
+//! half-duplex mode, the interface drives its TX pin only while transmitting and receives on
+//! the same pin otherwise. [`SetHalfDuplex`] switches interfaces supporting it between this
+//! mode and the default full-duplex operation on separate pins.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::serial::blocking::{Read, Write};
+//! use embedded_hal::serial::half_duplex::{HalfDuplexConfig, SetHalfDuplex};
+//!
+//! /// Reads the position of a smart servo sharing one line with others
+//! fn position<S>(serial: &mut S, id: u8) -> Result<u16, <S as Read>::Error>
+//! where
+//!     S: Read + Write<Error = <S as Read>::Error> + SetHalfDuplex<Error = <S as Read>::Error>,
+//! {
+//!     serial.set_half_duplex(Some(HalfDuplexConfig::new().with_turnaround_us(100)))?;
+//!     serial.write_all(&[0xff, 0xff, id, 0x04, 0x02, 0x38, 0x02])?;
+//!     let mut response = [0; 8];
+//!     serial.read_exact(&mut response)?;
+//!     Ok(u16::from_le_bytes([response[5], response[6]]))
+//! }
+//! ```
+
+/// Half-duplex configuration
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct HalfDuplexConfig {
+    /// Whether the pin is driven open-drain, so several nodes can share the line without
+    /// contention, with an external or internal pull-up keeping it idle high
+    pub open_drain: bool,
+    /// Minimum idle time in microseconds between the end of a received word and the start of
+    /// a transmission, giving the other node time to release the line
+    pub turnaround_us: u32,
+    /// Whether transmitted words are also received
+    ///
+    /// Comparing the echo with the written words detects collisions, which implementations
+    /// can report as [`ErrorKind::Collision`](super::ErrorKind::Collision). Otherwise the
+    /// receiver is disabled while transmitting.
+    pub echo: bool,
+}
+
+impl HalfDuplexConfig {
+    /// Creates a new open-drain configuration without turnaround time and echo
+    pub const fn new() -> Self {
+        HalfDuplexConfig {
+            open_drain: true,
+            turnaround_us: 0,
+            echo: false,
+        }
+    }
+
+    /// Sets whether the pin is driven open-drain
+    pub const fn with_open_drain(mut self, open_drain: bool) -> Self {
+        self.open_drain = open_drain;
+        self
+    }
+
+    /// Sets the turnaround time in microseconds
+    pub const fn with_turnaround_us(mut self, turnaround_us: u32) -> Self {
+        self.turnaround_us = turnaround_us;
+        self
+    }
+
+    /// Sets whether transmitted words are received
+    pub const fn with_echo(mut self, echo: bool) -> Self {
+        self.echo = echo;
+        self
+    }
+}
+
+impl Default for HalfDuplexConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Single-wire half-duplex configuration
+pub trait SetHalfDuplex {
+    /// Error type
+    type Error: super::Error;
+
+    /// Enables half-duplex operation on the TX pin with the given configuration, or restores
+    /// full-duplex operation if `config` is `None`
+    ///
+    /// Words which are still being transmitted are sent before the mode changes. Returns an
+    /// error if the hardware doesn't support `config`.
+    fn set_half_duplex(&mut self, config: Option<HalfDuplexConfig>) -> Result<(), Self::Error>;
+}
+
+impl<T: SetHalfDuplex> SetHalfDuplex for &mut T {
+    type Error = T::Error;
+
+    fn set_half_duplex(&mut self, config: Option<HalfDuplexConfig>) -> Result<(), Self::Error> {
+        T::set_half_duplex(self, config)
+    }
+}
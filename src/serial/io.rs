@@ -0,0 +1,167 @@
+//! Adapters between the serial traits and `std::io`
+//!
+//! [`FromStd`] implements the blocking serial traits for a `std::io` reader or writer, e.g. a
+//! USB-serial port opened with the `serialport` crate, so drivers can run on a desktop
+//! against real devices or test fixtures. [`ToStd`] goes the other way, so a serial
+//! interface can be used with code written for `std::io`.
+//!
+//! Only available with the `std` feature.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::serial::blocking::{Read, Write};
+//! use embedded_hal::serial::io::FromStd;
+//!
+//! /// Driver under test, echoing a line
+//! fn echo<S: Read + Write<Error = <S as Read>::Error>>(serial: &mut S) -> Result<(), <S as Read>::Error> {
+//!     let mut line = [0; 6];
+//!     serial.read_exact(&mut line)?;
+//!     serial.write_all(&line)
+//! }
+//!
+//! // A cursor's writes overwrite what has been read
+//! let mut serial = FromStd::new(std::io::Cursor::new(b"hello\nworld\n".to_vec()));
+//! echo(&mut serial).unwrap();
+//! assert_eq!(serial.release().into_inner(), b"hello\nhello\n");
+//! ```
+
+use std::io;
+
+use super::blocking::{Read, Write};
+use super::ErrorKind;
+
+impl super::Error for io::Error {
+    fn kind(&self) -> ErrorKind {
+        match io::Error::kind(self) {
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => ErrorKind::Timeout,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+impl std::error::Error for ErrorKind {}
+
+/// Serial interface backed by a `std::io` reader or writer
+///
+/// Reads fail with [`io::ErrorKind::UnexpectedEof`] at the end of the input, as a serial read
+/// blocks until at least one word has been received. Interrupted calls are retried.
+#[derive(Debug)]
+pub struct FromStd<T> {
+    inner: T,
+}
+
+impl<T> FromStd<T> {
+    /// Creates a new serial interface using `inner`
+    pub fn new(inner: T) -> Self {
+        FromStd { inner }
+    }
+
+    /// Releases the reader or writer
+    pub fn release(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: io::Read> Read for FromStd<T> {
+    type Error = io::Error;
+
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+        if buffer.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            match self.inner.read(buffer) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                result => return result,
+            }
+        }
+    }
+}
+
+impl<T: io::Write> Write for FromStd<T> {
+    type Error = io::Error;
+
+    fn write(&mut self, buffer: &[u8]) -> Result<usize, Self::Error> {
+        if buffer.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            match self.inner.write(buffer) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                result => return result,
+            }
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+}
+
+/// `std::io` reader or writer backed by a serial interface
+///
+/// Errors of the serial interface are converted to [`io::Error`]s carrying their
+/// [`ErrorKind`]. [`ErrorKind::Timeout`] becomes [`io::ErrorKind::TimedOut`].
+#[derive(Debug)]
+pub struct ToStd<S> {
+    serial: S,
+}
+
+impl<S> ToStd<S> {
+    /// Creates a new reader or writer using `serial`
+    pub fn new(serial: S) -> Self {
+        ToStd { serial }
+    }
+
+    /// Releases the serial interface
+    pub fn release(self) -> S {
+        self.serial
+    }
+}
+
+fn to_io<E: super::Error>(e: E) -> io::Error {
+    let kind = e.kind();
+    match kind {
+        ErrorKind::Timeout => io::Error::new(io::ErrorKind::TimedOut, kind),
+        _ => io::Error::other(kind),
+    }
+}
+
+impl<S: Read> io::Read for ToStd<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.serial.read(buf).map_err(to_io)
+    }
+}
+
+impl<S: Write> io::Write for ToStd<S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.serial.write(buf).map_err(to_io)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.serial.flush().map_err(to_io)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut serial = ToStd::new(FromStd::new(io::Cursor::new(std::vec![0; 4])));
+        io::Write::write_all(&mut serial, b"ab").unwrap();
+        let mut buffer = [0; 2];
+        io::Read::read_exact(&mut serial, &mut buffer).unwrap();
+        assert_eq!(buffer, [0, 0]);
+        let e = io::Read::read_exact(&mut serial, &mut buffer).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::Other);
+        let mut serial = serial.release();
+        let e = Read::read(&mut serial, &mut buffer).unwrap_err();
+        assert_eq!(crate::serial::Error::kind(&e), ErrorKind::Other);
+        assert_eq!(&serial.release().into_inner()[..2], b"ab");
+    }
+}
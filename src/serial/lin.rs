@@ -0,0 +1,445 @@
+//! LIN frames over a UART
+//!
+//! [`Lin`] implements the framing of the Local Interconnect Network on top of the blocking
+//! serial traits, so LIN master and slave drivers work with any UART supporting
+//! [`SendBreak`] and break detection. A frame consists of a header sent by the master and a
+//! response sent by the master or a slave:
+//!
+//! - The header is a break of at least 13 bit times, the sync byte `0x55` and the protected
+//!   identifier (PID), i.e. the 6-bit frame identifier with two parity bits.
+//! - The response is 1 to 8 data bytes followed by a checksum byte.
+//!
+//! The UART must be configured for 8N1 frames at the baud rate of the bus, and its break must
+//! last at least 13 bit times. As LIN transceivers put the master's own transmission on its
+//! receive line as well, [`with_echo`](Lin::with_echo) makes [`Lin`] read back and verify
+//! everything it sends.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::serial::blocking::{Read, SendBreak, Write};
+//! use embedded_hal::serial::lin::{Checksum, Error, Lin};
+//!
+//! /// Reads the state of a window lift motor with frame identifier 0x21
+//! fn motor_state<S>(serial: S) -> Result<[u8; 4], Error<<S as Read>::Error>>
+//! where
+//!     S: Read + Write<Error = <S as Read>::Error> + SendBreak<Error = <S as Read>::Error>,
+//! {
+//!     let mut lin = Lin::new(serial).with_echo(true);
+//!     let mut state = [0; 4];
+//!     lin.read_frame(0x21, &mut state, Checksum::Enhanced)?;
+//!     Ok(state)
+//! }
+//! ```
+
+use super::blocking::{Read, SendBreak, Write};
+use super::ErrorKind;
+
+/// Sync byte following the break of a header
+pub const SYNC: u8 = 0x55;
+
+/// Returns the protected identifier of the frame identifier `id`, or `None` if `id` is larger
+/// than 63
+pub fn protected_id(id: u8) -> Option<u8> {
+    if id >= 64 {
+        return None;
+    }
+    let bit = |n: u8| (id >> n) & 1;
+    let p0 = bit(0) ^ bit(1) ^ bit(2) ^ bit(4);
+    let p1 = !(bit(1) ^ bit(3) ^ bit(4) ^ bit(5)) & 1;
+    Some(id | p0 << 6 | p1 << 7)
+}
+
+/// Returns the frame identifier of the protected identifier `pid`, or `None` if its parity
+/// bits are wrong
+pub fn frame_id(pid: u8) -> Option<u8> {
+    let id = pid & 0x3f;
+    if protected_id(id) == Some(pid) {
+        Some(id)
+    } else {
+        None
+    }
+}
+
+/// Checksum model
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Checksum {
+    /// Checksum over the data bytes only, used by LIN 1.x and for diagnostic frames
+    Classic,
+    /// Checksum over the protected identifier and the data bytes, used by LIN 2.x
+    Enhanced,
+}
+
+impl Checksum {
+    /// Computes the checksum of a response
+    ///
+    /// The classic checksum is always used for the diagnostic frames with the identifiers
+    /// 60 and 61.
+    pub fn compute(self, pid: u8, data: &[u8]) -> u8 {
+        let mut sum: u16 = match self {
+            Checksum::Enhanced if pid & 0x3f < 60 => u16::from(pid),
+            _ => 0,
+        };
+        for &byte in data {
+            sum += u16::from(byte);
+            if sum > 0xff {
+                sum -= 0xff;
+            }
+        }
+        !(sum as u8)
+    }
+}
+
+/// LIN error
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error<E> {
+    /// The serial interface returned an error
+    Serial(E),
+    /// A received header didn't contain the sync byte
+    Sync,
+    /// The parity bits of a received protected identifier were wrong
+    Parity,
+    /// The checksum of a received response was wrong
+    Checksum,
+    /// A word read back from the bus differed from the word sent
+    Collision,
+    /// The frame identifier is larger than 63
+    InvalidId,
+    /// The response data is empty or longer than 8 bytes
+    DataLength,
+}
+
+impl<E: super::Error> super::Error for Error<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Serial(e) => e.kind(),
+            Self::Sync => ErrorKind::FrameFormat,
+            Self::Parity => ErrorKind::Parity,
+            Self::Checksum => ErrorKind::Other,
+            Self::Collision => ErrorKind::Collision,
+            Self::InvalidId | Self::DataLength => ErrorKind::Other,
+        }
+    }
+}
+
+/// Returns the protected identifier of `id`, or [`Error::InvalidId`]
+fn pid<E>(id: u8) -> Result<u8, Error<E>> {
+    protected_id(id).ok_or(Error::InvalidId)
+}
+
+/// Checks that a response carries 1 to 8 data bytes
+fn check_length<E>(data: &[u8]) -> Result<(), Error<E>> {
+    if data.is_empty() || data.len() > 8 {
+        Err(Error::DataLength)
+    } else {
+        Ok(())
+    }
+}
+
+/// LIN master or slave using a serial interface
+#[derive(Debug)]
+pub struct Lin<S> {
+    serial: S,
+    echo: bool,
+}
+
+impl<S, E> Lin<S>
+where
+    S: Read<Error = E> + Write<Error = E> + SendBreak<Error = E>,
+    E: super::Error,
+{
+    /// Creates a new LIN node not expecting an echo of its transmissions
+    pub fn new(serial: S) -> Self {
+        Lin {
+            serial,
+            echo: false,
+        }
+    }
+
+    /// Sets whether the node's own transmissions are received as well
+    ///
+    /// If so, they are read back after sending and compared with the sent bytes.
+    pub fn with_echo(mut self, echo: bool) -> Self {
+        self.echo = echo;
+        self
+    }
+
+    /// Releases the serial interface
+    pub fn release(self) -> S {
+        self.serial
+    }
+
+    /// Sends the header of the frame `id` (master)
+    ///
+    /// Fails with [`Error::InvalidId`] without sending anything if `id` is larger than 63.
+    pub fn send_header(&mut self, id: u8) -> Result<(), Error<E>> {
+        let header = [SYNC, pid(id)?];
+        self.serial.send_break().map_err(Error::Serial)?;
+        if self.echo {
+            // The break is received as a zero byte with a framing error or a break error
+            let mut word = [0];
+            match self.serial.read(&mut word) {
+                Ok(_) => {}
+                Err(e) if is_break(&e) => {}
+                Err(e) => return Err(Error::Serial(e)),
+            }
+        }
+        self.send(&header)
+    }
+
+    /// Waits for a header and returns its frame identifier (slave)
+    ///
+    /// Words received before the break are discarded.
+    pub fn read_header(&mut self) -> Result<u8, Error<E>> {
+        let mut word = [0];
+        loop {
+            match self.serial.read(&mut word) {
+                Ok(_) => {}
+                Err(e) if is_break(&e) => break,
+                Err(e) => return Err(Error::Serial(e)),
+            }
+        }
+        let mut header = [0; 2];
+        self.serial.read_exact(&mut header).map_err(Error::Serial)?;
+        // A break reported as a zero byte with a framing error may be followed by the zero
+        // byte itself
+        if header[0] == 0 {
+            header[0] = header[1];
+            self.serial
+                .read_exact(&mut header[1..])
+                .map_err(Error::Serial)?;
+        }
+        if header[0] != SYNC {
+            return Err(Error::Sync);
+        }
+        frame_id(header[1]).ok_or(Error::Parity)
+    }
+
+    /// Sends the response to the frame `id`, after its header was sent or received
+    ///
+    /// Fails with [`Error::InvalidId`] or [`Error::DataLength`] without sending anything if
+    /// `id` is larger than 63 or `data` is empty or longer than 8 bytes.
+    pub fn send_response(
+        &mut self,
+        id: u8,
+        data: &[u8],
+        checksum: Checksum,
+    ) -> Result<(), Error<E>> {
+        check_length(data)?;
+        let checksum = checksum.compute(pid(id)?, data);
+        self.send(data)?;
+        self.send(&[checksum])
+    }
+
+    /// Receives the response to the frame `id`, after its header was sent or received
+    ///
+    /// Fails with [`Error::InvalidId`] or [`Error::DataLength`] without reading anything if
+    /// `id` is larger than 63 or `data` is empty or longer than 8 bytes.
+    pub fn read_response(
+        &mut self,
+        id: u8,
+        data: &mut [u8],
+        checksum: Checksum,
+    ) -> Result<(), Error<E>> {
+        let pid = pid(id)?;
+        check_length(data)?;
+        let mut received = [0];
+        self.serial.read_exact(data).map_err(Error::Serial)?;
+        self.serial
+            .read_exact(&mut received)
+            .map_err(Error::Serial)?;
+        if checksum.compute(pid, data) == received[0] {
+            Ok(())
+        } else {
+            Err(Error::Checksum)
+        }
+    }
+
+    /// Sends a frame with data from the master
+    ///
+    /// The identifier and the data length are checked before the header is sent.
+    pub fn write_frame(&mut self, id: u8, data: &[u8], checksum: Checksum) -> Result<(), Error<E>> {
+        check_length(data)?;
+        self.send_header(id)?;
+        self.send_response(id, data, checksum)
+    }
+
+    /// Requests a frame and receives its data from a slave
+    ///
+    /// The identifier and the data length are checked before the header is sent.
+    pub fn read_frame(
+        &mut self,
+        id: u8,
+        data: &mut [u8],
+        checksum: Checksum,
+    ) -> Result<(), Error<E>> {
+        check_length(data)?;
+        self.send_header(id)?;
+        self.read_response(id, data, checksum)
+    }
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), Error<E>> {
+        self.serial.write_all(bytes).map_err(Error::Serial)?;
+        if self.echo {
+            for &byte in bytes {
+                let mut echo = [0];
+                self.serial.read_exact(&mut echo).map_err(Error::Serial)?;
+                if echo[0] != byte {
+                    return Err(Error::Collision);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_break<E: super::Error>(e: &E) -> bool {
+    matches!(e.kind(), ErrorKind::BreakDetected | ErrorKind::FrameFormat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bus receiving the queued words followed by the echo of everything sent
+    struct Bus {
+        rx: [Result<u8, ErrorKind>; 16],
+        head: usize,
+        tail: usize,
+        sent: [u8; 16],
+        len: usize,
+    }
+
+    impl Bus {
+        fn new(queued: &[Result<u8, ErrorKind>]) -> Self {
+            let mut rx = [Ok(0); 16];
+            rx[..queued.len()].copy_from_slice(queued);
+            Bus {
+                rx,
+                head: 0,
+                tail: queued.len(),
+                sent: [0; 16],
+                len: 0,
+            }
+        }
+
+        fn receive(&mut self, word: Result<u8, ErrorKind>) {
+            self.rx[self.tail] = word;
+            self.tail += 1;
+        }
+    }
+
+    impl Read for Bus {
+        type Error = ErrorKind;
+
+        fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+            assert!(self.head < self.tail, "lin bus: nothing to read");
+            self.head += 1;
+            buffer[0] = self.rx[self.head - 1]?;
+            Ok(1)
+        }
+    }
+
+    impl Write for Bus {
+        type Error = ErrorKind;
+
+        fn write(&mut self, buffer: &[u8]) -> Result<usize, Self::Error> {
+            for &word in buffer {
+                self.sent[self.len] = word;
+                self.len += 1;
+                self.receive(Ok(word));
+            }
+            Ok(buffer.len())
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    impl SendBreak for Bus {
+        type Error = ErrorKind;
+
+        fn send_break(&mut self) -> Result<(), Self::Error> {
+            self.receive(Err(ErrorKind::BreakDetected));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn master_writes_frame() {
+        let mut lin = Lin::new(Bus::new(&[])).with_echo(true);
+        lin.write_frame(0x22, &[0x4a, 0x55, 0x93, 0xe5], Checksum::Enhanced)
+            .unwrap();
+        let bus = lin.release();
+        assert_eq!(bus.head, bus.tail);
+        assert_eq!(
+            &bus.sent[..bus.len],
+            &[SYNC, 0xe2, 0x4a, 0x55, 0x93, 0xe5, 0x04]
+        );
+    }
+
+    #[test]
+    fn slave_reads_frame() {
+        let mut lin = Lin::new(Bus::new(&[
+            Ok(0x12),
+            Err(ErrorKind::BreakDetected),
+            Ok(SYNC),
+            Ok(0xe2),
+            Ok(0x4a),
+            Ok(0x55),
+            Ok(0x93),
+            Ok(0xe5),
+            Ok(0x05),
+            Err(ErrorKind::BreakDetected),
+            Ok(SYNC),
+            Ok(0x02),
+        ]));
+        assert_eq!(lin.read_header(), Ok(0x22));
+        let mut data = [0; 4];
+        assert_eq!(
+            lin.read_response(0x22, &mut data, Checksum::Enhanced),
+            Err(Error::Checksum)
+        );
+        assert_eq!(data, [0x4a, 0x55, 0x93, 0xe5]);
+        assert_eq!(lin.read_header(), Err(Error::Parity));
+    }
+
+    #[test]
+    fn rejects_invalid_frames_before_sending() {
+        let mut lin = Lin::new(Bus::new(&[])).with_echo(true);
+        assert_eq!(
+            lin.write_frame(0x40, &[1], Checksum::Enhanced),
+            Err(Error::InvalidId)
+        );
+        assert_eq!(
+            lin.write_frame(0x22, &[0; 9], Checksum::Enhanced),
+            Err(Error::DataLength)
+        );
+        assert_eq!(
+            lin.read_frame(0x22, &mut [], Checksum::Enhanced),
+            Err(Error::DataLength)
+        );
+        let bus = lin.release();
+        assert_eq!((bus.len, bus.tail), (0, 0));
+    }
+
+    #[test]
+    fn protected_ids() {
+        assert_eq!(protected_id(0x00), Some(0x80));
+        assert_eq!(protected_id(0x01), Some(0xc1));
+        assert_eq!(protected_id(0x3c), Some(0x3c));
+        assert_eq!(protected_id(0x3d), Some(0x7d));
+        assert_eq!(protected_id(0x40), None);
+        assert_eq!(frame_id(0xc1), Some(0x01));
+        assert_eq!(frame_id(0x41), None);
+    }
+
+    #[test]
+    fn checksums() {
+        let data = [0x4a, 0x55, 0x93, 0xe5];
+        assert_eq!(Checksum::Classic.compute(0xe2, &data), 0xe6);
+        assert_eq!(Checksum::Enhanced.compute(0xe2, &data), 0x04);
+        assert_eq!(Checksum::Enhanced.compute(0x3c, &data), 0xe6);
+    }
+}
@@ -1,7 +1,119 @@
 //! Serial traits
 
+#[cfg(feature = "async")]
+pub mod asynch;
+pub mod bitbang;
 pub mod blocking;
+#[cfg(feature = "futures")]
+pub mod futures;
+pub mod half_duplex;
+#[cfg(feature = "std")]
+pub mod io;
+pub mod lin;
+pub mod modem;
+pub mod multidrop;
 pub mod nb;
+pub mod ring;
+pub mod stats;
+pub mod tap;
+pub mod timeout;
+
+/// Readiness of the read half of a serial interface
+///
+/// Lets polling code and schedulers check for received data without committing to a
+/// blocking call.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::serial::{blocking::Read, ReadReady};
+///
+/// /// Returns the next command byte, if one has been received
+/// fn poll_command<S>(serial: &mut S) -> Result<Option<u8>, <S as Read>::Error>
+/// where
+///     S: Read + ReadReady<Error = <S as Read>::Error>,
+/// {
+///     if !serial.read_ready()? {
+///         return Ok(None);
+///     }
+///     let mut command = [0];
+///     serial.read(&mut command)?;
+///     Ok(Some(command[0]))
+/// }
+/// ```
+pub trait ReadReady {
+    /// Error type
+    type Error: Error;
+
+    /// Returns `true` if at least one word has been received, so that a read returns without
+    /// blocking
+    fn read_ready(&mut self) -> Result<bool, Self::Error>;
+}
+
+impl<T: ReadReady> ReadReady for &mut T {
+    type Error = T::Error;
+
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        T::read_ready(self)
+    }
+}
+
+/// Readiness of the write half of a serial interface
+pub trait WriteReady {
+    /// Error type
+    type Error: Error;
+
+    /// Returns `true` if at least one word can be accepted, so that a write returns without
+    /// blocking
+    fn write_ready(&mut self) -> Result<bool, Self::Error>;
+}
+
+impl<T: WriteReady> WriteReady for &mut T {
+    type Error = T::Error;
+
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        T::write_ready(self)
+    }
+}
+
+/// Splitting of a serial interface into independent read and write halves
+///
+/// The halves can be moved to different tasks, e.g. one parsing received messages and one
+/// sending responses, without sharing the interface. Drivers require the traits they need on
+/// the halves, e.g. `S::Rx: blocking::Read, S::Tx: blocking::Write`.
+///
+/// HALs whose interfaces can be borrowed for a limited time can also implement this trait for
+/// `&'a mut` references to them, returning halves borrowing the interface.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::serial::{blocking::{Read, Write}, Split};
+///
+/// /// Echoes received words until an error occurs
+/// fn echo<S, E>(serial: S) -> Result<(), E>
+/// where
+///     S: Split,
+///     S::Rx: Read<Error = E>,
+///     S::Tx: Write<Error = E>,
+/// {
+///     let (mut rx, mut tx) = serial.split();
+///     let mut buffer = [0; 16];
+///     loop {
+///         let n = rx.read(&mut buffer)?;
+///         tx.write_all(&buffer[..n])?;
+///     }
+/// }
+/// ```
+pub trait Split {
+    /// Read half
+    type Rx;
+    /// Write half
+    type Tx;
+
+    /// Splits the interface into its read and write halves
+    fn split(self) -> (Self::Rx, Self::Tx);
+}
 
 /// Serial error
 pub trait Error: core::fmt::Debug {
@@ -36,6 +148,16 @@ pub enum ErrorKind {
     Parity,
     /// Serial line is too noisy to read valid data.
     Noise,
+    /// A break condition was received, i.e. the line was held low for longer than a frame.
+    BreakDetected,
+    /// A software receive buffer was full, so received words were dropped.
+    /// Unlike `Overrun`, the hardware received the words correctly.
+    BufferOverflow,
+    /// The transmitted words were not read back correctly on a shared line,
+    /// e.g. because another node transmitted at the same time.
+    Collision,
+    /// The operation did not complete in time.
+    Timeout,
     /// A different error occurred. The original error may contain more information.
     Other,
 }
@@ -52,6 +174,16 @@ impl core::fmt::Display for ErrorKind {
             Self::Overrun => write!(f, "The peripheral receive buffer was overrun"),
             Self::Parity => write!(f, "Parity check failed"),
             Self::Noise => write!(f, "Serial line is too noisy to read valid data"),
+            Self::BreakDetected => write!(f, "A break condition was received"),
+            Self::BufferOverflow => write!(
+                f,
+                "A software receive buffer was full, so received words were dropped"
+            ),
+            Self::Collision => write!(
+                f,
+                "The transmitted words were not read back correctly on a shared line"
+            ),
+            Self::Timeout => write!(f, "The operation did not complete in time"),
             Self::FrameFormat => write!(
                 f,
                 "Received data does not conform to the peripheral configuration"
@@ -0,0 +1,123 @@
+//! Hardware flow control and modem lines
+//!
+//! [`SetFlowControl`] configures RTS/CTS handshaking done by the peripheral. Interfaces which
+//! expose the modem lines implement [`ModemStatus`] for the inputs and [`ModemControl`] for
+//! the outputs, so modem and radio drivers can implement handshaking in software. Lines are
+//! reported and set as asserted (`true`) or deasserted, independent of their electrical level.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::serial::modem::ModemControl;
+//!
+//! /// Resets a modem by toggling DTR
+//! fn hang_up<S: ModemControl>(serial: &mut S) -> Result<(), S::Error> {
+//!     serial.set_dtr(false)?;
+//!     // ... wait for the modem to drop the connection ...
+//!     serial.set_dtr(true)
+//! }
+//! ```
+
+/// Hardware flow control mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FlowControl {
+    /// No flow control
+    None,
+    /// RTS is deasserted while the receiver can't accept more words
+    Rts,
+    /// Transmission pauses while CTS is deasserted
+    Cts,
+    /// Both RTS and CTS flow control
+    RtsCts,
+}
+
+impl Default for FlowControl {
+    /// No flow control
+    fn default() -> Self {
+        FlowControl::None
+    }
+}
+
+/// Hardware flow control configuration
+pub trait SetFlowControl {
+    /// Error type
+    type Error: super::Error;
+
+    /// Sets the flow control mode
+    ///
+    /// Returns an error if the hardware doesn't support `flow_control`, e.g. because the pins
+    /// aren't connected.
+    fn set_flow_control(&mut self, flow_control: FlowControl) -> Result<(), Self::Error>;
+}
+
+impl<T: SetFlowControl> SetFlowControl for &mut T {
+    type Error = T::Error;
+
+    fn set_flow_control(&mut self, flow_control: FlowControl) -> Result<(), Self::Error> {
+        T::set_flow_control(self, flow_control)
+    }
+}
+
+/// Modem status inputs
+pub trait ModemStatus {
+    /// Error type
+    type Error: super::Error;
+
+    /// Returns `true` if Clear To Send is asserted
+    fn cts(&mut self) -> Result<bool, Self::Error>;
+
+    /// Returns `true` if Data Set Ready is asserted
+    fn dsr(&mut self) -> Result<bool, Self::Error>;
+
+    /// Returns `true` if Data Carrier Detect is asserted
+    fn dcd(&mut self) -> Result<bool, Self::Error>;
+
+    /// Returns `true` if Ring Indicator is asserted
+    fn ri(&mut self) -> Result<bool, Self::Error>;
+}
+
+impl<T: ModemStatus> ModemStatus for &mut T {
+    type Error = T::Error;
+
+    fn cts(&mut self) -> Result<bool, Self::Error> {
+        T::cts(self)
+    }
+
+    fn dsr(&mut self) -> Result<bool, Self::Error> {
+        T::dsr(self)
+    }
+
+    fn dcd(&mut self) -> Result<bool, Self::Error> {
+        T::dcd(self)
+    }
+
+    fn ri(&mut self) -> Result<bool, Self::Error> {
+        T::ri(self)
+    }
+}
+
+/// Modem control outputs
+pub trait ModemControl {
+    /// Error type
+    type Error: super::Error;
+
+    /// Asserts or deasserts Request To Send
+    ///
+    /// If RTS flow control is enabled, the hardware may override the line.
+    fn set_rts(&mut self, asserted: bool) -> Result<(), Self::Error>;
+
+    /// Asserts or deasserts Data Terminal Ready
+    fn set_dtr(&mut self, asserted: bool) -> Result<(), Self::Error>;
+}
+
+impl<T: ModemControl> ModemControl for &mut T {
+    type Error = T::Error;
+
+    fn set_rts(&mut self, asserted: bool) -> Result<(), Self::Error> {
+        T::set_rts(self, asserted)
+    }
+
+    fn set_dtr(&mut self, asserted: bool) -> Result<(), Self::Error> {
+        T::set_dtr(self, asserted)
+    }
+}
@@ -0,0 +1,112 @@
+//! 9-bit multidrop (multiprocessor) mode
+//!
+//! RS-485 multidrop networks and many industrial protocols use 9-bit frames whose ninth bit
+//! marks a frame as an address frame. Nodes ignore the data frames following an address
+//! other than their own, which UART peripherals can do in hardware (address mark wakeup).
+//!
+//! Interfaces supporting this mode implement the serial traits for `u16` words holding the
+//! nine bits, and [`SetMultidrop`] to configure the address filter. [`MultidropWord`]
+//! converts between the raw words and address and data frames.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::serial::blocking::Write;
+//! use embedded_hal::serial::multidrop::MultidropWord;
+//!
+//! /// Sends `data` to the node with `address`
+//! fn send<S: Write<u16>>(serial: &mut S, address: u8, data: &[u8]) -> Result<(), S::Error> {
+//!     serial.write_all(&[MultidropWord::Address(address).into()])?;
+//!     for &byte in data {
+//!         serial.write_all(&[MultidropWord::Data(byte).into()])?;
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+/// Frame of a multidrop network
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MultidropWord {
+    /// Address frame, with the ninth bit set
+    Address(u8),
+    /// Data frame, with the ninth bit cleared
+    Data(u8),
+}
+
+impl MultidropWord {
+    /// Bit marking address frames
+    pub const ADDRESS_BIT: u16 = 1 << 8;
+
+    /// Creates a frame from a raw 9-bit word, ignoring bits above the ninth
+    pub fn from_bits(bits: u16) -> Self {
+        if bits & Self::ADDRESS_BIT != 0 {
+            MultidropWord::Address(bits as u8)
+        } else {
+            MultidropWord::Data(bits as u8)
+        }
+    }
+
+    /// Returns the raw 9-bit word
+    pub fn to_bits(self) -> u16 {
+        match self {
+            MultidropWord::Address(address) => Self::ADDRESS_BIT | u16::from(address),
+            MultidropWord::Data(data) => u16::from(data),
+        }
+    }
+}
+
+impl From<MultidropWord> for u16 {
+    fn from(word: MultidropWord) -> u16 {
+        word.to_bits()
+    }
+}
+
+impl From<u16> for MultidropWord {
+    fn from(bits: u16) -> Self {
+        Self::from_bits(bits)
+    }
+}
+
+/// Multidrop mode of the receiver
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MultidropMode {
+    /// 8-bit frames without address marks
+    Disabled,
+    /// 9-bit frames, all of which are received, e.g. by the controller of the network
+    ReceiveAll,
+    /// 9-bit frames, of which only address frames and the data frames following the given
+    /// address are received
+    Address(u8),
+}
+
+/// Multidrop mode configuration
+pub trait SetMultidrop {
+    /// Error type
+    type Error: super::Error;
+
+    /// Sets the multidrop mode of subsequent transfers
+    ///
+    /// Returns an error if the hardware doesn't support `mode`, e.g. address filtering.
+    fn set_multidrop(&mut self, mode: MultidropMode) -> Result<(), Self::Error>;
+}
+
+impl<T: SetMultidrop> SetMultidrop for &mut T {
+    type Error = T::Error;
+
+    fn set_multidrop(&mut self, mode: MultidropMode) -> Result<(), Self::Error> {
+        T::set_multidrop(self, mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_conversions() {
+        assert_eq!(u16::from(MultidropWord::Address(0x12)), 0x112);
+        assert_eq!(u16::from(MultidropWord::Data(0x12)), 0x012);
+        assert_eq!(MultidropWord::from(0x1FF), MultidropWord::Address(0xFF));
+        assert_eq!(MultidropWord::from(0x2AB), MultidropWord::Data(0xAB));
+    }
+}
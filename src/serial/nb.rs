@@ -1,4 +1,34 @@
 //! Serial interface
+//!
+//! # Polling without transferring data
+//!
+//! Interrupt-driven drivers need to know whether a word has been received or can be sent, to
+//! decide whether to arm or disarm the RX and TX interrupts, without consuming a word or
+//! committing to a write. Implementations of these traits should therefore also implement
+//! [`ReadReady`](super::ReadReady) ("data available") and [`WriteReady`](super::WriteReady)
+//! ("transmit register empty"). Whether all written words have left the shift register can be
+//! polled with [`Write::flush`], which doesn't write anything.
+//!
+//! ```
+//! use embedded_hal::serial::nb::Write;
+//! use embedded_hal::serial::WriteReady;
+//!
+//! /// Feeds the transmitter from `queue` in the TX interrupt, returning `true` if the
+//! /// interrupt is still needed
+//! fn on_tx_interrupt<S>(serial: &mut S, queue: &mut &[u8]) -> Result<bool, <S as Write>::Error>
+//! where
+//!     S: Write + WriteReady<Error = <S as Write>::Error>,
+//! {
+//!     while let Some((&word, rest)) = queue.split_first() {
+//!         if !serial.write_ready()? {
+//!             return Ok(true);
+//!         }
+//!         nb::block!(serial.write(word))?;
+//!         *queue = rest;
+//!     }
+//!     Ok(false)
+//! }
+//! ```
 
 /// Read half of a serial interface
 ///
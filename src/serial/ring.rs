@@ -0,0 +1,78 @@
+//! Serial reception into a DMA ring buffer
+//!
+//! Many UART drivers let a DMA channel write received words into a circular buffer
+//! continuously, so no word is lost while the core is busy. [`Read`] copies out whatever has
+//! accumulated since the last call. If the DMA channel has wrapped around and overwritten
+//! words which haven't been read yet, the read fails with an error whose kind is
+//! [`ErrorKind::Overrun`](super::ErrorKind::Overrun).
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::serial::ring::Read;
+//! use embedded_hal::serial::{Error, ErrorKind};
+//!
+//! /// Collects received words into `line` until a newline, returning the line length once
+//! /// complete. A line with lost words is discarded.
+//! fn poll_line<R: Read>(rx: &mut R, line: &mut [u8], len: &mut usize) -> Result<Option<usize>, R::Error> {
+//!     let n = match rx.read(&mut line[*len..]) {
+//!         Ok(n) => n,
+//!         Err(e) if e.kind() == ErrorKind::Overrun => {
+//!             *len = 0;
+//!             return Ok(None);
+//!         }
+//!         Err(e) => return Err(e),
+//!     };
+//!     let end = *len + n;
+//!     match line[*len..end].iter().position(|&word| word == b'\n') {
+//!         Some(i) => {
+//!             *len = 0;
+//!             Ok(Some(end - n + i))
+//!         }
+//!         None => {
+//!             *len = end;
+//!             Ok(None)
+//!         }
+//!     }
+//! }
+//! ```
+
+/// Reception into a continuously running DMA ring buffer
+pub trait Read<Word = u8> {
+    /// Error type
+    type Error: crate::serial::Error;
+
+    /// Copies the words received since the last call into `buffer`, without blocking
+    ///
+    /// Returns the number of words copied, which is zero if nothing has been received. Words
+    /// which don't fit into `buffer` remain in the ring for the next call.
+    ///
+    /// If words were overwritten before they could be read, an error whose kind is
+    /// [`ErrorKind::Overrun`](crate::serial::ErrorKind::Overrun) is returned and the contents
+    /// of the ring are discarded, so the next call only returns words received after it.
+    fn read(&mut self, buffer: &mut [Word]) -> Result<usize, Self::Error>;
+
+    /// Returns the number of words which can be read right away
+    fn available(&mut self) -> Result<usize, Self::Error>;
+
+    /// Returns the number of words the ring can hold
+    ///
+    /// Reading less often than it takes to receive this many words results in overruns.
+    fn capacity(&self) -> usize;
+}
+
+impl<T: Read<Word>, Word> Read<Word> for &mut T {
+    type Error = T::Error;
+
+    fn read(&mut self, buffer: &mut [Word]) -> Result<usize, Self::Error> {
+        T::read(self, buffer)
+    }
+
+    fn available(&mut self) -> Result<usize, Self::Error> {
+        T::available(self)
+    }
+
+    fn capacity(&self) -> usize {
+        T::capacity(self)
+    }
+}
@@ -0,0 +1,208 @@
+//! Serial error statistics
+//!
+//! [`Statistics`] wraps a serial interface and counts the transferred words and the errors
+//! by kind, so link quality can be monitored in the field, e.g. a rising number of noise
+//! errors on a long RS-485 line.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::serial::nb::Read;
+//! use embedded_hal::serial::stats::Statistics;
+//!
+//! /// Receives words for a while, returning the share of words received with an error in
+//! /// per mille
+//! fn error_rate<S: Read>(serial: S) -> u32 {
+//!     let mut serial = Statistics::new(serial);
+//!     for _ in 0..10_000 {
+//!         let _ = serial.read();
+//!     }
+//!     let counters = serial.counters();
+//!     let errors = counters.errors();
+//!     1000 * errors / (counters.words_read + errors).max(1)
+//! }
+//! ```
+
+use super::{blocking, nb, Error, ErrorKind};
+
+/// Counters of a serial interface
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct Counters {
+    /// Number of words read successfully
+    pub words_read: u32,
+    /// Number of words written successfully
+    pub words_written: u32,
+    /// Number of errors of kind [`ErrorKind::Overrun`]
+    pub overruns: u32,
+    /// Number of errors of kind [`ErrorKind::FrameFormat`]
+    pub frame_format: u32,
+    /// Number of errors of kind [`ErrorKind::Parity`]
+    pub parity: u32,
+    /// Number of errors of kind [`ErrorKind::Noise`]
+    pub noise: u32,
+    /// Number of errors of any other kind
+    pub other: u32,
+}
+
+impl Counters {
+    /// Returns the number of errors of all kinds
+    pub fn errors(&self) -> u32 {
+        self.overruns
+            .wrapping_add(self.frame_format)
+            .wrapping_add(self.parity)
+            .wrapping_add(self.noise)
+            .wrapping_add(self.other)
+    }
+
+    fn record<T, E: Error>(&mut self, result: Result<T, E>) -> Result<T, E> {
+        if let Err(e) = &result {
+            self.count(e.kind());
+        }
+        result
+    }
+
+    fn record_nb<T, E: Error>(&mut self, result: ::nb::Result<T, E>) -> ::nb::Result<T, E> {
+        if let Err(::nb::Error::Other(e)) = &result {
+            self.count(e.kind());
+        }
+        result
+    }
+
+    fn count(&mut self, kind: ErrorKind) {
+        let counter = match kind {
+            ErrorKind::Overrun => &mut self.overruns,
+            ErrorKind::FrameFormat => &mut self.frame_format,
+            ErrorKind::Parity => &mut self.parity,
+            ErrorKind::Noise => &mut self.noise,
+            _ => &mut self.other,
+        };
+        *counter = counter.wrapping_add(1);
+    }
+}
+
+/// Serial interface counting transferred words and errors
+///
+/// Counters wrap around on overflow.
+#[derive(Debug)]
+pub struct Statistics<S> {
+    serial: S,
+    counters: Counters,
+}
+
+impl<S> Statistics<S> {
+    /// Creates a new serial interface with all counters at zero
+    pub fn new(serial: S) -> Self {
+        Statistics {
+            serial,
+            counters: Counters::default(),
+        }
+    }
+
+    /// Returns the counters
+    pub fn counters(&self) -> Counters {
+        self.counters
+    }
+
+    /// Sets all counters to zero
+    pub fn reset(&mut self) {
+        self.counters = Counters::default();
+    }
+
+    /// Releases the serial interface
+    pub fn release(self) -> S {
+        self.serial
+    }
+}
+
+impl<S: blocking::Read<Word>, Word> blocking::Read<Word> for Statistics<S> {
+    type Error = S::Error;
+
+    fn read(&mut self, buffer: &mut [Word]) -> Result<usize, Self::Error> {
+        let n = self.counters.record(self.serial.read(buffer))?;
+        self.counters.words_read = self.counters.words_read.wrapping_add(n as u32);
+        Ok(n)
+    }
+}
+
+impl<S: blocking::Write<Word>, Word> blocking::Write<Word> for Statistics<S> {
+    type Error = S::Error;
+
+    fn write(&mut self, buffer: &[Word]) -> Result<usize, Self::Error> {
+        let n = self.counters.record(self.serial.write(buffer))?;
+        self.counters.words_written = self.counters.words_written.wrapping_add(n as u32);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.counters.record(self.serial.flush())
+    }
+}
+
+impl<S: nb::Read<Word>, Word> nb::Read<Word> for Statistics<S> {
+    type Error = S::Error;
+
+    fn read(&mut self) -> ::nb::Result<Word, Self::Error> {
+        let word = self.counters.record_nb(self.serial.read())?;
+        self.counters.words_read = self.counters.words_read.wrapping_add(1);
+        Ok(word)
+    }
+}
+
+impl<S: nb::Write<Word>, Word> nb::Write<Word> for Statistics<S> {
+    type Error = S::Error;
+
+    fn write(&mut self, word: Word) -> ::nb::Result<(), Self::Error> {
+        self.counters.record_nb(self.serial.write(word))?;
+        self.counters.words_written = self.counters.words_written.wrapping_add(1);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> ::nb::Result<(), Self::Error> {
+        self.counters.record_nb(self.serial.flush())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::nb::Read;
+
+    /// Port receiving words and errors from a list
+    struct Port<'a>(&'a [Result<u8, ErrorKind>]);
+
+    impl Read for Port<'_> {
+        type Error = ErrorKind;
+
+        fn read(&mut self) -> ::nb::Result<u8, Self::Error> {
+            match self.0.split_first() {
+                Some((&result, rest)) => {
+                    self.0 = rest;
+                    Ok(result?)
+                }
+                None => Err(::nb::Error::WouldBlock),
+            }
+        }
+    }
+
+    #[test]
+    fn counts_errors_by_kind() {
+        use ErrorKind::*;
+        let mut serial = Statistics::new(Port(&[
+            Ok(1),
+            Err(Noise),
+            Ok(2),
+            Err(Parity),
+            Err(Noise),
+            Err(BreakDetected),
+        ]));
+        while serial.read() != Err(::nb::Error::WouldBlock) {}
+        let counters = serial.counters();
+        assert_eq!(counters.words_read, 2);
+        assert_eq!(counters.noise, 2);
+        assert_eq!(counters.parity, 1);
+        assert_eq!(counters.other, 1);
+        assert_eq!(counters.errors(), 4);
+        serial.reset();
+        assert_eq!(serial.counters(), Counters::default());
+    }
+}
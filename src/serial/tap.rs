@@ -0,0 +1,221 @@
+//! Mirroring of serial traffic
+//!
+//! [`Tap`] wraps a serial interface and mirrors every word read or written to a [`Sink`],
+//! so protocols can be debugged in the field, e.g. by copying the traffic of a modem link to a
+//! debug UART or an RTT channel.
+//!
+//! Any `FnMut(Direction, &[u8])` closure is a sink. [`Mirror`] writes the traffic to a second
+//! serial writer, tagging it with its direction.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::serial::blocking::{Read, Write};
+//! use embedded_hal::serial::tap::{Mirror, Tap};
+//!
+//! /// Queries the firmware version of a modem, copying the exchange to `debug`
+//! fn version<S: Read + Write<Error = <S as Read>::Error>, D: Write>(
+//!     modem: S,
+//!     debug: D,
+//! ) -> Result<[u8; 8], <S as Read>::Error> {
+//!     let mut modem = Tap::new(modem, Mirror::new(debug));
+//!     modem.write_all(b"AT+CGMR\r")?;
+//!     let mut version = [0; 8];
+//!     modem.read_exact(&mut version)?;
+//!     Ok(version)
+//! }
+//! ```
+
+use super::{blocking, nb};
+
+/// Direction of mirrored words
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Direction {
+    /// Words read from the interface
+    Read,
+    /// Words written to the interface
+    Write,
+}
+
+/// Receiver of mirrored words
+pub trait Sink {
+    /// Handles words transferred in the given direction
+    fn mirror(&mut self, direction: Direction, words: &[u8]);
+}
+
+impl<F: FnMut(Direction, &[u8])> Sink for F {
+    fn mirror(&mut self, direction: Direction, words: &[u8]) {
+        self(direction, words)
+    }
+}
+
+/// [`Sink`] writing the words to a serial writer
+///
+/// Whenever the direction changes, a line starting with `< ` for read words or `> ` for
+/// written words is begun, so the exchange of text-based protocols stays readable. The words
+/// themselves are written unchanged.
+///
+/// Errors of the writer are ignored, so that debug output can't disturb the mirrored link.
+#[derive(Debug)]
+pub struct Mirror<W> {
+    writer: W,
+    direction: Option<Direction>,
+}
+
+impl<W: blocking::Write> Mirror<W> {
+    /// Creates a new sink writing to `writer`
+    pub fn new(writer: W) -> Self {
+        Mirror {
+            writer,
+            direction: None,
+        }
+    }
+
+    /// Releases the writer
+    pub fn release(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: blocking::Write> Sink for Mirror<W> {
+    fn mirror(&mut self, direction: Direction, words: &[u8]) {
+        if self.direction != Some(direction) {
+            let tag: &[u8] = match (self.direction, direction) {
+                (None, Direction::Read) => b"< ",
+                (None, Direction::Write) => b"> ",
+                (Some(_), Direction::Read) => b"\r\n< ",
+                (Some(_), Direction::Write) => b"\r\n> ",
+            };
+            self.direction = Some(direction);
+            let _ = self.writer.write_all(tag);
+        }
+        let _ = self.writer.write_all(words);
+    }
+}
+
+/// Serial interface mirroring its traffic to a [`Sink`]
+///
+/// Words are mirrored once they have been transferred, so words which were not accepted by a
+/// write aren't mirrored.
+#[derive(Debug)]
+pub struct Tap<S, K> {
+    serial: S,
+    sink: K,
+}
+
+impl<S, K: Sink> Tap<S, K> {
+    /// Creates a new interface mirroring the traffic of `serial`
+    pub fn new(serial: S, sink: K) -> Self {
+        Tap { serial, sink }
+    }
+
+    /// Releases the serial interface and the sink
+    pub fn release(self) -> (S, K) {
+        (self.serial, self.sink)
+    }
+}
+
+impl<S: blocking::Read, K: Sink> blocking::Read for Tap<S, K> {
+    type Error = S::Error;
+
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+        let n = self.serial.read(buffer)?;
+        self.sink.mirror(Direction::Read, &buffer[..n]);
+        Ok(n)
+    }
+}
+
+impl<S: blocking::Write, K: Sink> blocking::Write for Tap<S, K> {
+    type Error = S::Error;
+
+    fn write(&mut self, buffer: &[u8]) -> Result<usize, Self::Error> {
+        let n = self.serial.write(buffer)?;
+        self.sink.mirror(Direction::Write, &buffer[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.serial.flush()
+    }
+}
+
+impl<S: nb::Read, K: Sink> nb::Read for Tap<S, K> {
+    type Error = S::Error;
+
+    fn read(&mut self) -> ::nb::Result<u8, Self::Error> {
+        let word = self.serial.read()?;
+        self.sink.mirror(Direction::Read, &[word]);
+        Ok(word)
+    }
+}
+
+impl<S: nb::Write, K: Sink> nb::Write for Tap<S, K> {
+    type Error = S::Error;
+
+    fn write(&mut self, word: u8) -> ::nb::Result<(), Self::Error> {
+        self.serial.write(word)?;
+        self.sink.mirror(Direction::Write, &[word]);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> ::nb::Result<(), Self::Error> {
+        self.serial.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::ErrorKind;
+    use blocking::{Read, Write};
+
+    /// Port answering every read with `OK` and accepting at most two words per write, or a
+    /// debug output recording what is written to it
+    struct Port {
+        sent: [u8; 32],
+        len: usize,
+    }
+
+    impl Read for Port {
+        type Error = ErrorKind;
+
+        fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+            buffer[..2].copy_from_slice(b"OK");
+            Ok(2)
+        }
+    }
+
+    impl Write for Port {
+        type Error = ErrorKind;
+
+        fn write(&mut self, buffer: &[u8]) -> Result<usize, Self::Error> {
+            let n = buffer.len().min(2);
+            self.sent[self.len..self.len + n].copy_from_slice(&buffer[..n]);
+            self.len += n;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    fn port() -> Port {
+        Port {
+            sent: [0; 32],
+            len: 0,
+        }
+    }
+
+    #[test]
+    fn mirrors_with_direction_tags() {
+        let mut serial = Tap::new(port(), Mirror::new(port()));
+        serial.write_all(b"AT\r").unwrap();
+        let mut response = [0; 4];
+        serial.read_exact(&mut response).unwrap();
+        assert_eq!(Write::write(&mut serial, b"ATI"), Ok(2));
+        let (_, mirror) = serial.release();
+        let debug = mirror.release();
+        assert_eq!(&debug.sent[..debug.len], b"> AT\r\r\n< OKOK\r\n> AT");
+    }
+}
@@ -0,0 +1,268 @@
+//! Serial transfers with a deadline
+//!
+//! [`TimeoutSerial`] combines a non-blocking serial interface with a [`CountDown`] timer and
+//! implements the blocking traits on top, failing with [`ErrorKind::Timeout`] when nothing
+//! could be transferred before the timer expired. Request/response protocols can thereby
+//! recover from dropped words without relying on HAL-specific timeout registers.
+//!
+//! The timer is restarted by every call. A read waits up to the timeout for the first word
+//! and then returns the words which arrived without waiting for more, so a response can be
+//! collected with [`read_exact`](Read::read_exact), which fails if the gap between two words
+//! exceeds the timeout.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::serial::blocking::{Read, Write};
+//! use embedded_hal::serial::nb;
+//! use embedded_hal::serial::timeout::TimeoutSerial;
+//! use embedded_hal::timer::nb::CountDown;
+//!
+//! /// Sends a query to a modem, returning `None` if it didn't respond in time
+//! fn query<S, T>(serial: S, timer: T, timeout: T::Time) -> Option<[u8; 4]>
+//! where
+//!     S: nb::Read + nb::Write,
+//!     T: CountDown,
+//!     T::Time: Clone,
+//! {
+//!     let mut serial = TimeoutSerial::new(serial, timer, timeout);
+//!     serial.write_all(b"AT\r\n").ok()?;
+//!     let mut response = [0; 4];
+//!     serial.read_exact(&mut response).ok()?;
+//!     Some(response)
+//! }
+//! ```
+
+use super::blocking::{Read, Write};
+use super::{nb, ErrorKind};
+use crate::timer::nb::CountDown;
+
+/// Error of a [`TimeoutSerial`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error<E, TE> {
+    /// The inner serial interface returned an error
+    Serial(E),
+    /// The timer returned an error
+    Timer(TE),
+    /// Nothing was transferred before the timer expired
+    Timeout,
+}
+
+impl<E: super::Error, TE: core::fmt::Debug> super::Error for Error<E, TE> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Serial(e) => e.kind(),
+            Self::Timer(_) => ErrorKind::Other,
+            Self::Timeout => ErrorKind::Timeout,
+        }
+    }
+}
+
+/// Blocking serial interface with a deadline on every call
+#[derive(Debug)]
+pub struct TimeoutSerial<S, T: CountDown> {
+    serial: S,
+    timer: T,
+    timeout: T::Time,
+}
+
+impl<S, T> TimeoutSerial<S, T>
+where
+    T: CountDown,
+    T::Time: Clone,
+{
+    /// Creates a new serial interface which fails calls taking longer than `timeout`
+    pub fn new(serial: S, timer: T, timeout: T::Time) -> Self {
+        TimeoutSerial {
+            serial,
+            timer,
+            timeout,
+        }
+    }
+
+    /// Releases the serial interface and the timer
+    pub fn release(self) -> (S, T) {
+        (self.serial, self.timer)
+    }
+
+    /// Polls `f` until it succeeds, fails or the timer expires
+    fn poll<R, E>(
+        &mut self,
+        mut f: impl FnMut(&mut S) -> ::nb::Result<R, E>,
+    ) -> Result<R, Error<E, T::Error>> {
+        self.timer
+            .start(self.timeout.clone())
+            .map_err(Error::Timer)?;
+        loop {
+            match f(&mut self.serial) {
+                Ok(r) => return Ok(r),
+                Err(::nb::Error::Other(e)) => return Err(Error::Serial(e)),
+                Err(::nb::Error::WouldBlock) => match self.timer.wait() {
+                    Ok(()) => return Err(Error::Timeout),
+                    Err(::nb::Error::WouldBlock) => {}
+                    Err(::nb::Error::Other(e)) => return Err(Error::Timer(e)),
+                },
+            }
+        }
+    }
+}
+
+impl<S, T, Word> Read<Word> for TimeoutSerial<S, T>
+where
+    S: nb::Read<Word>,
+    T: CountDown,
+    T::Time: Clone,
+{
+    type Error = Error<S::Error, T::Error>;
+
+    fn read(&mut self, buffer: &mut [Word]) -> Result<usize, Self::Error> {
+        let (first, rest) = match buffer.split_first_mut() {
+            Some(split) => split,
+            None => return Ok(0),
+        };
+        *first = self.poll(|serial| serial.read())?;
+        for (n, word) in rest.iter_mut().enumerate() {
+            match self.serial.read() {
+                Ok(w) => *word = w,
+                Err(::nb::Error::WouldBlock) => return Ok(n + 1),
+                Err(::nb::Error::Other(e)) => return Err(Error::Serial(e)),
+            }
+        }
+        Ok(buffer.len())
+    }
+}
+
+impl<S, T, Word> Write<Word> for TimeoutSerial<S, T>
+where
+    S: nb::Write<Word>,
+    T: CountDown,
+    T::Time: Clone,
+    Word: Copy,
+{
+    type Error = Error<S::Error, T::Error>;
+
+    fn write(&mut self, buffer: &[Word]) -> Result<usize, Self::Error> {
+        let (&first, rest) = match buffer.split_first() {
+            Some(split) => split,
+            None => return Ok(0),
+        };
+        self.poll(|serial| serial.write(first))?;
+        for (n, &word) in rest.iter().enumerate() {
+            match self.serial.write(word) {
+                Ok(()) => {}
+                Err(::nb::Error::WouldBlock) => return Ok(n + 1),
+                Err(::nb::Error::Other(e)) => return Err(Error::Serial(e)),
+            }
+        }
+        Ok(buffer.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.poll(|serial| serial.flush())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::Error as _;
+    use core::cell::Cell;
+    use core::convert::Infallible;
+
+    /// Port on which every poll takes one tick, receiving words at the given times and never
+    /// completing a flush
+    struct Port<'a> {
+        now: &'a Cell<u32>,
+        arrivals: &'a [(u32, u8)],
+    }
+
+    impl nb::Read for Port<'_> {
+        type Error = ErrorKind;
+
+        fn read(&mut self) -> ::nb::Result<u8, Self::Error> {
+            self.now.set(self.now.get() + 1);
+            match self.arrivals.split_first() {
+                Some((&(time, word), rest)) if time <= self.now.get() => {
+                    self.arrivals = rest;
+                    Ok(word)
+                }
+                _ => Err(::nb::Error::WouldBlock),
+            }
+        }
+    }
+
+    impl nb::Write for Port<'_> {
+        type Error = ErrorKind;
+
+        fn write(&mut self, _word: u8) -> ::nb::Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn flush(&mut self) -> ::nb::Result<(), Self::Error> {
+            self.now.set(self.now.get() + 1);
+            Err(::nb::Error::WouldBlock)
+        }
+    }
+
+    struct Timer<'a> {
+        now: &'a Cell<u32>,
+        deadline: u32,
+    }
+
+    impl CountDown for Timer<'_> {
+        type Error = Infallible;
+        type Time = u32;
+
+        fn start<T: Into<u32>>(&mut self, count: T) -> Result<(), Self::Error> {
+            self.deadline = self.now.get() + count.into();
+            Ok(())
+        }
+
+        fn wait(&mut self) -> ::nb::Result<(), Self::Error> {
+            if self.now.get() >= self.deadline {
+                Ok(())
+            } else {
+                Err(::nb::Error::WouldBlock)
+            }
+        }
+    }
+
+    #[test]
+    fn reads_until_gap_exceeds_timeout() {
+        let now = Cell::new(0);
+        let port = Port {
+            now: &now,
+            arrivals: &[(5, 1), (6, 2), (12, 3), (30, 4)],
+        };
+        let timer = Timer {
+            now: &now,
+            deadline: 0,
+        };
+        let mut serial = TimeoutSerial::new(port, timer, 10);
+        let mut buffer = [0; 4];
+        assert_eq!(serial.read(&mut buffer), Ok(2));
+        assert_eq!(buffer[..2], [1, 2]);
+        assert_eq!(serial.read(&mut buffer), Ok(1));
+        assert_eq!(buffer[0], 3);
+        let result = serial.read(&mut buffer);
+        assert_eq!(result, Err(Error::Timeout));
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::Timeout);
+        assert_eq!(now.get(), 23);
+    }
+
+    #[test]
+    fn flush_times_out() {
+        let now = Cell::new(0);
+        let port = Port {
+            now: &now,
+            arrivals: &[],
+        };
+        let timer = Timer {
+            now: &now,
+            deadline: 0,
+        };
+        let mut serial = TimeoutSerial::new(port, timer, 10);
+        assert_eq!(serial.write(&[1, 2, 3]), Ok(3));
+        assert_eq!(serial.flush(), Err(Error::Timeout));
+    }
+}
@@ -0,0 +1,427 @@
+//! System Management Bus (SMBus)
+//!
+//! SMBus is a two-wire protocol based on I2C which defines a fixed set of
+//! transfer formats ("protocols") and an optional Packet Error Code (PEC) byte,
+//! a CRC-8 computed over every byte of a transfer, including the address bytes.
+//!
+//! [`SmBus`] implements these transfer formats on top of any blocking
+//! [`I2c`] implementation. Word values are transferred least significant
+//! byte first, as required by the SMBus specification.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::i2c::blocking::I2c;
+//! use embedded_hal::smbus::{Error, SmBus};
+//!
+//! const ADDR: u8 = 0x0B;
+//! const VOLTAGE: u8 = 0x09;
+//!
+//! pub fn battery_voltage<I2C: I2c>(i2c: I2C) -> Result<u16, Error<I2C::Error>> {
+//!     let mut smbus = SmBus::new(i2c).with_pec(true);
+//!     smbus.read_word(ADDR, VOLTAGE)
+//! }
+//! ```
+
+use crate::i2c::blocking::{I2c, Operation};
+use crate::i2c::{ErrorKind, SevenBitAddress};
+
+/// Maximum number of data bytes in a block transfer
+pub const MAX_BLOCK_LEN: usize = 255;
+
+/// SMBus error
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error<E> {
+    /// The underlying I2C implementation returned an error
+    I2c(E),
+    /// The PEC byte received from the device does not match the transferred data
+    Pec,
+    /// The block length is larger than [`MAX_BLOCK_LEN`] or than the provided buffer
+    BlockLength,
+}
+
+impl<E: crate::i2c::Error> crate::i2c::Error for Error<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::I2c(e) => e.kind(),
+            Self::Pec | Self::BlockLength => ErrorKind::Other,
+        }
+    }
+}
+
+/// Packet Error Code calculation (CRC-8, polynomial `x^8 + x^2 + x + 1`)
+///
+/// ```
+/// use embedded_hal::smbus::Pec;
+///
+/// let mut pec = Pec::new();
+/// pec.update(b"1234");
+/// pec.update(b"56789");
+/// assert_eq!(pec.finish(), 0xF4);
+/// ```
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct Pec(u8);
+
+impl Pec {
+    /// Creates a new PEC calculation
+    pub const fn new() -> Self {
+        Pec(0)
+    }
+
+    /// Adds `bytes` to the calculation
+    pub fn update(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= byte;
+            for _ in 0..8 {
+                self.0 = if self.0 & 0x80 != 0 {
+                    (self.0 << 1) ^ 0x07
+                } else {
+                    self.0 << 1
+                };
+            }
+        }
+    }
+
+    /// Returns the PEC of all bytes added so far
+    pub fn finish(&self) -> u8 {
+        self.0
+    }
+}
+
+/// SMBus controller on top of an I2C implementation
+#[derive(Debug)]
+pub struct SmBus<I2C> {
+    i2c: I2C,
+    pec: bool,
+}
+
+impl<I2C: I2c> SmBus<I2C> {
+    /// Creates a new SMBus controller with PEC disabled
+    pub fn new(i2c: I2C) -> Self {
+        SmBus { i2c, pec: false }
+    }
+
+    /// Enables or disables PEC generation and verification
+    pub fn with_pec(mut self, pec: bool) -> Self {
+        self.pec = pec;
+        self
+    }
+
+    /// Releases the underlying I2C implementation
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+
+    /// Quick Command: sends only the address and the read/write bit
+    ///
+    /// `read` selects the value of the R/W bit. No PEC is used.
+    pub fn quick_command(
+        &mut self,
+        address: SevenBitAddress,
+        read: bool,
+    ) -> Result<(), Error<I2C::Error>> {
+        let result = if read {
+            self.i2c.read(address, &mut [])
+        } else {
+            self.i2c.write(address, &[])
+        };
+        result.map_err(Error::I2c)
+    }
+
+    /// Send Byte: writes a single byte without a command code
+    pub fn send_byte(
+        &mut self,
+        address: SevenBitAddress,
+        byte: u8,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.write(address, &[], &[byte])
+    }
+
+    /// Receive Byte: reads a single byte without a command code
+    pub fn receive_byte(&mut self, address: SevenBitAddress) -> Result<u8, Error<I2C::Error>> {
+        let mut buffer = [0; 2];
+        let len = if self.pec { 2 } else { 1 };
+        self.i2c
+            .read(address, &mut buffer[..len])
+            .map_err(Error::I2c)?;
+        if self.pec {
+            let mut pec = Pec::new();
+            pec.update(&[read_address(address), buffer[0]]);
+            check_pec(&pec, buffer[1])?;
+        }
+        Ok(buffer[0])
+    }
+
+    /// Write Byte: writes a single data byte to `command`
+    pub fn write_byte(
+        &mut self,
+        address: SevenBitAddress,
+        command: u8,
+        byte: u8,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.write(address, &[command], &[byte])
+    }
+
+    /// Read Byte: reads a single data byte from `command`
+    pub fn read_byte(
+        &mut self,
+        address: SevenBitAddress,
+        command: u8,
+    ) -> Result<u8, Error<I2C::Error>> {
+        let mut buffer = [0; 1];
+        self.write_read(address, &[command], &mut buffer)?;
+        Ok(buffer[0])
+    }
+
+    /// Write Word: writes a 16-bit data word to `command`
+    pub fn write_word(
+        &mut self,
+        address: SevenBitAddress,
+        command: u8,
+        word: u16,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.write(address, &[command], &word.to_le_bytes())
+    }
+
+    /// Read Word: reads a 16-bit data word from `command`
+    pub fn read_word(
+        &mut self,
+        address: SevenBitAddress,
+        command: u8,
+    ) -> Result<u16, Error<I2C::Error>> {
+        let mut buffer = [0; 2];
+        self.write_read(address, &[command], &mut buffer)?;
+        Ok(u16::from_le_bytes(buffer))
+    }
+
+    /// Process Call: writes a 16-bit data word to `command` and reads back a 16-bit word
+    pub fn process_call(
+        &mut self,
+        address: SevenBitAddress,
+        command: u8,
+        word: u16,
+    ) -> Result<u16, Error<I2C::Error>> {
+        let [lsb, msb] = word.to_le_bytes();
+        let mut buffer = [0; 2];
+        self.write_read(address, &[command, lsb, msb], &mut buffer)?;
+        Ok(u16::from_le_bytes(buffer))
+    }
+
+    /// Block Write: writes a byte count followed by `bytes` to `command`
+    pub fn block_write(
+        &mut self,
+        address: SevenBitAddress,
+        command: u8,
+        bytes: &[u8],
+    ) -> Result<(), Error<I2C::Error>> {
+        if bytes.len() > MAX_BLOCK_LEN {
+            return Err(Error::BlockLength);
+        }
+        self.write(address, &[command, bytes.len() as u8], bytes)
+    }
+
+    /// Block Read: reads a byte count followed by that many bytes from `command`
+    ///
+    /// As the byte count is only known while the transfer is ongoing, `buffer.len()` bytes
+    /// are clocked out after the byte count, so `buffer` should be sized to the block length
+    /// the device is expected to return. Returns the number of valid bytes in `buffer`.
+    pub fn block_read(
+        &mut self,
+        address: SevenBitAddress,
+        command: u8,
+        buffer: &mut [u8],
+    ) -> Result<usize, Error<I2C::Error>> {
+        if buffer.len() > MAX_BLOCK_LEN {
+            return Err(Error::BlockLength);
+        }
+        let mut data = [0; MAX_BLOCK_LEN + 2];
+        let len = buffer.len() + 1 + self.pec as usize;
+        self.i2c
+            .write_read(address, &[command], &mut data[..len])
+            .map_err(Error::I2c)?;
+
+        let count = data[0] as usize;
+        if count > buffer.len() {
+            return Err(Error::BlockLength);
+        }
+        if self.pec {
+            let mut pec = Pec::new();
+            pec.update(&[write_address(address), command, read_address(address)]);
+            pec.update(&data[..=count]);
+            check_pec(&pec, data[count + 1])?;
+        }
+        buffer[..count].copy_from_slice(&data[1..=count]);
+        Ok(count)
+    }
+
+    fn write(
+        &mut self,
+        address: SevenBitAddress,
+        header: &[u8],
+        bytes: &[u8],
+    ) -> Result<(), Error<I2C::Error>> {
+        let mut pec = Pec::new();
+        pec.update(&[write_address(address)]);
+        pec.update(header);
+        pec.update(bytes);
+        let pec = [pec.finish()];
+
+        let operations = &mut [
+            Operation::Write(header),
+            Operation::Write(bytes),
+            Operation::Write(&pec),
+        ];
+        let len = if self.pec { 3 } else { 2 };
+        self.i2c
+            .transaction(address, &mut operations[..len])
+            .map_err(Error::I2c)
+    }
+
+    fn write_read(
+        &mut self,
+        address: SevenBitAddress,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Error<I2C::Error>> {
+        if !self.pec {
+            return self
+                .i2c
+                .write_read(address, bytes, buffer)
+                .map_err(Error::I2c);
+        }
+
+        let mut received = [0; 1];
+        self.i2c
+            .transaction(
+                address,
+                &mut [
+                    Operation::Write(bytes),
+                    Operation::Read(buffer),
+                    Operation::Read(&mut received),
+                ],
+            )
+            .map_err(Error::I2c)?;
+
+        let mut pec = Pec::new();
+        pec.update(&[write_address(address)]);
+        pec.update(bytes);
+        pec.update(&[read_address(address)]);
+        pec.update(buffer);
+        check_pec(&pec, received[0])
+    }
+}
+
+fn write_address(address: SevenBitAddress) -> u8 {
+    address << 1
+}
+
+fn read_address(address: SevenBitAddress) -> u8 {
+    (address << 1) | 1
+}
+
+fn check_pec<E>(pec: &Pec, received: u8) -> Result<(), Error<E>> {
+    if pec.finish() == received {
+        Ok(())
+    } else {
+        Err(Error::Pec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Device which answers every read with the bytes from `response`
+    /// and records the bytes of the last write.
+    struct Device {
+        response: [u8; 8],
+        written: [u8; 8],
+        written_len: usize,
+    }
+
+    impl I2c for Device {
+        type Error = ErrorKind;
+
+        fn transaction<'a>(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'a>],
+        ) -> Result<(), Self::Error> {
+            assert_eq!(address, 0x0B);
+            self.written_len = 0;
+            let mut read = 0;
+            for operation in operations {
+                match operation {
+                    Operation::Write(bytes) => {
+                        let end = self.written_len + bytes.len();
+                        self.written[self.written_len..end].copy_from_slice(bytes);
+                        self.written_len = end;
+                    }
+                    Operation::Read(buffer) => {
+                        let end = read + buffer.len();
+                        buffer.copy_from_slice(&self.response[read..end]);
+                        read = end;
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn device(response: [u8; 8]) -> Device {
+        Device {
+            response,
+            written: [0; 8],
+            written_len: 0,
+        }
+    }
+
+    #[test]
+    fn pec_check_value() {
+        let mut pec = Pec::new();
+        pec.update(b"123456789");
+        assert_eq!(pec.finish(), 0xF4);
+    }
+
+    #[test]
+    fn write_word_appends_pec() {
+        let mut smbus = SmBus::new(device([0; 8])).with_pec(true);
+        smbus.write_word(0x0B, 0x09, 0x1234).unwrap();
+
+        let mut pec = Pec::new();
+        pec.update(&[0x16, 0x09, 0x34, 0x12]);
+        let device = smbus.release();
+        assert_eq!(
+            &device.written[..device.written_len],
+            &[0x09, 0x34, 0x12, pec.finish()]
+        );
+    }
+
+    #[test]
+    fn read_word_checks_pec() {
+        let mut pec = Pec::new();
+        pec.update(&[0x16, 0x09, 0x17, 0x34, 0x12]);
+        let response = [0x34, 0x12, pec.finish(), 0, 0, 0, 0, 0];
+
+        let mut smbus = SmBus::new(device(response)).with_pec(true);
+        assert_eq!(smbus.read_word(0x0B, 0x09), Ok(0x1234));
+
+        let mut smbus =
+            SmBus::new(device([0x34, 0x12, !pec.finish(), 0, 0, 0, 0, 0])).with_pec(true);
+        assert_eq!(smbus.read_word(0x0B, 0x09), Err(Error::Pec));
+    }
+
+    #[test]
+    fn block_read_returns_count() {
+        let mut smbus = SmBus::new(device([2, 0xAA, 0xBB, 0xFF, 0xFF, 0, 0, 0]));
+        let mut buffer = [0; 4];
+        assert_eq!(smbus.block_read(0x0B, 0x20, &mut buffer), Ok(2));
+        assert_eq!(&buffer[..2], &[0xAA, 0xBB]);
+
+        let mut smbus = SmBus::new(device([5, 0, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(
+            smbus.block_read(0x0B, 0x20, &mut buffer),
+            Err(Error::BlockLength)
+        );
+    }
+}
@@ -0,0 +1,253 @@
+//! Async SPI API
+//!
+//! The async counterparts of the [`blocking`](super::blocking) traits. The split between bus and
+//! device traits, the flushing rules and the transaction contracts are the same.
+//!
+//! Only available with the `async` feature.
+
+pub use super::blocking::Operation;
+use super::Word;
+
+/// Flush support for SPI bus
+pub trait SpiBusFlush {
+    /// Error type
+    type Error: crate::spi::Error;
+
+    /// Waits until all operations have completed and the bus is idle.
+    async fn flush(&mut self) -> Result<(), Self::Error>;
+}
+
+impl<T: SpiBusFlush> SpiBusFlush for &mut T {
+    type Error = T::Error;
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        T::flush(self).await
+    }
+}
+
+/// Read-only SPI bus
+pub trait SpiBusRead<W: Word = u8>: SpiBusFlush {
+    /// Reads `words` from the slave.
+    async fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error>;
+}
+
+impl<T: SpiBusRead<W>, W: Word> SpiBusRead<W> for &mut T {
+    async fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        T::read(self, words).await
+    }
+}
+
+/// Write-only SPI bus
+pub trait SpiBusWrite<W: Word = u8>: SpiBusFlush {
+    /// Writes `words` to the slave, ignoring all the incoming words
+    async fn write(&mut self, words: &[W]) -> Result<(), Self::Error>;
+}
+
+impl<T: SpiBusWrite<W>, W: Word> SpiBusWrite<W> for &mut T {
+    async fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        T::write(self, words).await
+    }
+}
+
+/// Read-write SPI bus
+pub trait SpiBus<W: Word = u8>: SpiBusRead<W> + SpiBusWrite<W> {
+    /// Writes and reads simultaneously
+    ///
+    /// Same contract as [`blocking::SpiBus::transfer`](super::blocking::SpiBus::transfer).
+    async fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error>;
+
+    /// Writes and reads simultaneously, in place
+    async fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error>;
+}
+
+impl<T: SpiBus<W>, W: Word> SpiBus<W> for &mut T {
+    async fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+        T::transfer(self, read, write).await
+    }
+
+    async fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        T::transfer_in_place(self, words).await
+    }
+}
+
+/// SPI device with a managed chip-select line
+pub trait SpiDevice<W: Word = u8> {
+    /// Error type
+    type Error: crate::spi::Error;
+
+    /// Executes the provided operations with CS asserted
+    ///
+    /// Same contract as [`blocking::SpiDevice::transaction`](super::blocking::SpiDevice::transaction).
+    async fn transaction<'a>(
+        &mut self,
+        operations: &mut [Operation<'a, W>],
+    ) -> Result<(), Self::Error>;
+
+    /// Reads `words` in a single transaction
+    async fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        self.transaction(&mut [Operation::Read(words)]).await
+    }
+
+    /// Writes `words` in a single transaction
+    async fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        self.transaction(&mut [Operation::Write(words)]).await
+    }
+
+    /// Writes `write` and reads into `read` simultaneously in a single transaction
+    async fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+        self.transaction(&mut [Operation::Transfer(read, write)])
+            .await
+    }
+
+    /// Writes `words` and reads into it simultaneously in a single transaction
+    async fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        self.transaction(&mut [Operation::TransferInPlace(words)])
+            .await
+    }
+}
+
+impl<T: SpiDevice<W>, W: Word> SpiDevice<W> for &mut T {
+    type Error = T::Error;
+
+    async fn transaction<'a>(
+        &mut self,
+        operations: &mut [Operation<'a, W>],
+    ) -> Result<(), Self::Error> {
+        T::transaction(self, operations).await
+    }
+
+    async fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        T::read(self, words).await
+    }
+
+    async fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        T::write(self, words).await
+    }
+
+    async fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+        T::transfer(self, read, write).await
+    }
+
+    async fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        T::transfer_in_place(self, words).await
+    }
+}
+
+/// SPI device giving access to its bus within a transaction
+///
+/// The async counterpart of [`blocking::SpiDeviceBus`](super::blocking::SpiDeviceBus).
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::spi::asynch::{SpiBus, SpiBusRead, SpiBusWrite, SpiDeviceBus};
+///
+/// /// Reads a length-prefixed frame, returning its length
+/// async fn read_frame<SPI>(spi: &mut SPI, frame: &mut [u8]) -> Result<usize, SPI::Error>
+/// where
+///     SPI: SpiDeviceBus,
+///     SPI::Bus: SpiBus,
+/// {
+///     spi.bus_transaction(async |bus| {
+///         bus.write(&[0x0B]).await?;
+///         let mut len = [0];
+///         bus.read(&mut len).await?;
+///         let len = (len[0] as usize).min(frame.len());
+///         bus.read(&mut frame[..len]).await?;
+///         Ok(len)
+///     })
+///     .await
+/// }
+/// ```
+pub trait SpiDeviceBus {
+    /// Error type
+    type Error: crate::spi::Error;
+
+    /// Bus type
+    type Bus: SpiBusFlush;
+
+    /// Calls `f` with exclusive access to the bus and CS asserted
+    ///
+    /// Same contract as
+    /// [`blocking::SpiDeviceBus::bus_transaction`](super::blocking::SpiDeviceBus::bus_transaction).
+    async fn bus_transaction<R, F>(&mut self, f: F) -> Result<R, Self::Error>
+    where
+        F: AsyncFnOnce(&mut Self::Bus) -> Result<R, <Self::Bus as SpiBusFlush>::Error>;
+}
+
+impl<T: SpiDeviceBus> SpiDeviceBus for &mut T {
+    type Error = T::Error;
+    type Bus = T::Bus;
+
+    async fn bus_transaction<R, F>(&mut self, f: F) -> Result<R, Self::Error>
+    where
+        F: AsyncFnOnce(&mut Self::Bus) -> Result<R, <Self::Bus as SpiBusFlush>::Error>,
+    {
+        T::bus_transaction(self, f).await
+    }
+}
+
+/// Half-duplex (3-wire) SPI bus
+///
+/// The async counterpart of [`blocking::HalfDuplex`](super::blocking::HalfDuplex).
+pub trait HalfDuplex: SpiBusFlush {}
+
+impl<T: HalfDuplex> HalfDuplex for &mut T {}
+
+/// Half-duplex (3-wire) SPI device with a managed chip-select line
+///
+/// The async counterpart of [`blocking::SpiDeviceHalfDuplex`](super::blocking::SpiDeviceHalfDuplex).
+pub trait SpiDeviceHalfDuplex<W: Word = u8> {
+    /// Error type
+    type Error: crate::spi::Error;
+
+    /// Writes `write`, turns the data line around and reads into `read`, in a single
+    /// transaction
+    async fn write_then_read(&mut self, write: &[W], read: &mut [W]) -> Result<(), Self::Error>;
+
+    /// Writes `words` in a single transaction
+    async fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        self.write_then_read(words, &mut []).await
+    }
+
+    /// Reads `words` in a single transaction
+    async fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        self.write_then_read(&[], words).await
+    }
+}
+
+impl<T: SpiDeviceHalfDuplex<W>, W: Word> SpiDeviceHalfDuplex<W> for &mut T {
+    type Error = T::Error;
+
+    async fn write_then_read(&mut self, write: &[W], read: &mut [W]) -> Result<(), Self::Error> {
+        T::write_then_read(self, write, read).await
+    }
+
+    async fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        T::write(self, words).await
+    }
+
+    async fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        T::read(self, words).await
+    }
+}
+
+/// Async SPI target
+///
+/// Same contract as [`target::Target`](super::target::Target). The future completes when the
+/// controller deasserts CS at the end of the transaction.
+pub trait Target<W: Word = u8> {
+    /// Error type
+    type Error: crate::spi::Error;
+
+    /// Pre-loads `write`, then waits until the controller has completed a transaction
+    async fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<usize, Self::Error>;
+}
+
+impl<T: Target<W>, W: Word> Target<W> for &mut T {
+    type Error = T::Error;
+
+    async fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<usize, Self::Error> {
+        T::transfer(self, read, write).await
+    }
+}
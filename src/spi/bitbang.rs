@@ -0,0 +1,593 @@
+//! Bit-banged SPI bus
+//!
+//! [`Bitbang`] drives SCK and MOSI and samples MISO with plain GPIO pins, for devices on pins
+//! which are not connected to an SPI peripheral. Every half clock period is timed with a
+//! delay, so the clock frequency is only an upper bound.
+//!
+//! With a [blocking delay](crate::delay::blocking::DelayUs), it implements the blocking bus
+//! traits. With an [async delay](crate::delay::asynch::DelayUs) (`async` feature), it
+//! implements the async bus traits instead, so low-speed auxiliary devices can be serviced
+//! without blocking the executor while waiting for the clock edges. Since the bus drives the
+//! clock itself, the pins don't need to support waiting for edges.
+//!
+//! All word sizes and both bit orders are supported. Combine it with a device implementation
+//! such as [`ExclusiveDevice`](super::exclusive::ExclusiveDevice) to drive CS.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::delay::blocking::DelayUs;
+//! use embedded_hal::digital::blocking::{InputPin, OutputPin};
+//! use embedded_hal::spi::bitbang::{Bitbang, Error};
+//! use embedded_hal::spi::blocking::SpiBus;
+//! use embedded_hal::spi::MODE_3;
+//!
+//! /// Reads the ID of a flash chip on spare pins
+//! fn read_id<SCK, MOSI, MISO, D, E>(
+//!     sck: SCK,
+//!     mosi: MOSI,
+//!     miso: MISO,
+//!     delay: D,
+//! ) -> Result<[u8; 4], Error<E, D::Error>>
+//! where
+//!     SCK: OutputPin<Error = E>,
+//!     MOSI: OutputPin<Error = E>,
+//!     MISO: InputPin<Error = E>,
+//!     D: DelayUs,
+//!     E: core::fmt::Debug,
+//! {
+//!     let mut bus = Bitbang::new(sck, mosi, miso, delay)
+//!         .with_mode(MODE_3)
+//!         .map_err(Error::Pin)?
+//!         .with_half_period_us(5);
+//!     let mut id = [0x9F, 0, 0, 0];
+//!     bus.transfer_in_place(&mut id)?;
+//!     Ok(id)
+//! }
+//! ```
+
+use super::blocking::{SpiBus, SpiBusFlush, SpiBusRead, SpiBusWrite};
+use super::{
+    BitOrder, Config, ErrorKind, FrameFormat, Mode, Phase, Polarity, SetBitOrder, SetConfig, Word,
+    MODE_0,
+};
+use crate::delay::blocking::DelayUs;
+use crate::digital::blocking::{InputPin, OutputPin};
+
+/// Bit-banged bus error
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error<P, D> {
+    /// Setting or reading a pin failed
+    Pin(P),
+    /// The delay failed
+    Delay(D),
+    /// The configuration requested a frame format other than Motorola SPI
+    Unsupported,
+}
+
+impl<P: core::fmt::Debug, D: core::fmt::Debug> super::Error for Error<P, D> {
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
+
+/// SPI bus driving GPIO pins
+///
+/// `SCK`, `MOSI` and `MISO` must share their error type, which is the case for the pins of
+/// most HALs.
+#[derive(Debug)]
+pub struct Bitbang<SCK, MOSI, MISO, D> {
+    sck: SCK,
+    mosi: MOSI,
+    miso: MISO,
+    delay: D,
+    mode: Mode,
+    bit_order: BitOrder,
+    half_period_us: u32,
+}
+
+impl<SCK, MOSI, MISO, D, E> Bitbang<SCK, MOSI, MISO, D>
+where
+    SCK: OutputPin<Error = E>,
+    MOSI: OutputPin<Error = E>,
+    MISO: InputPin<Error = E>,
+{
+    /// Creates a new bus in SPI mode 0, MSB first, with a half clock period of 1 µs
+    ///
+    /// `sck` must already be low, or the first clock edge will be missed.
+    pub fn new(sck: SCK, mosi: MOSI, miso: MISO, delay: D) -> Self {
+        Bitbang {
+            sck,
+            mosi,
+            miso,
+            delay,
+            mode: MODE_0,
+            bit_order: BitOrder::MsbFirst,
+            half_period_us: 1,
+        }
+    }
+
+    /// Sets the SPI mode, moving SCK to its idle level
+    pub fn with_mode(mut self, mode: Mode) -> Result<Self, E> {
+        self.set_mode(mode)?;
+        Ok(self)
+    }
+
+    /// Sets the bit order
+    pub fn with_bit_order(mut self, bit_order: BitOrder) -> Self {
+        self.bit_order = bit_order;
+        self
+    }
+
+    /// Sets the duration of half a clock period
+    pub fn with_half_period_us(mut self, half_period_us: u32) -> Self {
+        self.half_period_us = half_period_us;
+        self
+    }
+
+    /// Releases the pins and the delay
+    pub fn release(self) -> (SCK, MOSI, MISO, D) {
+        (self.sck, self.mosi, self.miso, self.delay)
+    }
+
+    fn set_mode(&mut self, mode: Mode) -> Result<(), E> {
+        self.mode = mode;
+        self.set_sck(false)
+    }
+
+    fn set_sck(&mut self, active: bool) -> Result<(), E> {
+        if active == (self.mode.polarity == Polarity::IdleLow) {
+            self.sck.set_high()
+        } else {
+            self.sck.set_low()
+        }
+    }
+
+    /// Bits of a word in the order they are shifted, as masks
+    fn bit_masks<W: Word>(&self) -> impl Iterator<Item = u32> {
+        let bits = u32::from(W::BITS);
+        let order = self.bit_order;
+        (0..bits).map(move |i| match order {
+            BitOrder::MsbFirst => 1 << (bits - 1 - i),
+            BitOrder::LsbFirst => 1 << i,
+        })
+    }
+
+    // Each bit is shifted in three steps separated by half a clock period.
+
+    fn begin_bit(&mut self, bit: bool) -> Result<(), E> {
+        if self.mode.phase == Phase::CaptureOnSecondTransition {
+            self.set_sck(true)?;
+        }
+        if bit {
+            self.mosi.set_high()
+        } else {
+            self.mosi.set_low()
+        }
+    }
+
+    fn sample_bit(&mut self) -> Result<bool, E> {
+        let active = self.mode.phase == Phase::CaptureOnFirstTransition;
+        self.set_sck(active)?;
+        self.miso.is_high()
+    }
+
+    fn end_bit(&mut self) -> Result<(), E> {
+        if self.mode.phase == Phase::CaptureOnFirstTransition {
+            self.set_sck(false)?;
+        }
+        Ok(())
+    }
+
+    fn apply_config(&mut self, config: &Config) -> Result<(), E> {
+        self.bit_order = config.bit_order;
+        self.half_period_us = half_period_us(config.max_frequency_hz);
+        self.set_mode(config.mode)
+    }
+}
+
+/// Shortest half clock period not exceeding `max_frequency_hz`
+fn half_period_us(max_frequency_hz: u32) -> u32 {
+    let max_frequency_hz = max_frequency_hz.max(1);
+    let half_period_us = 500_000 / max_frequency_hz;
+    if 500_000 % max_frequency_hz == 0 {
+        half_period_us
+    } else {
+        half_period_us + 1
+    }
+}
+
+impl<SCK, MOSI, MISO, D, E> Bitbang<SCK, MOSI, MISO, D>
+where
+    SCK: OutputPin<Error = E>,
+    MOSI: OutputPin<Error = E>,
+    MISO: InputPin<Error = E>,
+    D: DelayUs,
+{
+    fn wait(&mut self) -> Result<(), Error<E, D::Error>> {
+        self.delay
+            .delay_us(self.half_period_us)
+            .map_err(Error::Delay)
+    }
+
+    fn shift<W: Word>(&mut self, word: W) -> Result<W, Error<E, D::Error>> {
+        let word = word.to_bits();
+        let mut read = 0;
+        for mask in self.bit_masks::<W>() {
+            self.begin_bit(word & mask != 0).map_err(Error::Pin)?;
+            self.wait()?;
+            if self.sample_bit().map_err(Error::Pin)? {
+                read |= mask;
+            }
+            self.wait()?;
+            self.end_bit().map_err(Error::Pin)?;
+        }
+        Ok(W::from_bits(read))
+    }
+}
+
+impl<SCK, MOSI, MISO, D, E> SpiBusFlush for Bitbang<SCK, MOSI, MISO, D>
+where
+    SCK: OutputPin<Error = E>,
+    MOSI: OutputPin<Error = E>,
+    MISO: InputPin<Error = E>,
+    D: DelayUs,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E, D::Error>;
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<SCK, MOSI, MISO, D, E, W> SpiBusRead<W> for Bitbang<SCK, MOSI, MISO, D>
+where
+    SCK: OutputPin<Error = E>,
+    MOSI: OutputPin<Error = E>,
+    MISO: InputPin<Error = E>,
+    D: DelayUs,
+    E: core::fmt::Debug,
+    W: Word,
+{
+    /// Writes zero words
+    fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        for word in words {
+            *word = self.shift(W::from_bits(0))?;
+        }
+        Ok(())
+    }
+}
+
+impl<SCK, MOSI, MISO, D, E, W> SpiBusWrite<W> for Bitbang<SCK, MOSI, MISO, D>
+where
+    SCK: OutputPin<Error = E>,
+    MOSI: OutputPin<Error = E>,
+    MISO: InputPin<Error = E>,
+    D: DelayUs,
+    E: core::fmt::Debug,
+    W: Word,
+{
+    fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        for &word in words {
+            self.shift(word)?;
+        }
+        Ok(())
+    }
+}
+
+impl<SCK, MOSI, MISO, D, E, W> SpiBus<W> for Bitbang<SCK, MOSI, MISO, D>
+where
+    SCK: OutputPin<Error = E>,
+    MOSI: OutputPin<Error = E>,
+    MISO: InputPin<Error = E>,
+    D: DelayUs,
+    E: core::fmt::Debug,
+    W: Word,
+{
+    /// Words sent after `write` has been exhausted are zero
+    fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+        for i in 0..read.len().max(write.len()) {
+            let word = write.get(i).copied().unwrap_or_else(|| W::from_bits(0));
+            let word = self.shift(word)?;
+            if let Some(read) = read.get_mut(i) {
+                *read = word;
+            }
+        }
+        Ok(())
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        for word in words {
+            *word = self.shift(*word)?;
+        }
+        Ok(())
+    }
+}
+
+impl<SCK, MOSI, MISO, D, E> SetBitOrder for Bitbang<SCK, MOSI, MISO, D>
+where
+    SCK: OutputPin<Error = E>,
+    MOSI: OutputPin<Error = E>,
+    MISO: InputPin<Error = E>,
+    D: DelayUs,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E, D::Error>;
+
+    fn set_bit_order(&mut self, bit_order: BitOrder) -> Result<(), Self::Error> {
+        self.bit_order = bit_order;
+        Ok(())
+    }
+}
+
+/// Sets the mode, bit order and the half clock period matching the maximum frequency
+///
+/// Only the Motorola frame format is supported.
+impl<SCK, MOSI, MISO, D, E> SetConfig for Bitbang<SCK, MOSI, MISO, D>
+where
+    SCK: OutputPin<Error = E>,
+    MOSI: OutputPin<Error = E>,
+    MISO: InputPin<Error = E>,
+    D: DelayUs,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E, D::Error>;
+
+    fn set_config(&mut self, config: &Config) -> Result<(), Self::Error> {
+        if config.frame_format != FrameFormat::Motorola {
+            return Err(Error::Unsupported);
+        }
+        self.apply_config(config).map_err(Error::Pin)
+    }
+}
+
+#[cfg(feature = "async")]
+mod asynch {
+    use super::{Bitbang, Error};
+    use crate::delay::asynch::DelayUs;
+    use crate::digital::blocking::{InputPin, OutputPin};
+    use crate::spi::asynch::{SpiBus, SpiBusFlush, SpiBusRead, SpiBusWrite};
+    use crate::spi::Word;
+
+    impl<SCK, MOSI, MISO, D, E> Bitbang<SCK, MOSI, MISO, D>
+    where
+        SCK: OutputPin<Error = E>,
+        MOSI: OutputPin<Error = E>,
+        MISO: InputPin<Error = E>,
+        D: DelayUs,
+    {
+        async fn wait_async(&mut self) -> Result<(), Error<E, D::Error>> {
+            let half_period_us = self.half_period_us;
+            self.delay
+                .delay_us(half_period_us)
+                .await
+                .map_err(Error::Delay)
+        }
+
+        async fn shift_async<W: Word>(&mut self, word: W) -> Result<W, Error<E, D::Error>> {
+            let word = word.to_bits();
+            let mut read = 0;
+            for mask in self.bit_masks::<W>() {
+                self.begin_bit(word & mask != 0).map_err(Error::Pin)?;
+                self.wait_async().await?;
+                if self.sample_bit().map_err(Error::Pin)? {
+                    read |= mask;
+                }
+                self.wait_async().await?;
+                self.end_bit().map_err(Error::Pin)?;
+            }
+            Ok(W::from_bits(read))
+        }
+    }
+
+    impl<SCK, MOSI, MISO, D, E> SpiBusFlush for Bitbang<SCK, MOSI, MISO, D>
+    where
+        SCK: OutputPin<Error = E>,
+        MOSI: OutputPin<Error = E>,
+        MISO: InputPin<Error = E>,
+        D: DelayUs,
+        E: core::fmt::Debug,
+    {
+        type Error = Error<E, D::Error>;
+
+        async fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    impl<SCK, MOSI, MISO, D, E, W> SpiBusRead<W> for Bitbang<SCK, MOSI, MISO, D>
+    where
+        SCK: OutputPin<Error = E>,
+        MOSI: OutputPin<Error = E>,
+        MISO: InputPin<Error = E>,
+        D: DelayUs,
+        E: core::fmt::Debug,
+        W: Word,
+    {
+        /// Writes zero words
+        async fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+            for word in words {
+                *word = self.shift_async(W::from_bits(0)).await?;
+            }
+            Ok(())
+        }
+    }
+
+    impl<SCK, MOSI, MISO, D, E, W> SpiBusWrite<W> for Bitbang<SCK, MOSI, MISO, D>
+    where
+        SCK: OutputPin<Error = E>,
+        MOSI: OutputPin<Error = E>,
+        MISO: InputPin<Error = E>,
+        D: DelayUs,
+        E: core::fmt::Debug,
+        W: Word,
+    {
+        async fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+            for &word in words {
+                self.shift_async(word).await?;
+            }
+            Ok(())
+        }
+    }
+
+    impl<SCK, MOSI, MISO, D, E, W> SpiBus<W> for Bitbang<SCK, MOSI, MISO, D>
+    where
+        SCK: OutputPin<Error = E>,
+        MOSI: OutputPin<Error = E>,
+        MISO: InputPin<Error = E>,
+        D: DelayUs,
+        E: core::fmt::Debug,
+        W: Word,
+    {
+        /// Words sent after `write` has been exhausted are zero
+        async fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+            for i in 0..read.len().max(write.len()) {
+                let word = write.get(i).copied().unwrap_or_else(|| W::from_bits(0));
+                let word = self.shift_async(word).await?;
+                if let Some(read) = read.get_mut(i) {
+                    *read = word;
+                }
+            }
+            Ok(())
+        }
+
+        async fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+            for word in words {
+                *word = self.shift_async(*word).await?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spi::{MODE_1, MODE_3, U9};
+    use core::cell::Cell;
+    use core::convert::Infallible;
+
+    /// Wire connecting the pins, looping MOSI back to MISO and counting SCK edges
+    #[derive(Default)]
+    struct Wire {
+        sck: Cell<bool>,
+        edges: Cell<u32>,
+        data: Cell<bool>,
+    }
+
+    struct Sck<'a>(&'a Wire);
+    struct Mosi<'a>(&'a Wire);
+    struct Miso<'a>(&'a Wire);
+    struct NoWait;
+
+    impl OutputPin for Sck<'_> {
+        type Error = Infallible;
+
+        fn set_low(&mut self) -> Result<(), Infallible> {
+            if self.0.sck.replace(false) {
+                self.0.edges.set(self.0.edges.get() + 1);
+            }
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Infallible> {
+            if !self.0.sck.replace(true) {
+                self.0.edges.set(self.0.edges.get() + 1);
+            }
+            Ok(())
+        }
+    }
+
+    impl OutputPin for Mosi<'_> {
+        type Error = Infallible;
+
+        fn set_low(&mut self) -> Result<(), Infallible> {
+            self.0.data.set(false);
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Infallible> {
+            self.0.data.set(true);
+            Ok(())
+        }
+    }
+
+    impl InputPin for Miso<'_> {
+        type Error = Infallible;
+
+        fn is_high(&self) -> Result<bool, Infallible> {
+            Ok(self.0.data.get())
+        }
+
+        fn is_low(&self) -> Result<bool, Infallible> {
+            Ok(!self.0.data.get())
+        }
+    }
+
+    impl DelayUs for NoWait {
+        type Error = Infallible;
+
+        fn delay_us(&mut self, _us: u32) -> Result<(), Infallible> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl crate::delay::asynch::DelayUs for NoWait {
+        type Error = Infallible;
+
+        async fn delay_us(&mut self, _us: u32) -> Result<(), Infallible> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn loops_back_in_all_modes() {
+        let wire = Wire::default();
+        for &mode in &[MODE_0, MODE_1] {
+            let mut bus = Bitbang::new(Sck(&wire), Mosi(&wire), Miso(&wire), NoWait)
+                .with_mode(mode)
+                .unwrap();
+            let mut words = [0xA5u8, 0x3C];
+            bus.transfer_in_place(&mut words).unwrap();
+            assert_eq!(words, [0xA5, 0x3C]);
+            assert!(!wire.sck.get());
+        }
+        assert_eq!(wire.edges.replace(0), 64);
+
+        wire.sck.set(true);
+        let mut bus = Bitbang::new(Sck(&wire), Mosi(&wire), Miso(&wire), NoWait)
+            .with_mode(MODE_3)
+            .unwrap()
+            .with_bit_order(BitOrder::LsbFirst);
+        let mut words = [U9::new(0x1A5).unwrap()];
+        bus.transfer_in_place(&mut words).unwrap();
+        assert_eq!(words[0].get(), 0x1A5);
+        assert!(wire.sck.get());
+        assert_eq!(wire.edges.get(), 18);
+    }
+
+    #[test]
+    fn half_period_from_frequency() {
+        assert_eq!(half_period_us(100_000), 5);
+        assert_eq!(half_period_us(300_000), 2);
+        assert_eq!(half_period_us(10_000_000), 1);
+        assert_eq!(half_period_us(0), 500_000);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_bus() {
+        use crate::adapter::{BlockOn, Spin};
+        use crate::spi::asynch::SpiBus;
+
+        let wire = Wire::default();
+        let mut bus = Bitbang::new(Sck(&wire), Mosi(&wire), Miso(&wire), NoWait);
+        let mut read = [0u16; 2];
+        Spin.block_on(SpiBus::transfer(&mut bus, &mut read, &[0x8001]))
+            .unwrap();
+        assert_eq!(read, [0x8001, 0]);
+        assert_eq!(wire.edges.get(), 64);
+    }
+}
@@ -1,10 +1,155 @@
 //! Blocking SPI API
+//!
+//! # Bus vs Device
+//!
+//! SPI allows sharing a single bus between many SPI devices. The SCK, MOSI and MISO lines are
+//! wired in parallel to all the devices, and each device gets a dedicated chip-select (CS) line
+//! from the MCU, like this:
+//!
+//! ``` text
+//!      MCU ──SCK/MOSI/MISO──┬──────────┬──────────┐
+//!       │                   │          │          │
+//!       ├──CS1──────── Device 1        │          │
+//!       ├──CS2─────────────────── Device 2        │
+//!       └──CS3────────────────────────────── Device 3
+//! ```
+//!
+//! CS is usually active-low. When CS is high (not asserted), SPI devices ignore all incoming data
+//! and don't drive MISO. When CS is low (asserted), the device is active: reacts to incoming data
+//! on MOSI and drives MISO with the response data. By asserting one CS or another, the MCU can
+//! choose to which device it wants to talk.
+//!
+//! This module therefore has two kinds of traits:
+//!
+//! - The bus traits ([`SpiBus`], [`SpiBusRead`], [`SpiBusWrite`] and [`SpiBusFlush`]) represent
+//!   exclusive ownership over the whole SPI bus. They only transfer words; they do not know
+//!   about CS. HALs implement them for their SPI peripherals.
+//! - [`SpiDevice`] represents ownership over a single device selected with a CS line. Every
+//!   [`transaction`](SpiDevice::transaction) asserts CS, executes the operations, waits for the
+//!   bus to be flushed and deasserts CS, so the transactions of different devices sharing the
+//!   bus can never interleave.
+//!
+//! Drivers for SPI devices should take a [`SpiDevice`], never a bus: only then can the
+//! application decide whether the device gets the bus to itself or shares it with others.
+//!
+//! # Flushing
+//!
+//! To improve performance, bus implementations are allowed to return before an operation is
+//! complete, e.g. while the last words are still in a FIFO or being shifted out.
+//! [`SpiBusFlush::flush`] waits until the bus is idle. It must be called before deasserting CS,
+//! which [`SpiDevice`] implementations do at the end of every transaction.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::spi::blocking::{Operation, SpiDevice};
+//!
+//! /// Driver for a flash chip
+//! pub struct Flash<SPI> {
+//!     spi: SPI,
+//! }
+//!
+//! impl<SPI: SpiDevice> Flash<SPI> {
+//!     /// Reads `buffer.len()` bytes starting at `address`
+//!     pub fn read(&mut self, address: u32, buffer: &mut [u8]) -> Result<(), SPI::Error> {
+//!         let [_, a2, a1, a0] = address.to_be_bytes();
+//!         // CS stays asserted between the command and the data
+//!         self.spi.transaction(&mut [
+//!             Operation::Write(&[0x03, a2, a1, a0]),
+//!             Operation::Read(buffer),
+//!         ])
+//!     }
+//! }
+//! ```
 
-/// Blocking transfer with separate buffers
-pub trait Transfer<W = u8> {
+use super::Word;
+
+/// Operation of a [`SpiDevice`] transaction
+///
+/// This allows composition of SPI operations into a single transaction, executed with CS
+/// asserted throughout.
+#[derive(Debug, PartialEq)]
+pub enum Operation<'a, W: Word = u8> {
+    /// Read data into the provided buffer.
+    ///
+    /// Equivalent to [`SpiBusRead::read`].
+    Read(&'a mut [W]),
+    /// Write data from the provided buffer, discarding read data
+    ///
+    /// Equivalent to [`SpiBusWrite::write`].
+    Write(&'a [W]),
+    /// Write data out while reading data into the provided buffer
+    ///
+    /// Equivalent to [`SpiBus::transfer`].
+    Transfer(&'a mut [W], &'a [W]),
+    /// Write data out while reading data into the provided buffer, in place
+    ///
+    /// Equivalent to [`SpiBus::transfer_in_place`].
+    TransferInPlace(&'a mut [W]),
+    /// Wait at least the given number of microseconds with CS asserted
+    ///
+    /// The bus is flushed before the delay starts, so the delay is measured from the end of
+    /// the previous operation. Useful for devices which need time between a command and
+    /// its response.
+    DelayUs(u32),
+}
+
+/// Flush support for SPI bus
+pub trait SpiBusFlush {
     /// Error type
     type Error: crate::spi::Error;
 
+    /// Waits until all operations have completed and the bus is idle.
+    ///
+    /// See the [module-level documentation](self) for important usage information.
+    fn flush(&mut self) -> Result<(), Self::Error>;
+}
+
+impl<T: SpiBusFlush> SpiBusFlush for &mut T {
+    type Error = T::Error;
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        T::flush(self)
+    }
+}
+
+/// Read-only SPI bus
+pub trait SpiBusRead<W: Word = u8>: SpiBusFlush {
+    /// Reads `words` from the slave.
+    ///
+    /// The word value sent on MOSI during reading is implementation-defined,
+    /// typically `0x00`, `0xFF`, or configurable.
+    ///
+    /// Implementations are allowed to return before the operation is
+    /// complete. See the [module-level documentation](self) for details.
+    fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error>;
+}
+
+impl<T: SpiBusRead<W>, W: Word> SpiBusRead<W> for &mut T {
+    fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        T::read(self, words)
+    }
+}
+
+/// Write-only SPI bus
+pub trait SpiBusWrite<W: Word = u8>: SpiBusFlush {
+    /// Writes `words` to the slave, ignoring all the incoming words
+    ///
+    /// Implementations are allowed to return before the operation is
+    /// complete. See the [module-level documentation](self) for details.
+    fn write(&mut self, words: &[W]) -> Result<(), Self::Error>;
+}
+
+impl<T: SpiBusWrite<W>, W: Word> SpiBusWrite<W> for &mut T {
+    fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        T::write(self, words)
+    }
+}
+
+/// Read-write SPI bus
+///
+/// The bus does not manage chip-select lines, see the [module-level documentation](self).
+pub trait SpiBus<W: Word = u8>: SpiBusRead<W> + SpiBusWrite<W> {
     /// Writes and reads simultaneously. `write` is written to the slave on MOSI and
     /// words received on MISO are stored in `read`.
     ///
@@ -13,124 +158,219 @@ pub trait Transfer<W = u8> {
     /// incoming words after `read` has been filled will be discarded. If `write` is shorter,
     /// the value of words sent in MOSI after all `write` has been sent is implementation-defined,
     /// typically `0x00`, `0xFF`, or configurable.
+    ///
+    /// Implementations are allowed to return before the operation is
+    /// complete. See the [module-level documentation](self) for details.
     fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error>;
-}
 
-impl<T: Transfer<W>, W> Transfer<W> for &mut T {
-    type Error = T::Error;
+    /// Writes and reads simultaneously. The contents of `words` are
+    /// written to the slave, and the received words are stored into the same
+    /// `words` buffer, overwriting it.
+    ///
+    /// Implementations are allowed to return before the operation is
+    /// complete. See the [module-level documentation](self) for details.
+    fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error>;
+}
 
+impl<T: SpiBus<W>, W: Word> SpiBus<W> for &mut T {
     fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
         T::transfer(self, read, write)
     }
+
+    fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        T::transfer_in_place(self, words)
+    }
 }
 
-/// Blocking transfer with single buffer (in-place)
-pub trait TransferInplace<W = u8> {
+/// SPI device with a managed chip-select line
+///
+/// See the [module-level documentation](self) for the difference to the bus traits.
+pub trait SpiDevice<W: Word = u8> {
     /// Error type
     type Error: crate::spi::Error;
 
-    /// Writes and reads simultaneously. The contents of `words` are
-    /// written to the slave, and the received words are stored into the same
-    /// `words` buffer, overwriting it.
-    fn transfer_inplace(&mut self, words: &mut [W]) -> Result<(), Self::Error>;
-}
+    /// Executes the provided operations with CS asserted
+    ///
+    /// Transaction contract:
+    /// - Locks the bus, if it is shared, so that no other device can use it.
+    /// - Asserts CS.
+    /// - Executes the operations in order, without deasserting CS between them. For
+    ///   [`Operation::DelayUs`], flushes the bus and then waits.
+    /// - Waits for the bus to be flushed.
+    /// - Deasserts CS, even if an operation failed.
+    /// - Unlocks the bus.
+    fn transaction<'a>(&mut self, operations: &mut [Operation<'a, W>]) -> Result<(), Self::Error>;
 
-impl<T: TransferInplace<W>, W> TransferInplace<W> for &mut T {
-    type Error = T::Error;
+    /// Reads `words` in a single transaction
+    fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        self.transaction(&mut [Operation::Read(words)])
+    }
+
+    /// Writes `words` in a single transaction
+    fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        self.transaction(&mut [Operation::Write(words)])
+    }
 
-    fn transfer_inplace(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
-        T::transfer_inplace(self, words)
+    /// Writes `write` and reads into `read` simultaneously in a single transaction
+    ///
+    /// See [`SpiBus::transfer`] for the handling of buffers of different lengths.
+    fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+        self.transaction(&mut [Operation::Transfer(read, write)])
+    }
+
+    /// Writes `words` and reads into it simultaneously in a single transaction
+    fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        self.transaction(&mut [Operation::TransferInPlace(words)])
     }
 }
 
-/// Blocking read
-pub trait Read<W = u8> {
+/// Half-duplex (3-wire) SPI bus
+///
+/// Marker for buses whose MOSI and MISO share a single data line, so words can be sent or
+/// received, but not both at once. Half-duplex buses implement [`SpiBusRead`],
+/// [`SpiBusWrite`] and this trait, but never [`SpiBus`].
+///
+/// Devices on such a bus implement [`SpiDeviceHalfDuplex`] instead of [`SpiDevice`], so a
+/// driver which needs full-duplex transfers cannot be used with them by mistake.
+pub trait HalfDuplex: SpiBusFlush {}
+
+impl<T: HalfDuplex> HalfDuplex for &mut T {}
+
+/// Half-duplex (3-wire) SPI device with a managed chip-select line
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::spi::blocking::SpiDeviceHalfDuplex;
+///
+/// /// Reads the WHO_AM_I register of a LIS3-style sensor in 3-wire mode
+/// fn who_am_i<SPI: SpiDeviceHalfDuplex>(spi: &mut SPI) -> Result<u8, SPI::Error> {
+///     let mut id = [0];
+///     spi.write_then_read(&[0x80 | 0x0F], &mut id)?;
+///     Ok(id[0])
+/// }
+/// ```
+pub trait SpiDeviceHalfDuplex<W: Word = u8> {
     /// Error type
     type Error: crate::spi::Error;
 
-    /// Reads `words` from the slave.
+    /// Writes `write`, turns the data line around and reads into `read`, in a single
+    /// transaction
     ///
-    /// The word value sent on MOSI during reading is implementation-defined,
-    /// typically `0x00`, `0xFF`, or configurable.
-    fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error>;
-}
+    /// CS stays asserted from the first written word to the last read word. Either buffer
+    /// may be empty.
+    fn write_then_read(&mut self, write: &[W], read: &mut [W]) -> Result<(), Self::Error>;
 
-impl<T: Read<W>, W> Read<W> for &mut T {
-    type Error = T::Error;
+    /// Writes `words` in a single transaction
+    fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        self.write_then_read(words, &mut [])
+    }
 
+    /// Reads `words` in a single transaction
     fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
-        T::read(self, words)
+        self.write_then_read(&[], words)
     }
 }
 
-/// Blocking write
-pub trait Write<W = u8> {
-    /// Error type
-    type Error: crate::spi::Error;
-
-    /// Writes `words` to the slave, ignoring all the incoming words
-    fn write(&mut self, words: &[W]) -> Result<(), Self::Error>;
-}
-
-impl<T: Write<W>, W> Write<W> for &mut T {
+impl<T: SpiDeviceHalfDuplex<W>, W: Word> SpiDeviceHalfDuplex<W> for &mut T {
     type Error = T::Error;
 
+    fn write_then_read(&mut self, write: &[W], read: &mut [W]) -> Result<(), Self::Error> {
+        T::write_then_read(self, write, read)
+    }
+
     fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
         T::write(self, words)
     }
+
+    fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        T::read(self, words)
+    }
 }
 
-/// Blocking write (iterator version)
-pub trait WriteIter<W = u8> {
+/// SPI device giving access to its bus within a transaction
+///
+/// For protocols where the operations of a transaction depend on earlier responses within
+/// the same CS window, e.g. a length header followed by a payload of that length, which
+/// cannot be expressed as a fixed list of [`Operation`]s.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::spi::blocking::{SpiBus, SpiBusRead, SpiBusWrite, SpiDeviceBus};
+///
+/// /// Reads a length-prefixed frame, returning its length
+/// fn read_frame<SPI>(spi: &mut SPI, frame: &mut [u8]) -> Result<usize, SPI::Error>
+/// where
+///     SPI: SpiDeviceBus,
+///     SPI::Bus: SpiBus,
+/// {
+///     spi.bus_transaction(|bus| {
+///         bus.write(&[0x0B])?;
+///         let mut len = [0];
+///         bus.read(&mut len)?;
+///         let len = (len[0] as usize).min(frame.len());
+///         bus.read(&mut frame[..len])?;
+///         Ok(len)
+///     })
+/// }
+/// ```
+pub trait SpiDeviceBus {
     /// Error type
     type Error: crate::spi::Error;
 
-    /// Writes `words` to the slave, ignoring all the incoming words
-    fn write_iter<WI>(&mut self, words: WI) -> Result<(), Self::Error>
-    where
-        WI: IntoIterator<Item = W>;
+    /// Bus type
+    type Bus: SpiBusFlush;
+
+    /// Calls `f` with exclusive access to the bus and CS asserted
+    ///
+    /// Transaction contract:
+    /// - Locks the bus, if it is shared, so that no other device can use it.
+    /// - Asserts CS.
+    /// - Calls `f` with the bus.
+    /// - Flushes the bus.
+    /// - Deasserts CS, even if `f` failed.
+    /// - Unlocks the bus.
+    ///
+    /// `f` must not deassert CS, e.g. through another device on the same bus.
+    fn bus_transaction<R>(
+        &mut self,
+        f: impl FnOnce(&mut Self::Bus) -> Result<R, <Self::Bus as SpiBusFlush>::Error>,
+    ) -> Result<R, Self::Error>;
 }
 
-impl<T: WriteIter<W>, W> WriteIter<W> for &mut T {
+impl<T: SpiDeviceBus> SpiDeviceBus for &mut T {
     type Error = T::Error;
+    type Bus = T::Bus;
 
-    fn write_iter<WI>(&mut self, words: WI) -> Result<(), Self::Error>
-    where
-        WI: IntoIterator<Item = W>,
-    {
-        T::write_iter(self, words)
+    fn bus_transaction<R>(
+        &mut self,
+        f: impl FnOnce(&mut Self::Bus) -> Result<R, <Self::Bus as SpiBusFlush>::Error>,
+    ) -> Result<R, Self::Error> {
+        T::bus_transaction(self, f)
     }
 }
 
-/// Operation for transactional SPI trait
-///
-/// This allows composition of SPI operations into a single bus transaction
-#[derive(Debug, PartialEq)]
-pub enum Operation<'a, W: 'static = u8> {
-    /// Read data into the provided buffer.
-    Read(&'a mut [W]),
-    /// Write data from the provided buffer, discarding read data
-    Write(&'a [W]),
-    /// Write data out while reading data into the provided buffer
-    Transfer(&'a mut [W], &'a [W]),
-    /// Write data out while reading data into the provided buffer
-    TransferInplace(&'a mut [W]),
-}
+impl<T: SpiDevice<W>, W: Word> SpiDevice<W> for &mut T {
+    type Error = T::Error;
 
-/// Transactional trait allows multiple actions to be executed
-/// as part of a single SPI transaction
-pub trait Transactional<W: 'static = u8> {
-    /// Associated error type
-    type Error: crate::spi::Error;
+    fn transaction<'a>(&mut self, operations: &mut [Operation<'a, W>]) -> Result<(), Self::Error> {
+        T::transaction(self, operations)
+    }
 
-    /// Execute the provided transactions
-    fn exec<'a>(&mut self, operations: &mut [Operation<'a, W>]) -> Result<(), Self::Error>;
-}
+    fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        T::read(self, words)
+    }
 
-impl<T: Transactional<W>, W: 'static> Transactional<W> for &mut T {
-    type Error = T::Error;
+    fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        T::write(self, words)
+    }
+
+    fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+        T::transfer(self, read, write)
+    }
 
-    fn exec<'a>(&mut self, operations: &mut [Operation<'a, W>]) -> Result<(), Self::Error> {
-        T::exec(self, operations)
+    fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        T::transfer_in_place(self, words)
     }
 }
@@ -0,0 +1,257 @@
+//! Daisy-chained devices
+//!
+//! Shift registers, LED drivers and gate drivers are often chained: the data output of each
+//! device is connected to the data input of the next one, and all share one CS. The whole
+//! chain then behaves like one long shift register, which is written in a single transaction.
+//!
+//! [`DaisyChain`] composes the frames of the individual devices into such a transaction, and
+//! splits the words shifted out of the chain back into per-device frames. Frames are indexed
+//! by the position of the device in the chain, starting with the device whose data input is
+//! connected to MOSI. The frames may have different lengths, e.g. for chains of different
+//! devices.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::spi::blocking::{SpiBus, SpiDeviceBus};
+//! use embedded_hal::spi::daisy_chain::{DaisyChain, Error};
+//!
+//! /// Sets the outputs of three chained 16-bit LED drivers, returning their fault flags
+//! fn set_leds<SPI>(
+//!     chain: &mut DaisyChain<SPI>,
+//!     leds: [u16; 3],
+//! ) -> Result<[u16; 3], Error<SPI::Error>>
+//! where
+//!     SPI: SpiDeviceBus,
+//!     SPI::Bus: SpiBus,
+//! {
+//!     let mut frames = [leds[0].to_be_bytes(), leds[1].to_be_bytes(), leds[2].to_be_bytes()];
+//!     {
+//!         let [first, second, third] = &mut frames;
+//!         chain.transfer_in_place(&mut [first, second, third])?;
+//!     }
+//!     Ok([
+//!         u16::from_be_bytes(frames[0]),
+//!         u16::from_be_bytes(frames[1]),
+//!         u16::from_be_bytes(frames[2]),
+//!     ])
+//! }
+//! ```
+
+use super::blocking::{SpiBus, SpiBusWrite, SpiDeviceBus};
+use super::{ErrorKind, Word};
+
+/// Daisy chain error
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error<E> {
+    /// The device returned an error
+    Spi(E),
+    /// The number of frames differs from the number of devices in the chain
+    FrameCount,
+}
+
+impl<E: super::Error> super::Error for Error<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Spi(e) => e.kind(),
+            Self::FrameCount => ErrorKind::Other,
+        }
+    }
+}
+
+/// Chain of devices sharing one CS
+///
+/// The number of devices is given when the chain is created. Transactions with a different
+/// number of frames fail with [`Error::FrameCount`] before anything is sent, so a driver
+/// can't shift its frames into the wrong devices.
+#[derive(Debug)]
+pub struct DaisyChain<SPI> {
+    device: SPI,
+    len: usize,
+}
+
+impl<SPI> DaisyChain<SPI> {
+    /// Creates a chain of `len` devices selected by `device`
+    pub fn new(device: SPI, len: usize) -> Self {
+        DaisyChain { device, len }
+    }
+
+    /// Returns the number of devices in the chain
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if there are no devices in the chain
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Releases the device
+    pub fn release(self) -> SPI {
+        self.device
+    }
+
+    fn check<E>(&self, frames: usize) -> Result<(), Error<E>> {
+        if frames == self.len {
+            Ok(())
+        } else {
+            Err(Error::FrameCount)
+        }
+    }
+}
+
+impl<SPI: SpiDeviceBus> DaisyChain<SPI> {
+    /// Writes one frame to every device in a single transaction
+    pub fn write<W: Word>(&mut self, frames: &[&[W]]) -> Result<(), Error<SPI::Error>>
+    where
+        SPI::Bus: SpiBusWrite<W>,
+    {
+        self.check(frames.len())?;
+        self.device
+            .bus_transaction(|bus| {
+                // The frame written first is shifted furthest down the chain.
+                for frame in frames.iter().rev() {
+                    bus.write(frame)?;
+                }
+                Ok(())
+            })
+            .map_err(Error::Spi)
+    }
+
+    /// Writes one frame to every device in a single transaction, replacing each frame with the
+    /// words shifted out of its device
+    ///
+    /// The words shifted out are the contents of the device's shift register before the
+    /// transaction, which is usually the response to the previous frame.
+    pub fn transfer_in_place<W: Word>(
+        &mut self,
+        frames: &mut [&mut [W]],
+    ) -> Result<(), Error<SPI::Error>>
+    where
+        SPI::Bus: SpiBus<W>,
+    {
+        self.check(frames.len())?;
+        self.device
+            .bus_transaction(|bus| {
+                for frame in frames.iter_mut().rev() {
+                    bus.transfer_in_place(frame)?;
+                }
+                Ok(())
+            })
+            .map_err(Error::Spi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spi::blocking::{SpiBusFlush, SpiBusRead};
+    use crate::spi::ErrorKind;
+
+    /// Two chained 2-byte shift registers, the device nearest to MOSI at the end
+    struct Chain {
+        register: [u8; 4],
+        transactions: usize,
+    }
+
+    impl Chain {
+        fn shift(&mut self, word: u8) -> u8 {
+            let out = self.register[0];
+            self.register.rotate_left(1);
+            self.register[3] = word;
+            out
+        }
+    }
+
+    impl SpiBusFlush for Chain {
+        type Error = ErrorKind;
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    impl SpiBusRead for Chain {
+        fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            for word in words {
+                *word = self.shift(0);
+            }
+            Ok(())
+        }
+    }
+
+    impl SpiBusWrite for Chain {
+        fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+            for &word in words {
+                self.shift(word);
+            }
+            Ok(())
+        }
+    }
+
+    impl SpiBus for Chain {
+        fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+            for i in 0..read.len().max(write.len()) {
+                let out = self.shift(write.get(i).copied().unwrap_or(0));
+                if let Some(word) = read.get_mut(i) {
+                    *word = out;
+                }
+            }
+            Ok(())
+        }
+
+        fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            for word in words {
+                *word = self.shift(*word);
+            }
+            Ok(())
+        }
+    }
+
+    impl SpiDeviceBus for Chain {
+        type Error = ErrorKind;
+        type Bus = Self;
+
+        fn bus_transaction<R>(
+            &mut self,
+            f: impl FnOnce(&mut Self) -> Result<R, ErrorKind>,
+        ) -> Result<R, ErrorKind> {
+            self.transactions += 1;
+            f(self)
+        }
+    }
+
+    #[test]
+    fn demultiplexes_frames() {
+        let mut chain = DaisyChain::new(
+            Chain {
+                register: [1, 2, 3, 4],
+                transactions: 0,
+            },
+            2,
+        );
+        let mut first = [0x30, 0x40];
+        let mut second = [0x10, 0x20];
+        chain
+            .transfer_in_place(&mut [&mut first, &mut second])
+            .unwrap();
+        assert_eq!((first, second), ([3, 4], [1, 2]));
+        chain.write(&[&[5, 6], &[7, 8]]).unwrap();
+        let chain = chain.release();
+        assert_eq!(chain.register, [7, 8, 5, 6]);
+        assert_eq!(chain.transactions, 2);
+    }
+
+    #[test]
+    fn rejects_wrong_frame_count() {
+        let mut chain = DaisyChain::new(
+            Chain {
+                register: [0; 4],
+                transactions: 0,
+            },
+            2,
+        );
+        assert_eq!(chain.write(&[&[0u8; 4]]), Err(Error::FrameCount));
+        assert_eq!(chain.release().transactions, 0);
+    }
+}
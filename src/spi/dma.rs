@@ -0,0 +1,230 @@
+//! SPI with owned DMA buffers
+//!
+//! These traits mirror the [`SpiDevice`](super::blocking::SpiDevice) methods, but take
+//! ownership of the buffers (see [`dma`](crate::dma)). If the transfer fails, the buffers
+//! are returned along with the error. Each call is a single transaction with CS asserted.
+//!
+//! The blocking traits start the transfer and return a [`TransferHandle`] which owns the
+//! buffers until the transfer is complete, so the caller can do other work in the meantime.
+//! The handle doesn't borrow the device. Starting a transfer while a previous one is still in
+//! progress waits for the previous transfer to complete first.
+//!
+//! The async traits in `asynch` return a future which completes with the transfer, so the
+//! core is free while the DMA controller moves the data. They are only available with the
+//! `async` feature.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::dma::TransferHandle;
+//! use embedded_hal::spi::dma::Write;
+//!
+//! /// Display whose frame buffer is sent without copying it
+//! struct Display<SPI> {
+//!     spi: SPI,
+//!     frame: Option<&'static [u16]>,
+//! }
+//!
+//! impl<SPI: Write<&'static [u16]>> Display<SPI> {
+//!     /// Sends the frame buffer, running `render` while it is being sent
+//!     pub fn refresh(&mut self, render: impl FnOnce()) -> Result<(), SPI::Error> {
+//!         let transfer = self.spi.write(self.frame.take().unwrap());
+//!         render();
+//!         let (result, frame) = match transfer.wait() {
+//!             Ok(frame) => (Ok(()), frame),
+//!             Err((e, frame)) => (Err(e), frame),
+//!         };
+//!         self.frame = Some(frame);
+//!         result
+//!     }
+//! }
+//! ```
+
+use super::{Error, Word};
+use crate::dma::{ReadBuffer, TransferHandle, WriteBuffer};
+
+/// Read into an owned buffer
+pub trait Read<B>
+where
+    B: WriteBuffer,
+    B::Word: Word,
+{
+    /// Error type
+    type Error: Error;
+
+    /// Handle of a read in progress
+    type Transfer: TransferHandle<Buffers = B, Error = Self::Error>;
+
+    /// Starts reading enough words to fill `buffer` in a single transaction
+    fn read(&mut self, buffer: B) -> Self::Transfer;
+}
+
+impl<B, T> Read<B> for &mut T
+where
+    B: WriteBuffer,
+    B::Word: Word,
+    T: Read<B>,
+{
+    type Error = T::Error;
+    type Transfer = T::Transfer;
+
+    fn read(&mut self, buffer: B) -> Self::Transfer {
+        T::read(self, buffer)
+    }
+}
+
+/// Write from an owned buffer
+pub trait Write<B>
+where
+    B: ReadBuffer,
+    B::Word: Word,
+{
+    /// Error type
+    type Error: Error;
+
+    /// Handle of a write in progress
+    type Transfer: TransferHandle<Buffers = B, Error = Self::Error>;
+
+    /// Starts writing `words` in a single transaction
+    fn write(&mut self, words: B) -> Self::Transfer;
+}
+
+impl<B, T> Write<B> for &mut T
+where
+    B: ReadBuffer,
+    B::Word: Word,
+    T: Write<B>,
+{
+    type Error = T::Error;
+    type Transfer = T::Transfer;
+
+    fn write(&mut self, words: B) -> Self::Transfer {
+        T::write(self, words)
+    }
+}
+
+/// Full-duplex transfer with owned buffers
+pub trait Transfer<R, W>
+where
+    R: WriteBuffer,
+    R::Word: Word,
+    W: ReadBuffer<Word = R::Word>,
+{
+    /// Error type
+    type Error: Error;
+
+    /// Handle of a transfer in progress
+    type Transfer: TransferHandle<Buffers = (R, W), Error = Self::Error>;
+
+    /// Starts writing `write` and reading into `read` simultaneously in a single transaction
+    ///
+    /// See [`SpiBus::transfer`](super::blocking::SpiBus::transfer) for the handling of
+    /// buffers of different lengths.
+    fn transfer(&mut self, read: R, write: W) -> Self::Transfer;
+}
+
+impl<R, W, T> Transfer<R, W> for &mut T
+where
+    R: WriteBuffer,
+    R::Word: Word,
+    W: ReadBuffer<Word = R::Word>,
+    T: Transfer<R, W>,
+{
+    type Error = T::Error;
+    type Transfer = T::Transfer;
+
+    fn transfer(&mut self, read: R, write: W) -> Self::Transfer {
+        T::transfer(self, read, write)
+    }
+}
+
+/// Async SPI with owned DMA buffers
+///
+/// Same contracts as the blocking traits of the [parent module](super), except that the
+/// transfers complete with the returned futures instead of handles.
+///
+/// Only available with the `async` feature.
+#[cfg(feature = "async")]
+pub mod asynch {
+    use super::{Error, ReadBuffer, Word, WriteBuffer};
+
+    /// Read into an owned buffer
+    pub trait Read<B>
+    where
+        B: WriteBuffer,
+        B::Word: Word,
+    {
+        /// Error type
+        type Error: Error;
+
+        /// Reads enough words to fill `buffer` in a single transaction
+        async fn read(&mut self, buffer: B) -> Result<B, (Self::Error, B)>;
+    }
+
+    impl<B, T> Read<B> for &mut T
+    where
+        B: WriteBuffer,
+        B::Word: Word,
+        T: Read<B>,
+    {
+        type Error = T::Error;
+
+        async fn read(&mut self, buffer: B) -> Result<B, (Self::Error, B)> {
+            T::read(self, buffer).await
+        }
+    }
+
+    /// Write from an owned buffer
+    pub trait Write<B>
+    where
+        B: ReadBuffer,
+        B::Word: Word,
+    {
+        /// Error type
+        type Error: Error;
+
+        /// Writes `words` in a single transaction
+        async fn write(&mut self, words: B) -> Result<B, (Self::Error, B)>;
+    }
+
+    impl<B, T> Write<B> for &mut T
+    where
+        B: ReadBuffer,
+        B::Word: Word,
+        T: Write<B>,
+    {
+        type Error = T::Error;
+
+        async fn write(&mut self, words: B) -> Result<B, (Self::Error, B)> {
+            T::write(self, words).await
+        }
+    }
+
+    /// Full-duplex transfer with owned buffers
+    pub trait Transfer<R, W>
+    where
+        R: WriteBuffer,
+        R::Word: Word,
+        W: ReadBuffer<Word = R::Word>,
+    {
+        /// Error type
+        type Error: Error;
+
+        /// Writes `write` and reads into `read` simultaneously in a single transaction
+        async fn transfer(&mut self, read: R, write: W) -> Result<(R, W), (Self::Error, R, W)>;
+    }
+
+    impl<R, W, T> Transfer<R, W> for &mut T
+    where
+        R: WriteBuffer,
+        R::Word: Word,
+        W: ReadBuffer<Word = R::Word>,
+        T: Transfer<R, W>,
+    {
+        type Error = T::Error;
+
+        async fn transfer(&mut self, read: R, write: W) -> Result<(R, W), (Self::Error, R, W)> {
+            T::transfer(self, read, write).await
+        }
+    }
+}
@@ -0,0 +1,590 @@
+//! SPI device with exclusive access to its bus
+//!
+//! [`ExclusiveDevice`] implements the device traits of [`blocking`](super::blocking) on top
+//! of a bus and an [`OutputPin`] used as chip select, for applications with a single device
+//! on the bus. Buses shared by several devices are handled by the types in
+//! [`shared`](super::shared).
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::digital::blocking::OutputPin;
+//! use embedded_hal::spi::blocking::{SpiBus, SpiDevice};
+//! use embedded_hal::spi::exclusive::{Error, ExclusiveDevice};
+//!
+//! fn read_id<BUS: SpiBus, CS: OutputPin>(
+//!     bus: BUS,
+//!     cs: CS,
+//! ) -> Result<[u8; 3], Error<BUS::Error, CS::Error>> {
+//!     let mut flash = ExclusiveDevice::new(bus, cs).map_err(Error::ChipSelect)?;
+//!     let mut id = [0; 4];
+//!     flash.transfer_in_place(&mut id)?;
+//!     Ok([id[1], id[2], id[3]])
+//! }
+//! ```
+
+use core::slice;
+
+use super::blocking::{
+    HalfDuplex, Operation, SpiBus, SpiBusFlush, SpiBusRead, SpiBusWrite, SpiDevice, SpiDeviceBus,
+    SpiDeviceHalfDuplex,
+};
+use super::{Config, CsConfig, CsPolarity, ErrorKind, SetConfig, Word};
+use crate::delay::blocking::DelayUs;
+use crate::digital::blocking::OutputPin;
+
+/// Delay used by [`ExclusiveDevice`] unless a delay is configured
+///
+/// Non-zero delays fail with [`DelayUnsupported`], as ignoring them could violate the timing
+/// requirements of the device. A transaction containing an [`Operation::DelayUs`] with a
+/// non-zero delay thus fails with [`Error::Delay`].
+#[derive(Debug, Default, Copy, Clone)]
+pub struct NoDelay;
+
+/// Error of [`NoDelay`] for non-zero delays
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DelayUnsupported;
+
+impl DelayUs for NoDelay {
+    type Error = DelayUnsupported;
+
+    fn delay_us(&mut self, us: u32) -> Result<(), Self::Error> {
+        if us == 0 {
+            Ok(())
+        } else {
+            Err(DelayUnsupported)
+        }
+    }
+}
+
+/// Error of an SPI device
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error<BUS, CS, D = DelayUnsupported> {
+    /// The bus returned an error
+    Spi(BUS),
+    /// The chip select pin returned an error
+    ChipSelect(CS),
+    /// The delay returned an error
+    Delay(D),
+}
+
+impl<BUS, CS, D> super::Error for Error<BUS, CS, D>
+where
+    BUS: super::Error,
+    CS: core::fmt::Debug,
+    D: core::fmt::Debug,
+{
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Spi(e) => e.kind(),
+            Self::ChipSelect(_) => ErrorKind::ChipSelectFault,
+            Self::Delay(_) => ErrorKind::Other,
+        }
+    }
+}
+
+/// SPI device owning its bus
+#[derive(Debug)]
+pub struct ExclusiveDevice<BUS, CS, D = NoDelay> {
+    bus: BUS,
+    cs: CS,
+    delay: D,
+    cs_config: CsConfig,
+}
+
+impl<BUS, CS: OutputPin> ExclusiveDevice<BUS, CS> {
+    /// Creates a new device, deasserting (driving high) `cs`
+    ///
+    /// Transactions containing a non-zero [`Operation::DelayUs`] fail with [`Error::Delay`]
+    /// unless a delay is set, see [`with_delay`](ExclusiveDevice::with_delay).
+    pub fn new(bus: BUS, cs: CS) -> Result<Self, CS::Error> {
+        Self::new_with_cs_config(bus, cs, CsConfig::new(), NoDelay)
+    }
+}
+
+impl<BUS, CS: OutputPin, D: DelayUs> ExclusiveDevice<BUS, CS, D> {
+    /// Creates a new device using `cs_config` for the chip select pin, deasserting `cs`
+    /// according to its polarity
+    ///
+    /// `delay` waits for the setup, hold, word and frame delays of `cs_config` and for the
+    /// [`Operation::DelayUs`] operations of transactions.
+    pub fn new_with_cs_config(
+        bus: BUS,
+        mut cs: CS,
+        cs_config: CsConfig,
+        delay: D,
+    ) -> Result<Self, CS::Error> {
+        set_cs(&mut cs, &cs_config, false)?;
+        Ok(ExclusiveDevice {
+            bus,
+            cs,
+            delay,
+            cs_config,
+        })
+    }
+}
+
+impl<BUS, CS, D> ExclusiveDevice<BUS, CS, D> {
+    /// Uses `delay` for the [`Operation::DelayUs`] operations of transactions
+    ///
+    /// The delay is also used for the setup and hold times of the [`CsConfig`].
+    pub fn with_delay<D2: DelayUs>(self, delay: D2) -> ExclusiveDevice<BUS, CS, D2> {
+        ExclusiveDevice {
+            bus: self.bus,
+            cs: self.cs,
+            delay,
+            cs_config: self.cs_config,
+        }
+    }
+
+    /// Returns a reference to the bus
+    pub fn bus(&self) -> &BUS {
+        &self.bus
+    }
+
+    /// Returns a mutable reference to the bus
+    ///
+    /// Note that using the bus directly bypasses the chip select handling.
+    pub fn bus_mut(&mut self) -> &mut BUS {
+        &mut self.bus
+    }
+
+    /// Releases the bus, the chip select pin and the delay
+    pub fn release(self) -> (BUS, CS, D) {
+        (self.bus, self.cs, self.delay)
+    }
+}
+
+/// Error of a device on `BUS` with the chip select pin `CS` and the delay `D`
+type DeviceError<BUS, CS, D> =
+    Error<<BUS as SpiBusFlush>::Error, <CS as OutputPin>::Error, <D as DelayUs>::Error>;
+
+/// Asserts or deasserts `cs` according to the polarity of `cs_config`
+pub(super) fn set_cs<CS: OutputPin>(
+    cs: &mut CS,
+    cs_config: &CsConfig,
+    active: bool,
+) -> Result<(), CS::Error> {
+    match (cs_config.polarity, active) {
+        (CsPolarity::ActiveLow, true) | (CsPolarity::ActiveHigh, false) => cs.set_low(),
+        (CsPolarity::ActiveLow, false) | (CsPolarity::ActiveHigh, true) => cs.set_high(),
+    }
+}
+
+/// Waits `us` microseconds, unless it is zero
+fn wait<BUS, CS, D: DelayUs>(delay: &mut D, us: u32) -> Result<(), Error<BUS, CS, D::Error>> {
+    if us > 0 {
+        delay.delay_us(us).map_err(Error::Delay)?;
+    }
+    Ok(())
+}
+
+/// Runs `f` with `cs` asserted, flushing the bus before deasserting it
+///
+/// CS is deasserted even if `f` fails, in which case the error of `f` is returned. The frame
+/// delay is waited for after the deassertion.
+pub(super) fn with_cs<BUS, CS, D, R>(
+    bus: &mut BUS,
+    cs: &mut CS,
+    cs_config: &CsConfig,
+    delay: &mut D,
+    f: impl FnOnce(&mut BUS, &mut D) -> Result<R, DeviceError<BUS, CS, D>>,
+) -> Result<R, DeviceError<BUS, CS, D>>
+where
+    BUS: SpiBusFlush,
+    CS: OutputPin,
+    D: DelayUs,
+{
+    set_cs(cs, cs_config, true).map_err(Error::ChipSelect)?;
+    let result = wait(delay, cs_config.setup_us).and_then(|()| f(bus, delay));
+    let flushed = bus.flush().map_err(Error::Spi);
+    let held = wait(delay, cs_config.hold_us);
+    let deasserted = set_cs(cs, cs_config, false).map_err(Error::ChipSelect);
+    let value = result?;
+    flushed?;
+    held?;
+    deasserted?;
+    wait(delay, cs_config.frame_delay_us)?;
+    Ok(value)
+}
+
+/// Gaps between the words of a transaction
+pub(super) struct WordDelay {
+    us: u32,
+    first: bool,
+}
+
+impl WordDelay {
+    pub(super) fn new(cs_config: &CsConfig) -> Self {
+        WordDelay {
+            us: cs_config.word_delay_us,
+            first: true,
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.us == 0
+    }
+
+    /// Flushes `bus` and waits before every word but the first
+    fn wait<BUS: SpiBusFlush, CS, D: DelayUs>(
+        &mut self,
+        bus: &mut BUS,
+        delay: &mut D,
+    ) -> Result<(), Error<BUS::Error, CS, D::Error>> {
+        if !self.first {
+            bus.flush().map_err(Error::Spi)?;
+            wait(delay, self.us)?;
+        }
+        self.first = false;
+        Ok(())
+    }
+
+    pub(super) fn read<BUS: SpiBusRead<W>, CS, D: DelayUs, W: Word>(
+        &mut self,
+        bus: &mut BUS,
+        delay: &mut D,
+        words: &mut [W],
+    ) -> Result<(), Error<BUS::Error, CS, D::Error>> {
+        if self.is_zero() {
+            return bus.read(words).map_err(Error::Spi);
+        }
+        for word in words {
+            self.wait(bus, delay)?;
+            bus.read(slice::from_mut(word)).map_err(Error::Spi)?;
+        }
+        Ok(())
+    }
+
+    pub(super) fn write<BUS: SpiBusWrite<W>, CS, D: DelayUs, W: Word>(
+        &mut self,
+        bus: &mut BUS,
+        delay: &mut D,
+        words: &[W],
+    ) -> Result<(), Error<BUS::Error, CS, D::Error>> {
+        if self.is_zero() {
+            return bus.write(words).map_err(Error::Spi);
+        }
+        for word in words {
+            self.wait(bus, delay)?;
+            bus.write(slice::from_ref(word)).map_err(Error::Spi)?;
+        }
+        Ok(())
+    }
+
+    fn transfer<BUS: SpiBus<W>, CS, D: DelayUs, W: Word>(
+        &mut self,
+        bus: &mut BUS,
+        delay: &mut D,
+        read: &mut [W],
+        write: &[W],
+    ) -> Result<(), Error<BUS::Error, CS, D::Error>> {
+        if self.is_zero() {
+            return bus.transfer(read, write).map_err(Error::Spi);
+        }
+        for i in 0..read.len().max(write.len()) {
+            self.wait(bus, delay)?;
+            let read = read.get_mut(i).map_or(&mut [][..], slice::from_mut);
+            let write = write.get(i).map_or(&[][..], slice::from_ref);
+            bus.transfer(read, write).map_err(Error::Spi)?;
+        }
+        Ok(())
+    }
+
+    fn transfer_in_place<BUS: SpiBus<W>, CS, D: DelayUs, W: Word>(
+        &mut self,
+        bus: &mut BUS,
+        delay: &mut D,
+        words: &mut [W],
+    ) -> Result<(), Error<BUS::Error, CS, D::Error>> {
+        if self.is_zero() {
+            return bus.transfer_in_place(words).map_err(Error::Spi);
+        }
+        for word in words {
+            self.wait(bus, delay)?;
+            bus.transfer_in_place(slice::from_mut(word))
+                .map_err(Error::Spi)?;
+        }
+        Ok(())
+    }
+}
+
+/// Executes `operations` on `bus`, without handling CS
+pub(super) fn run<BUS, CS, D, W>(
+    bus: &mut BUS,
+    delay: &mut D,
+    cs_config: &CsConfig,
+    operations: &mut [Operation<'_, W>],
+) -> Result<(), Error<BUS::Error, CS, D::Error>>
+where
+    BUS: SpiBus<W>,
+    W: Word,
+    D: DelayUs,
+{
+    let mut words = WordDelay::new(cs_config);
+    for operation in operations {
+        match operation {
+            Operation::Read(read) => words.read(bus, delay, read)?,
+            Operation::Write(write) => words.write(bus, delay, write)?,
+            Operation::Transfer(read, write) => words.transfer(bus, delay, read, write)?,
+            Operation::TransferInPlace(buffer) => words.transfer_in_place(bus, delay, buffer)?,
+            Operation::DelayUs(us) => {
+                bus.flush().map_err(Error::Spi)?;
+                delay.delay_us(*us).map_err(Error::Delay)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+impl<BUS, CS, D, W> SpiDevice<W> for ExclusiveDevice<BUS, CS, D>
+where
+    BUS: SpiBus<W>,
+    CS: OutputPin,
+    D: DelayUs,
+    W: Word,
+{
+    type Error = Error<BUS::Error, CS::Error, D::Error>;
+
+    fn transaction<'a>(&mut self, operations: &mut [Operation<'a, W>]) -> Result<(), Self::Error> {
+        let cs_config = &self.cs_config;
+        with_cs(
+            &mut self.bus,
+            &mut self.cs,
+            cs_config,
+            &mut self.delay,
+            |bus, delay| run(bus, delay, cs_config, operations),
+        )
+    }
+}
+
+impl<BUS, CS, D, W> SpiDeviceHalfDuplex<W> for ExclusiveDevice<BUS, CS, D>
+where
+    BUS: HalfDuplex + SpiBusRead<W> + SpiBusWrite<W>,
+    CS: OutputPin,
+    D: DelayUs,
+    W: Word,
+{
+    type Error = Error<BUS::Error, CS::Error, D::Error>;
+
+    fn write_then_read(&mut self, write: &[W], read: &mut [W]) -> Result<(), Self::Error> {
+        let cs_config = &self.cs_config;
+        with_cs(
+            &mut self.bus,
+            &mut self.cs,
+            cs_config,
+            &mut self.delay,
+            |bus, delay| {
+                let mut words = WordDelay::new(cs_config);
+                words.write(bus, delay, write)?;
+                words.read(bus, delay, read)
+            },
+        )
+    }
+}
+
+impl<BUS, CS, D> SpiDeviceBus for ExclusiveDevice<BUS, CS, D>
+where
+    BUS: SpiBusFlush,
+    CS: OutputPin,
+    D: DelayUs,
+{
+    type Error = Error<BUS::Error, CS::Error, D::Error>;
+    type Bus = BUS;
+
+    fn bus_transaction<R>(
+        &mut self,
+        f: impl FnOnce(&mut Self::Bus) -> Result<R, BUS::Error>,
+    ) -> Result<R, Self::Error> {
+        with_cs(
+            &mut self.bus,
+            &mut self.cs,
+            &self.cs_config,
+            &mut self.delay,
+            |bus, _| f(bus).map_err(Error::Spi),
+        )
+    }
+}
+
+impl<BUS: SetConfig, CS, D> SetConfig for ExclusiveDevice<BUS, CS, D> {
+    type Error = BUS::Error;
+
+    fn set_config(&mut self, config: &Config) -> Result<(), Self::Error> {
+        self.bus.set_config(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spi::Error as _;
+    use core::cell::RefCell;
+    use core::convert::Infallible;
+
+    /// Records bus and pin activity as characters
+    #[derive(Default)]
+    struct Log {
+        events: [u8; 16],
+        len: usize,
+    }
+
+    impl Log {
+        fn push(&mut self, event: u8) {
+            self.events[self.len] = event;
+            self.len += 1;
+        }
+
+        fn events(&self) -> &[u8] {
+            &self.events[..self.len]
+        }
+    }
+
+    struct Bus<'a>(&'a RefCell<Log>);
+
+    impl<'a> SpiBusFlush for Bus<'a> {
+        type Error = ErrorKind;
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            self.0.borrow_mut().push(b'f');
+            Ok(())
+        }
+    }
+
+    impl<'a> SpiBusRead for Bus<'a> {
+        fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            self.0.borrow_mut().push(b'r');
+            for word in words {
+                *word = 0xA5;
+            }
+            Ok(())
+        }
+    }
+
+    impl<'a> SpiBusWrite for Bus<'a> {
+        fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+            self.0.borrow_mut().push(b'w');
+            if words.is_empty() {
+                Err(ErrorKind::Overrun)
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    impl<'a> SpiBus for Bus<'a> {
+        fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+            self.write(write)?;
+            self.read(read)
+        }
+
+        fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            self.read(words)
+        }
+    }
+
+    struct Cs<'a>(&'a RefCell<Log>);
+
+    impl<'a> OutputPin for Cs<'a> {
+        type Error = Infallible;
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.0.borrow_mut().push(b'L');
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.0.borrow_mut().push(b'H');
+            Ok(())
+        }
+    }
+
+    struct Delay<'a>(&'a RefCell<Log>);
+
+    impl<'a> DelayUs for Delay<'a> {
+        type Error = Infallible;
+
+        fn delay_us(&mut self, _us: u32) -> Result<(), Self::Error> {
+            self.0.borrow_mut().push(b'd');
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn transaction_asserts_cs() {
+        let log = RefCell::new(Log::default());
+        let mut device = ExclusiveDevice::new(Bus(&log), Cs(&log))
+            .unwrap()
+            .with_delay(Delay(&log));
+        let mut buffer = [0; 2];
+        device
+            .transaction(&mut [
+                Operation::Write(&[1]),
+                Operation::DelayUs(10),
+                Operation::Read(&mut buffer),
+            ])
+            .unwrap();
+        assert_eq!(buffer, [0xA5; 2]);
+        assert_eq!(log.borrow().events(), b"HLwfdrfH");
+    }
+
+    #[test]
+    fn cs_config() {
+        let log = RefCell::new(Log::default());
+        let cs_config = CsConfig::new()
+            .with_polarity(CsPolarity::ActiveHigh)
+            .with_setup_us(5)
+            .with_hold_us(5);
+        let mut device =
+            ExclusiveDevice::new_with_cs_config(Bus(&log), Cs(&log), cs_config, Delay(&log))
+                .unwrap();
+        SpiDevice::write(&mut device, &[1]).unwrap();
+        assert_eq!(log.borrow().events(), b"LHdwfdL");
+    }
+
+    #[test]
+    fn word_and_frame_delays() {
+        let log = RefCell::new(Log::default());
+        let cs_config = CsConfig::new()
+            .with_word_delay_us(2)
+            .with_frame_delay_us(10);
+        let mut device =
+            ExclusiveDevice::new_with_cs_config(Bus(&log), Cs(&log), cs_config, Delay(&log))
+                .unwrap();
+        SpiDevice::write(&mut device, &[1, 2]).unwrap();
+        assert_eq!(log.borrow().events(), b"HLwfdwfHd");
+    }
+
+    #[test]
+    fn cs_is_deasserted_on_error() {
+        let log = RefCell::new(Log::default());
+        let mut device = ExclusiveDevice::new(Bus(&log), Cs(&log)).unwrap();
+        let result = SpiDevice::write(&mut device, &[]);
+        assert_eq!(result, Err(Error::Spi(ErrorKind::Overrun)));
+        assert_eq!(log.borrow().events(), b"HLwfH");
+    }
+
+    #[test]
+    fn bus_transaction() {
+        let log = RefCell::new(Log::default());
+        let mut device = ExclusiveDevice::new(Bus(&log), Cs(&log)).unwrap();
+        let value = device
+            .bus_transaction(|bus| {
+                let mut len = [0];
+                bus.read(&mut len)?;
+                Ok(len[0])
+            })
+            .unwrap();
+        assert_eq!(value, 0xA5);
+        assert_eq!(log.borrow().events(), b"HLrfH");
+    }
+
+    #[test]
+    fn delay_without_delay_fails() {
+        let log = RefCell::new(Log::default());
+        let mut device = ExclusiveDevice::new(Bus(&log), Cs(&log)).unwrap();
+        let result = device.transaction(&mut [Operation::DelayUs(10)]);
+        assert_eq!(result, Err(Error::Delay(DelayUnsupported)));
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::Other);
+        assert_eq!(log.borrow().events(), b"HLffH");
+    }
+}
@@ -1,7 +1,125 @@
 //! SPI traits
 
+#[cfg(feature = "async")]
+pub mod asynch;
+pub mod bitbang;
 pub mod blocking;
+pub mod daisy_chain;
+pub mod dma;
+pub mod exclusive;
+pub mod multi_io;
 pub mod nb;
+pub mod shared;
+pub mod stats;
+pub mod target;
+pub mod trace;
+pub mod word_size;
+
+use crate::private;
+
+/// SPI word (frame) type
+///
+/// Implemented for `u8`, `u16` and `u32`, as well as for the types in this module holding
+/// words with other numbers of bits, e.g. [`U9`] for the 9-bit frames of some displays.
+/// HALs can implement the SPI traits for exactly the word sizes their hardware supports.
+///
+/// Note: This trait is sealed and should not be implemented outside of this crate.
+pub trait Word: Copy + 'static + private::Sealed {
+    /// Number of significant bits of a word
+    const BITS: u8;
+
+    /// Returns the word as raw bits, right-aligned
+    fn to_bits(self) -> u32;
+
+    /// Creates a word from right-aligned raw bits, discarding the bits above [`Word::BITS`]
+    fn from_bits(bits: u32) -> Self;
+}
+
+macro_rules! word {
+    ($($raw:ty: $bits:expr),+) => {
+        $(
+            impl Word for $raw {
+                const BITS: u8 = $bits;
+
+                fn to_bits(self) -> u32 {
+                    self.into()
+                }
+
+                fn from_bits(bits: u32) -> Self {
+                    bits as $raw
+                }
+            }
+        )+
+    };
+}
+
+word!(u8: 8, u16: 16, u32: 32);
+
+macro_rules! narrow_word {
+    ($($name:ident($raw:ty): $bits:expr, $doc:expr;)+) => {
+        $(
+            #[doc = $doc]
+            #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+            pub struct $name($raw);
+
+            impl $name {
+                /// Maximum value
+                pub const MAX: Self = $name(((1u64 << $bits) - 1) as $raw);
+
+                /// Creates a new word, returning `None` if `value` does not fit
+                pub fn new(value: $raw) -> Option<Self> {
+                    if value <= Self::MAX.0 {
+                        Some($name(value))
+                    } else {
+                        None
+                    }
+                }
+
+                /// Returns the value of the word
+                pub fn get(self) -> $raw {
+                    self.0
+                }
+            }
+
+            impl From<$name> for $raw {
+                fn from(word: $name) -> Self {
+                    word.0
+                }
+            }
+
+            impl private::Sealed for $name {}
+
+            impl Word for $name {
+                const BITS: u8 = $bits;
+
+                fn to_bits(self) -> u32 {
+                    self.0.into()
+                }
+
+                fn from_bits(bits: u32) -> Self {
+                    $name(bits as $raw & Self::MAX.0)
+                }
+            }
+        )+
+    };
+}
+
+narrow_word!(
+    U4(u8): 4, "4-bit SPI word";
+    U5(u8): 5, "5-bit SPI word";
+    U6(u8): 6, "6-bit SPI word";
+    U7(u8): 7, "7-bit SPI word";
+    U9(u16): 9, "9-bit SPI word";
+    U10(u16): 10, "10-bit SPI word";
+    U11(u16): 11, "11-bit SPI word";
+    U12(u16): 12, "12-bit SPI word";
+    U13(u16): 13, "13-bit SPI word";
+    U14(u16): 14, "14-bit SPI word";
+    U15(u16): 15, "15-bit SPI word";
+    U18(u32): 18, "18-bit SPI word";
+    U20(u32): 20, "20-bit SPI word";
+    U24(u32): 24, "24-bit SPI word";
+);
 
 /// Clock polarity
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -54,6 +172,352 @@ pub const MODE_3: Mode = Mode {
     phase: Phase::CaptureOnSecondTransition,
 };
 
+/// Order in which the bits of a word are shifted out and in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BitOrder {
+    /// Most significant bit first
+    MsbFirst,
+    /// Least significant bit first
+    LsbFirst,
+}
+
+impl Default for BitOrder {
+    /// Most significant bit first, which almost all devices use
+    fn default() -> Self {
+        BitOrder::MsbFirst
+    }
+}
+
+/// Bit order configuration
+///
+/// Implemented by buses whose hardware can shift words out least significant bit first, so
+/// drivers for such devices don't have to reverse the bits of their buffers in software.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::spi::{blocking::SpiBusWrite, BitOrder, SetBitOrder};
+///
+/// /// Sends a command to a device which expects LSB-first words
+/// fn command<SPI>(spi: &mut SPI, command: u8) -> Result<(), <SPI as SetBitOrder>::Error>
+/// where
+///     SPI: SpiBusWrite + SetBitOrder<Error = <SPI as embedded_hal::spi::blocking::SpiBusFlush>::Error>,
+/// {
+///     spi.set_bit_order(BitOrder::LsbFirst)?;
+///     spi.write(&[command])
+/// }
+/// ```
+pub trait SetBitOrder {
+    /// Error type
+    type Error: Error;
+
+    /// Sets the bit order of subsequent transfers
+    ///
+    /// Returns an error if the hardware doesn't support `order`.
+    fn set_bit_order(&mut self, order: BitOrder) -> Result<(), Self::Error>;
+}
+
+impl<T: SetBitOrder> SetBitOrder for &mut T {
+    type Error = T::Error;
+
+    fn set_bit_order(&mut self, order: BitOrder) -> Result<(), Self::Error> {
+        T::set_bit_order(self, order)
+    }
+}
+
+/// Frame format
+///
+/// Besides the Motorola format commonly called SPI, some peripherals support framings which
+/// use the same signals differently. Drivers for devices expecting such a framing can
+/// require it with [`SetFrameFormat`] or [`Config::with_frame_format`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FrameFormat {
+    /// Motorola SPI, with CS asserted during the whole transfer and the clock configured by
+    /// the [`Mode`]
+    Motorola,
+    /// Texas Instruments synchronous serial (TI-SSI), with a one clock cycle frame pulse on CS
+    /// before every word and data captured on the falling clock edge
+    ///
+    /// The [`Mode`] is ignored.
+    TexasInstruments,
+    /// National Semiconductor Microwire, half-duplex with an 8-bit control word sent to the
+    /// device before the response is read
+    ///
+    /// The [`Mode`] is ignored.
+    Microwire,
+}
+
+impl Default for FrameFormat {
+    /// Motorola SPI
+    fn default() -> Self {
+        FrameFormat::Motorola
+    }
+}
+
+/// Frame format configuration
+///
+/// Implemented by buses whose hardware supports framings other than Motorola SPI.
+pub trait SetFrameFormat {
+    /// Error type
+    type Error: Error;
+
+    /// Sets the frame format of subsequent transfers
+    ///
+    /// Returns an error if the hardware doesn't support `format`.
+    fn set_frame_format(&mut self, format: FrameFormat) -> Result<(), Self::Error>;
+}
+
+impl<T: SetFrameFormat> SetFrameFormat for &mut T {
+    type Error = T::Error;
+
+    fn set_frame_format(&mut self, format: FrameFormat) -> Result<(), Self::Error> {
+        T::set_frame_format(self, format)
+    }
+}
+
+/// Hardware CRC configuration
+///
+/// The CRC is computed over all words of a transfer, MSB first, starting with a zero
+/// register, which is what SPI peripherals with CRC support implement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CrcConfig {
+    /// Generator polynomial, without the leading bit, e.g. `0x07` for CRC-8
+    pub polynomial: u32,
+    /// Length of the CRC in bits
+    pub bits: u8,
+    /// Whether the CRC is sent after the written words of every transfer, and the last
+    /// received words of every transfer are checked as CRC
+    ///
+    /// Otherwise, the CRC is only computed and can be read from the peripheral by
+    /// implementation-specific means.
+    pub trailing: bool,
+}
+
+impl CrcConfig {
+    /// Creates a new configuration sending and checking a trailing CRC
+    pub const fn new(polynomial: u32, bits: u8) -> Self {
+        CrcConfig {
+            polynomial,
+            bits,
+            trailing: true,
+        }
+    }
+
+    /// Sets whether the CRC is sent and checked
+    pub const fn with_trailing(mut self, trailing: bool) -> Self {
+        self.trailing = trailing;
+        self
+    }
+}
+
+/// Hardware CRC generation and checking
+///
+/// Implemented by buses whose peripheral can compute a CRC over every transfer. With a
+/// trailing CRC, a mismatch of the received CRC is reported as [`ErrorKind::Crc`].
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::spi::{blocking::SpiBus, CrcConfig, SetCrc};
+///
+/// /// Reads a CRC-8 protected frame from a safety sensor
+/// fn read_frame<SPI>(spi: &mut SPI, frame: &mut [u8]) -> Result<(), <SPI as SetCrc>::Error>
+/// where
+///     SPI: SpiBus + SetCrc<Error = <SPI as embedded_hal::spi::blocking::SpiBusFlush>::Error>,
+/// {
+///     spi.set_crc(Some(CrcConfig::new(0x07, 8)))?;
+///     let result = spi.read(frame);
+///     spi.set_crc(None)?;
+///     result
+/// }
+/// ```
+pub trait SetCrc {
+    /// Error type
+    type Error: Error;
+
+    /// Enables CRC generation and checking for subsequent transfers, or disables it with
+    /// `None`
+    ///
+    /// Returns an error if the hardware doesn't support the polynomial or length.
+    fn set_crc(&mut self, crc: Option<CrcConfig>) -> Result<(), Self::Error>;
+}
+
+impl<T: SetCrc> SetCrc for &mut T {
+    type Error = T::Error;
+
+    fn set_crc(&mut self, crc: Option<CrcConfig>) -> Result<(), Self::Error> {
+        T::set_crc(self, crc)
+    }
+}
+
+/// Bus configuration
+///
+/// Used with [`SetConfig`] to change the clock rate and mode at runtime.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// SPI mode
+    pub mode: Mode,
+    /// Maximum clock frequency in Hz
+    ///
+    /// Implementations use the highest frequency they support that doesn't exceed this value.
+    pub max_frequency_hz: u32,
+    /// Bit order
+    pub bit_order: BitOrder,
+    /// Frame format
+    pub frame_format: FrameFormat,
+}
+
+impl Config {
+    /// Creates a new Motorola SPI configuration with MSB-first bit order
+    pub const fn new(mode: Mode, max_frequency_hz: u32) -> Self {
+        Config {
+            mode,
+            max_frequency_hz,
+            bit_order: BitOrder::MsbFirst,
+            frame_format: FrameFormat::Motorola,
+        }
+    }
+
+    /// Sets the bit order
+    pub const fn with_bit_order(mut self, bit_order: BitOrder) -> Self {
+        self.bit_order = bit_order;
+        self
+    }
+
+    /// Sets the frame format
+    pub const fn with_frame_format(mut self, frame_format: FrameFormat) -> Self {
+        self.frame_format = frame_format;
+        self
+    }
+}
+
+/// Active level of a chip select line
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CsPolarity {
+    /// CS is driven low to select the device
+    ActiveLow,
+    /// CS is driven high to select the device
+    ActiveHigh,
+}
+
+impl Default for CsPolarity {
+    /// Active low, which almost all devices use
+    fn default() -> Self {
+        CsPolarity::ActiveLow
+    }
+}
+
+/// Chip select and timing configuration of a device
+///
+/// Used by the device implementations in [`exclusive`] and [`shared`] which drive the chip
+/// select pin. The setup and hold times extend every transaction, so drivers which only need
+/// a delay for some transactions should add an [`Operation::DelayUs`](blocking::Operation)
+/// at its start or end instead.
+///
+/// The word and frame delays declare the gaps slow devices need, e.g. ADCs converting between
+/// frames, once for all transactions. The word delay splits transfers into single words, which
+/// is slow, so it should only be used if the bus can't be configured to insert the gaps in
+/// hardware. It doesn't apply to transactions using the bus directly through
+/// [`SpiDeviceBus`](blocking::SpiDeviceBus).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CsConfig {
+    /// Active level of the chip select pin
+    pub polarity: CsPolarity,
+    /// Time between the assertion of CS and the first clock edge, in microseconds
+    pub setup_us: u32,
+    /// Time between the last clock edge and the deassertion of CS, in microseconds
+    pub hold_us: u32,
+    /// Minimum time between consecutive words of a transaction, in microseconds
+    pub word_delay_us: u32,
+    /// Minimum time between the deassertion of CS and the next transaction, in microseconds
+    pub frame_delay_us: u32,
+}
+
+impl CsConfig {
+    /// Creates a new active low configuration without setup and hold times or delays
+    pub const fn new() -> Self {
+        CsConfig {
+            polarity: CsPolarity::ActiveLow,
+            setup_us: 0,
+            hold_us: 0,
+            word_delay_us: 0,
+            frame_delay_us: 0,
+        }
+    }
+
+    /// Sets the active level of the chip select pin
+    pub const fn with_polarity(mut self, polarity: CsPolarity) -> Self {
+        self.polarity = polarity;
+        self
+    }
+
+    /// Sets the setup time in microseconds
+    pub const fn with_setup_us(mut self, setup_us: u32) -> Self {
+        self.setup_us = setup_us;
+        self
+    }
+
+    /// Sets the hold time in microseconds
+    pub const fn with_hold_us(mut self, hold_us: u32) -> Self {
+        self.hold_us = hold_us;
+        self
+    }
+
+    /// Sets the minimum delay between consecutive words in microseconds
+    pub const fn with_word_delay_us(mut self, word_delay_us: u32) -> Self {
+        self.word_delay_us = word_delay_us;
+        self
+    }
+
+    /// Sets the minimum delay between transactions in microseconds
+    pub const fn with_frame_delay_us(mut self, frame_delay_us: u32) -> Self {
+        self.frame_delay_us = frame_delay_us;
+        self
+    }
+}
+
+/// Runtime configuration of mode and clock frequency
+///
+/// Implemented by buses, which apply the configuration right away, and by devices sharing a
+/// bus, which store it and apply it to the bus at the start of each of their transactions.
+/// Drivers use it for devices which need different settings at different times, e.g. SD cards,
+/// which must be initialized at 400 kHz or less before switching to a higher clock rate.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::spi::{blocking::SpiDevice, Config, SetConfig, MODE_0};
+///
+/// /// Initializes an SD card and switches to full speed
+/// fn init<SPI>(spi: &mut SPI) -> Result<(), <SPI as SetConfig>::Error>
+/// where
+///     SPI: SpiDevice + SetConfig,
+/// {
+///     spi.set_config(&Config::new(MODE_0, 400_000))?;
+///     // ... send the initialization sequence ...
+///     spi.set_config(&Config::new(MODE_0, 25_000_000))
+/// }
+/// ```
+pub trait SetConfig {
+    /// Error type
+    type Error: Error;
+
+    /// Sets the configuration of subsequent transfers
+    ///
+    /// Returns an error if the hardware doesn't support the mode, bit order or frame format.
+    /// Note that too low a frequency isn't an error, the slowest supported frequency is used
+    /// instead.
+    fn set_config(&mut self, config: &Config) -> Result<(), Self::Error>;
+}
+
+impl<T: SetConfig> SetConfig for &mut T {
+    type Error = T::Error;
+
+    fn set_config(&mut self, config: &Config) -> Result<(), Self::Error> {
+        T::set_config(self, config)
+    }
+}
+
 /// SPI error
 pub trait Error: core::fmt::Debug {
     /// Convert error to a generic SPI error kind
@@ -84,6 +548,10 @@ pub enum ErrorKind {
     ModeFault,
     /// Received data does not conform to the peripheral configuration
     FrameFormat,
+    /// Error while asserting or deasserting the chip select pin
+    ChipSelectFault,
+    /// The received CRC didn't match the CRC computed over the received words
+    Crc,
     /// A different error occurred. The original error may contain more information.
     Other,
 }
@@ -106,6 +574,14 @@ impl core::fmt::Display for ErrorKind {
                 f,
                 "Received data does not conform to the peripheral configuration"
             ),
+            Self::ChipSelectFault => write!(
+                f,
+                "Error while asserting or deasserting the chip select pin"
+            ),
+            Self::Crc => write!(
+                f,
+                "The received CRC didn't match the CRC computed over the received words"
+            ),
             Self::Other => write!(
                 f,
                 "A different error occurred. The original error may contain more information"
@@ -113,3 +589,31 @@ impl core::fmt::Display for ErrorKind {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn narrow_words_are_range_checked() {
+        assert_eq!(U9::new(0x1FF).map(U9::get), Some(0x1FF));
+        assert_eq!(U9::new(0x200), None);
+        assert_eq!(U12::MAX.get(), 0xFFF);
+        assert_eq!(U18::MAX.get(), 0x3_FFFF);
+    }
+
+    #[test]
+    fn from_bits_masks_extra_bits() {
+        assert_eq!(U9::from_bits(0xFFFF_FFFF), U9::MAX);
+        assert_eq!(U4::from_bits(0x35).to_bits(), 0x5);
+        assert_eq!(u8::from_bits(0x1234), 0x34);
+        assert_eq!(u32::from_bits(0x1234_5678), 0x1234_5678);
+    }
+
+    #[test]
+    fn bits() {
+        assert_eq!(<u16 as Word>::BITS, 16);
+        assert_eq!(U9::BITS, 9);
+        assert_eq!(U24::BITS, 24);
+    }
+}
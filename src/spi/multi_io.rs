@@ -0,0 +1,168 @@
+//! Dual, Quad and Octal SPI
+//!
+//! Multi-I/O SPI peripherals (QSPI, OSPI, ...) transfer frames made of up to four phases:
+//! an instruction, an address, a number of dummy cycles and data. Each phase can use 1, 2, 4
+//! or 8 data lines, as described by a [`Frame`]. The chip-select line is managed by the
+//! implementation, every call is one frame with CS asserted.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::spi::multi_io::{Frame, LineWidth, MultiIo};
+//!
+//! /// Reads from a QSPI flash with the "Quad Output Fast Read" (0x6B) command
+//! fn read<Q: MultiIo>(qspi: &mut Q, address: u32, buffer: &mut [u8]) -> Result<(), Q::Error> {
+//!     let frame = Frame::new()
+//!         .with_instruction(0x6B, LineWidth::Single)
+//!         .with_address(address, 24, LineWidth::Single)
+//!         .with_dummy_cycles(8)
+//!         .with_data_width(LineWidth::Quad);
+//!     qspi.read(&frame, buffer)
+//! }
+//! ```
+
+/// Number of data lines used by a phase
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum LineWidth {
+    /// One line (standard SPI)
+    Single,
+    /// Two lines (Dual SPI)
+    Dual,
+    /// Four lines (Quad SPI)
+    Quad,
+    /// Eight lines (Octal SPI)
+    Octal,
+}
+
+impl LineWidth {
+    /// Returns the number of data lines
+    pub fn lines(self) -> u8 {
+        match self {
+            Self::Single => 1,
+            Self::Dual => 2,
+            Self::Quad => 4,
+            Self::Octal => 8,
+        }
+    }
+}
+
+/// Instruction phase of a [`Frame`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Instruction {
+    /// Instruction value, sent most significant bit first
+    pub value: u16,
+    /// Length of the instruction in bits, 8 or 16
+    pub bits: u8,
+    /// Lines used by the instruction phase
+    pub width: LineWidth,
+}
+
+/// Address phase of a [`Frame`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Address {
+    /// Address value, sent most significant bit first
+    pub value: u32,
+    /// Length of the address in bits, one of 8, 16, 24 or 32
+    pub bits: u8,
+    /// Lines used by the address phase
+    pub width: LineWidth,
+}
+
+/// Description of a multi-I/O SPI frame, without the data
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Frame {
+    /// Instruction phase, if any
+    pub instruction: Option<Instruction>,
+    /// Address phase, if any
+    pub address: Option<Address>,
+    /// Number of clock cycles between the address and the data phase
+    pub dummy_cycles: u8,
+    /// Lines used by the data phase
+    pub data_width: LineWidth,
+}
+
+impl Frame {
+    /// Creates a frame consisting only of a data phase on a single line
+    pub fn new() -> Self {
+        Frame {
+            instruction: None,
+            address: None,
+            dummy_cycles: 0,
+            data_width: LineWidth::Single,
+        }
+    }
+
+    /// Adds an 8-bit instruction phase
+    pub fn with_instruction(mut self, instruction: u8, width: LineWidth) -> Self {
+        self.instruction = Some(Instruction {
+            value: instruction.into(),
+            bits: 8,
+            width,
+        });
+        self
+    }
+
+    /// Adds an address phase with `bits` address bits
+    pub fn with_address(mut self, address: u32, bits: u8, width: LineWidth) -> Self {
+        self.address = Some(Address {
+            value: address,
+            bits,
+            width,
+        });
+        self
+    }
+
+    /// Sets the number of dummy cycles before the data phase
+    pub fn with_dummy_cycles(mut self, cycles: u8) -> Self {
+        self.dummy_cycles = cycles;
+        self
+    }
+
+    /// Sets the lines used by the data phase
+    pub fn with_data_width(mut self, width: LineWidth) -> Self {
+        self.data_width = width;
+        self
+    }
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Blocking multi-I/O SPI
+pub trait MultiIo {
+    /// Error type
+    ///
+    /// Implementations return an error with [`ErrorKind::Other`](super::ErrorKind::Other) for
+    /// frames they cannot transfer, e.g. with a line width they do not support.
+    type Error: super::Error;
+
+    /// Transfers the phases described by `frame`, then reads `buffer.len()` bytes in the data phase
+    fn read(&mut self, frame: &Frame, buffer: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Transfers the phases described by `frame`, then writes `bytes` in the data phase
+    fn write(&mut self, frame: &Frame, bytes: &[u8]) -> Result<(), Self::Error>;
+
+    /// Transfers the phases described by `frame` without a data phase
+    fn command(&mut self, frame: &Frame) -> Result<(), Self::Error> {
+        self.write(frame, &[])
+    }
+}
+
+impl<T: MultiIo> MultiIo for &mut T {
+    type Error = T::Error;
+
+    fn read(&mut self, frame: &Frame, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        T::read(self, frame, buffer)
+    }
+
+    fn write(&mut self, frame: &Frame, bytes: &[u8]) -> Result<(), Self::Error> {
+        T::write(self, frame, bytes)
+    }
+
+    fn command(&mut self, frame: &Frame) -> Result<(), Self::Error> {
+        T::command(self, frame)
+    }
+}
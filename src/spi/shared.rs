@@ -0,0 +1,419 @@
+//! Shared SPI bus
+//!
+//! The types in this module share a single SPI bus between several devices, each with its
+//! own chip select pin. They implement the device traits of [`blocking`](super::blocking),
+//! locking the bus for the whole duration of each transaction, so transactions of different
+//! devices are never interleaved.
+//!
+//! The types differ in how the access to the bus is synchronized:
+//!
+//! - [`RefCellDevice`] uses a `RefCell`, it can only be used when all the devices
+//!   live in the same execution context (e.g. the same thread or interrupt priority).
+//! - [`CriticalSectionDevice`] uses a critical section from the [`critical-section`] crate.
+//!   Only available with the `critical-section` feature.
+//! - [`MutexDevice`] uses a `std::sync::Mutex`. Only available with the `std` feature.
+//!
+//! Devices with different clock rates or modes can share a bus if it implements
+//! [`SetConfig`]: a device created with `with_config` applies its [`Config`] to the bus at the
+//! start of each of its transactions.
+//!
+//! ## Example
+//!
+//! ```
+//! use core::cell::RefCell;
+//! use embedded_hal::digital::blocking::OutputPin;
+//! use embedded_hal::spi::blocking::{SpiBus, SpiDevice};
+//! use embedded_hal::spi::exclusive::Error;
+//! use embedded_hal::spi::shared::RefCellDevice;
+//!
+//! fn read_both<BUS, CS>(bus: BUS, cs_a: CS, cs_b: CS) -> Result<[u8; 2], Error<BUS::Error, CS::Error>>
+//! where
+//!     BUS: SpiBus,
+//!     CS: OutputPin,
+//! {
+//!     let bus = RefCell::new(bus);
+//!     let mut sensor_a = RefCellDevice::new(&bus, cs_a).map_err(Error::ChipSelect)?;
+//!     let mut sensor_b = RefCellDevice::new(&bus, cs_b).map_err(Error::ChipSelect)?;
+//!
+//!     let mut a = [0x81, 0];
+//!     let mut b = [0x81, 0];
+//!     sensor_a.transfer_in_place(&mut a)?;
+//!     sensor_b.transfer_in_place(&mut b)?;
+//!     Ok([a[1], b[1]])
+//! }
+//! ```
+//!
+//! [`critical-section`]: https://crates.io/crates/critical-section
+
+use core::cell::RefCell;
+
+use super::blocking::{
+    HalfDuplex, Operation, SpiBus, SpiBusFlush, SpiBusRead, SpiBusWrite, SpiDevice, SpiDeviceBus,
+    SpiDeviceHalfDuplex,
+};
+use super::exclusive::{run, set_cs, with_cs, Error, NoDelay, WordDelay};
+use super::{Config, CsConfig, SetConfig, Word};
+use crate::delay::blocking::DelayUs;
+use crate::digital::blocking::OutputPin;
+
+/// Configuration applied by a shared device at the start of each transaction
+///
+/// Implemented for `()`, which leaves the bus configuration unchanged, and for [`Config`],
+/// for buses implementing [`SetConfig`] with the same error type as their transfers.
+pub trait DeviceConfig<BUS: SpiBusFlush> {
+    /// Applies the configuration to `bus`
+    fn apply(&self, bus: &mut BUS) -> Result<(), BUS::Error>;
+}
+
+impl<BUS: SpiBusFlush> DeviceConfig<BUS> for () {
+    fn apply(&self, _bus: &mut BUS) -> Result<(), BUS::Error> {
+        Ok(())
+    }
+}
+
+impl<BUS> DeviceConfig<BUS> for Config
+where
+    BUS: SpiBusFlush + SetConfig<Error = <BUS as SpiBusFlush>::Error>,
+{
+    fn apply(&self, bus: &mut BUS) -> Result<(), <BUS as SpiBusFlush>::Error> {
+        bus.set_config(self)
+    }
+}
+
+macro_rules! shared_device {
+    ($name:ident, $shared:ty, |$bus:ident, $f:ident| $lock:expr) => {
+        impl<'a, BUS, CS: OutputPin> $name<'a, BUS, CS> {
+            /// Creates a new device on the shared `bus`, deasserting (driving high) `cs`
+            ///
+            /// Transactions containing a non-zero [`Operation::DelayUs`] fail with
+            /// [`Error::Delay`] unless a delay is set, see `with_delay`.
+            pub fn new(bus: &'a $shared, cs: CS) -> Result<Self, CS::Error> {
+                Self::new_with_cs_config(bus, cs, CsConfig::new(), NoDelay)
+            }
+        }
+
+        impl<'a, BUS, CS: OutputPin, D: DelayUs> $name<'a, BUS, CS, D> {
+            /// Creates a new device on the shared `bus` using `cs_config` for the chip select
+            /// pin, deasserting `cs` according to its polarity
+            ///
+            /// `delay` waits for the setup, hold, word and frame delays of `cs_config` and for
+            /// the [`Operation::DelayUs`] operations of transactions.
+            pub fn new_with_cs_config(
+                bus: &'a $shared,
+                mut cs: CS,
+                cs_config: CsConfig,
+                delay: D,
+            ) -> Result<Self, CS::Error> {
+                set_cs(&mut cs, &cs_config, false)?;
+                Ok($name {
+                    bus,
+                    cs,
+                    delay,
+                    cs_config,
+                    config: (),
+                })
+            }
+        }
+
+        impl<'a, BUS, CS, D, C> $name<'a, BUS, CS, D, C> {
+            /// Uses `delay` for the [`Operation::DelayUs`] operations of transactions
+            ///
+            /// The delay is also used for the setup and hold times of the [`CsConfig`].
+            pub fn with_delay<D2: DelayUs>(self, delay: D2) -> $name<'a, BUS, CS, D2, C> {
+                $name {
+                    bus: self.bus,
+                    cs: self.cs,
+                    delay,
+                    cs_config: self.cs_config,
+                    config: self.config,
+                }
+            }
+
+            /// Applies `config` to the bus at the start of each transaction
+            pub fn with_config(self, config: Config) -> $name<'a, BUS, CS, D, Config> {
+                $name {
+                    bus: self.bus,
+                    cs: self.cs,
+                    delay: self.delay,
+                    cs_config: self.cs_config,
+                    config,
+                }
+            }
+
+            /// Releases the chip select pin and the delay
+            pub fn release(self) -> (CS, D) {
+                (self.cs, self.delay)
+            }
+
+            fn lock<R>(
+                &mut self,
+                op: impl FnOnce(&mut BUS, &mut CS, &CsConfig, &mut D, &C) -> R,
+            ) -> R {
+                let $bus = self.bus;
+                let cs = &mut self.cs;
+                let cs_config = &self.cs_config;
+                let delay = &mut self.delay;
+                let config = &self.config;
+                let $f = |bus: &mut BUS| op(bus, cs, cs_config, delay, config);
+                $lock
+            }
+        }
+
+        impl<'a, BUS, CS, D, C, W> SpiDevice<W> for $name<'a, BUS, CS, D, C>
+        where
+            BUS: SpiBus<W>,
+            CS: OutputPin,
+            D: DelayUs,
+            C: DeviceConfig<BUS>,
+            W: Word,
+        {
+            type Error = Error<BUS::Error, CS::Error, D::Error>;
+
+            fn transaction<'b>(
+                &mut self,
+                operations: &mut [Operation<'b, W>],
+            ) -> Result<(), Self::Error> {
+                self.lock(|bus, cs, cs_config, delay, config| {
+                    config.apply(bus).map_err(Error::Spi)?;
+                    with_cs(bus, cs, cs_config, delay, |bus, delay| {
+                        run(bus, delay, cs_config, operations)
+                    })
+                })
+            }
+        }
+
+        impl<'a, BUS, CS, D, C, W> SpiDeviceHalfDuplex<W> for $name<'a, BUS, CS, D, C>
+        where
+            BUS: HalfDuplex + SpiBusRead<W> + SpiBusWrite<W>,
+            CS: OutputPin,
+            D: DelayUs,
+            C: DeviceConfig<BUS>,
+            W: Word,
+        {
+            type Error = Error<BUS::Error, CS::Error, D::Error>;
+
+            fn write_then_read(&mut self, write: &[W], read: &mut [W]) -> Result<(), Self::Error> {
+                self.lock(|bus, cs, cs_config, delay, config| {
+                    config.apply(bus).map_err(Error::Spi)?;
+                    with_cs(bus, cs, cs_config, delay, |bus, delay| {
+                        let mut words = WordDelay::new(cs_config);
+                        words.write(bus, delay, write)?;
+                        words.read(bus, delay, read)
+                    })
+                })
+            }
+        }
+
+        impl<'a, BUS, CS, D, C> SpiDeviceBus for $name<'a, BUS, CS, D, C>
+        where
+            BUS: SpiBusFlush,
+            CS: OutputPin,
+            D: DelayUs,
+            C: DeviceConfig<BUS>,
+        {
+            type Error = Error<BUS::Error, CS::Error, D::Error>;
+            type Bus = BUS;
+
+            fn bus_transaction<R>(
+                &mut self,
+                f: impl FnOnce(&mut Self::Bus) -> Result<R, BUS::Error>,
+            ) -> Result<R, Self::Error> {
+                self.lock(|bus, cs, cs_config, delay, config| {
+                    config.apply(bus).map_err(Error::Spi)?;
+                    with_cs(bus, cs, cs_config, delay, |bus, _| {
+                        f(bus).map_err(Error::Spi)
+                    })
+                })
+            }
+        }
+
+        impl<'a, BUS, CS, D> SetConfig for $name<'a, BUS, CS, D, Config>
+        where
+            BUS: SpiBusFlush,
+        {
+            type Error = core::convert::Infallible;
+
+            /// Replaces the configuration applied at the start of each transaction
+            fn set_config(&mut self, config: &Config) -> Result<(), Self::Error> {
+                self.config = *config;
+                Ok(())
+            }
+        }
+    };
+}
+
+/// `RefCell`-based shared bus device
+///
+/// # Panics
+///
+/// Calling a method of this device while another device of the same bus is
+/// executing one (e.g. re-entrantly from an interrupt) panics.
+#[derive(Debug)]
+pub struct RefCellDevice<'a, BUS, CS, D = NoDelay, C = ()> {
+    bus: &'a RefCell<BUS>,
+    cs: CS,
+    delay: D,
+    cs_config: CsConfig,
+    config: C,
+}
+
+shared_device!(RefCellDevice, RefCell<BUS>, |bus, f| f(
+    &mut bus.borrow_mut()
+));
+
+/// Critical-section-based shared bus device
+///
+/// The bus is locked for the whole duration of each transaction, so interrupts are
+/// typically disabled while the SPI transfer is ongoing.
+#[cfg(feature = "critical-section")]
+#[derive(Debug)]
+pub struct CriticalSectionDevice<'a, BUS, CS, D = NoDelay, C = ()> {
+    bus: &'a critical_section::Mutex<RefCell<BUS>>,
+    cs: CS,
+    delay: D,
+    cs_config: CsConfig,
+    config: C,
+}
+
+#[cfg(feature = "critical-section")]
+shared_device!(
+    CriticalSectionDevice,
+    critical_section::Mutex<RefCell<BUS>>,
+    |bus, f| critical_section::with(|cs| f(&mut bus.borrow_ref_mut(cs)))
+);
+
+/// `std::sync::Mutex`-based shared bus device
+///
+/// # Panics
+///
+/// Panics if the mutex has been poisoned.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct MutexDevice<'a, BUS, CS, D = NoDelay, C = ()> {
+    bus: &'a std::sync::Mutex<BUS>,
+    cs: CS,
+    delay: D,
+    cs_config: CsConfig,
+    config: C,
+}
+
+#[cfg(feature = "std")]
+shared_device!(MutexDevice, std::sync::Mutex<BUS>, |bus, f| f(&mut bus
+    .lock()
+    .unwrap()));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spi::exclusive::DelayUnsupported;
+    use crate::spi::{ErrorKind, MODE_0, MODE_3};
+    use core::convert::Infallible;
+
+    /// Bus recording its configuration and the last written word
+    #[derive(Default)]
+    struct Bus {
+        config: Option<Config>,
+        written: u8,
+    }
+
+    impl SpiBusFlush for Bus {
+        type Error = ErrorKind;
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    impl SpiBusRead for Bus {
+        fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            for word in words {
+                *word = 0;
+            }
+            Ok(())
+        }
+    }
+
+    impl SpiBusWrite for Bus {
+        fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+            self.written = words[words.len() - 1];
+            Ok(())
+        }
+    }
+
+    impl SpiBus for Bus {
+        fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+            self.write(write)?;
+            self.read(read)
+        }
+
+        fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            self.write(words)
+        }
+    }
+
+    impl SetConfig for Bus {
+        type Error = ErrorKind;
+
+        fn set_config(&mut self, config: &Config) -> Result<(), Self::Error> {
+            self.config = Some(*config);
+            Ok(())
+        }
+    }
+
+    /// Chip select pin which is low while borrowed
+    struct Cs<'a>(&'a RefCell<bool>);
+
+    impl<'a> OutputPin for Cs<'a> {
+        type Error = Infallible;
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            *self.0.borrow_mut() = false;
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            *self.0.borrow_mut() = true;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn devices_apply_their_config() {
+        let bus = RefCell::new(Bus::default());
+        let (cs_a, cs_b) = (RefCell::new(false), RefCell::new(false));
+        let mut a = RefCellDevice::new(&bus, Cs(&cs_a))
+            .unwrap()
+            .with_config(Config::new(MODE_0, 400_000));
+        let mut b = RefCellDevice::new(&bus, Cs(&cs_b))
+            .unwrap()
+            .with_config(Config::new(MODE_3, 8_000_000));
+        assert!(*cs_a.borrow() && *cs_b.borrow());
+
+        a.bus_transaction(|bus| {
+            assert!(!*cs_a.borrow() && *cs_b.borrow());
+            bus.write(&[1])
+        })
+        .unwrap();
+        assert_eq!(bus.borrow().config, Some(Config::new(MODE_0, 400_000)));
+
+        b.bus_transaction(|bus| bus.write(&[2])).unwrap();
+        assert_eq!(bus.borrow().config, Some(Config::new(MODE_3, 8_000_000)));
+        assert_eq!(bus.borrow().written, 2);
+
+        a.set_config(&Config::new(MODE_0, 25_000_000)).unwrap();
+        a.bus_transaction(|bus| bus.write(&[3])).unwrap();
+        assert_eq!(bus.borrow().config, Some(Config::new(MODE_0, 25_000_000)));
+        assert!(*cs_a.borrow() && *cs_b.borrow());
+    }
+
+    #[test]
+    fn delay_without_delay_fails() {
+        let bus = RefCell::new(Bus::default());
+        let cs = RefCell::new(false);
+        let mut device = RefCellDevice::new(&bus, Cs(&cs)).unwrap();
+        let result = device.transaction(&mut [Operation::Write(&[1]), Operation::DelayUs(10)]);
+        assert_eq!(result, Err(Error::Delay(DelayUnsupported)));
+        assert_eq!(bus.borrow().written, 1);
+        assert!(*cs.borrow());
+    }
+}
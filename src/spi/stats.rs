@@ -0,0 +1,244 @@
+//! SPI statistics
+//!
+//! [`Statistics`] wraps a device and records the number of transactions and words, and the
+//! duration of the transactions as measured by a [`Clock`]. This helps tuning DMA chunk sizes
+//! and finding out how long devices on a [shared](super::shared) bus wait for each other.
+//!
+//! Any `FnMut() -> u32` closure returning a microsecond timestamp is a clock, e.g. one reading
+//! a free-running hardware timer. Timestamps may wrap around, as long as no transaction takes
+//! longer than a full period of the clock.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::spi::blocking::SpiDevice;
+//! use embedded_hal::spi::stats::{Clock, Stats, Statistics};
+//!
+//! /// Reads `n` sensor samples, returning the statistics of the transactions
+//! fn profile<SPI: SpiDevice, C: Clock>(spi: SPI, clock: C, n: usize) -> Result<Stats, SPI::Error> {
+//!     let mut spi = Statistics::new(spi, clock);
+//!     let mut sample = [0; 6];
+//!     for _ in 0..n {
+//!         spi.transfer_in_place(&mut sample)?;
+//!     }
+//!     Ok(*spi.stats())
+//! }
+//! ```
+
+use super::blocking::{Operation, SpiDevice, SpiDeviceHalfDuplex};
+use super::Word;
+pub use crate::delay::Clock;
+
+/// Recorded statistics
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Stats {
+    transactions: u32,
+    errors: u32,
+    words: u64,
+    total_us: u64,
+    min_us: u32,
+    max_us: u32,
+}
+
+impl Stats {
+    /// Creates empty statistics
+    pub const fn new() -> Self {
+        Stats {
+            transactions: 0,
+            errors: 0,
+            words: 0,
+            total_us: 0,
+            min_us: u32::MAX,
+            max_us: 0,
+        }
+    }
+
+    /// Returns the number of transactions, including failed ones
+    pub fn transactions(&self) -> u32 {
+        self.transactions
+    }
+
+    /// Returns the number of failed transactions
+    pub fn errors(&self) -> u32 {
+        self.errors
+    }
+
+    /// Returns the number of words transferred
+    ///
+    /// Simultaneously read and written words are counted once. Words of failed transactions
+    /// are counted as well.
+    pub fn words(&self) -> u64 {
+        self.words
+    }
+
+    /// Returns the shortest transaction duration in microseconds
+    pub fn min_us(&self) -> Option<u32> {
+        self.duration(self.min_us)
+    }
+
+    /// Returns the longest transaction duration in microseconds
+    pub fn max_us(&self) -> Option<u32> {
+        self.duration(self.max_us)
+    }
+
+    /// Returns the average transaction duration in microseconds
+    pub fn avg_us(&self) -> Option<u32> {
+        self.duration((self.total_us / u64::from(self.transactions.max(1))) as u32)
+    }
+
+    fn duration(&self, us: u32) -> Option<u32> {
+        if self.transactions > 0 {
+            Some(us)
+        } else {
+            None
+        }
+    }
+
+    fn record(&mut self, words: usize, us: u32, ok: bool) {
+        self.transactions = self.transactions.saturating_add(1);
+        if !ok {
+            self.errors = self.errors.saturating_add(1);
+        }
+        self.words = self.words.saturating_add(words as u64);
+        self.total_us = self.total_us.saturating_add(u64::from(us));
+        self.min_us = self.min_us.min(us);
+        self.max_us = self.max_us.max(us);
+    }
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// SPI device recording [`Stats`] of its transactions
+#[derive(Debug)]
+pub struct Statistics<T, C> {
+    inner: T,
+    clock: C,
+    stats: Stats,
+}
+
+impl<T, C: Clock> Statistics<T, C> {
+    /// Creates a new device with empty statistics
+    pub fn new(inner: T, clock: C) -> Self {
+        Statistics {
+            inner,
+            clock,
+            stats: Stats::new(),
+        }
+    }
+
+    /// Returns the statistics recorded so far
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Clears the statistics
+    pub fn reset(&mut self) {
+        self.stats = Stats::new();
+    }
+
+    /// Releases the device and the clock
+    pub fn release(self) -> (T, C) {
+        (self.inner, self.clock)
+    }
+
+    fn measure<R, E>(
+        &mut self,
+        words: usize,
+        f: impl FnOnce(&mut T) -> Result<R, E>,
+    ) -> Result<R, E> {
+        let start = self.clock.now_us();
+        let result = f(&mut self.inner);
+        let us = self.clock.now_us().wrapping_sub(start);
+        self.stats.record(words, us, result.is_ok());
+        result
+    }
+}
+
+impl<T: SpiDevice<W>, C: Clock, W: Word> SpiDevice<W> for Statistics<T, C> {
+    type Error = T::Error;
+
+    fn transaction<'a>(&mut self, operations: &mut [Operation<'a, W>]) -> Result<(), Self::Error> {
+        let words = operations
+            .iter()
+            .map(|operation| match operation {
+                Operation::Read(words) => words.len(),
+                Operation::Write(words) => words.len(),
+                Operation::Transfer(read, write) => read.len().max(write.len()),
+                Operation::TransferInPlace(words) => words.len(),
+                Operation::DelayUs(_) => 0,
+            })
+            .sum();
+        self.measure(words, |inner| inner.transaction(operations))
+    }
+}
+
+impl<T: SpiDeviceHalfDuplex<W>, C: Clock, W: Word> SpiDeviceHalfDuplex<W> for Statistics<T, C> {
+    type Error = T::Error;
+
+    fn write_then_read(&mut self, write: &[W], read: &mut [W]) -> Result<(), Self::Error> {
+        self.measure(write.len() + read.len(), |inner| {
+            inner.write_then_read(write, read)
+        })
+    }
+
+    fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        self.measure(words.len(), |inner| inner.write(words))
+    }
+
+    fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        self.measure(words.len(), |inner| inner.read(words))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spi::ErrorKind;
+    use core::cell::Cell;
+
+    /// Device whose transactions take 10 µs per word and fail for reads
+    struct Device<'a>(&'a Cell<u32>);
+
+    impl SpiDevice for Device<'_> {
+        type Error = ErrorKind;
+
+        fn transaction<'a>(
+            &mut self,
+            operations: &mut [Operation<'a, u8>],
+        ) -> Result<(), Self::Error> {
+            for operation in operations {
+                match operation {
+                    Operation::Write(words) => self
+                        .0
+                        .set(self.0.get().wrapping_add(10 * words.len() as u32)),
+                    Operation::Read(_) => return Err(ErrorKind::Overrun),
+                    _ => {}
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn records_transactions() {
+        let time = Cell::new(u32::MAX - 5);
+        let mut spi = Statistics::new(Device(&time), || time.get());
+        assert_eq!(spi.stats().avg_us(), None);
+        spi.write(&[1, 2]).unwrap();
+        spi.write(&[1, 2, 3, 4]).unwrap();
+        assert_eq!(spi.read(&mut [0]), Err(ErrorKind::Overrun));
+        let stats = *spi.stats();
+        assert_eq!(stats.transactions(), 3);
+        assert_eq!(stats.errors(), 1);
+        assert_eq!(stats.words(), 7);
+        assert_eq!(stats.min_us(), Some(0));
+        assert_eq!(stats.max_us(), Some(40));
+        assert_eq!(stats.avg_us(), Some(20));
+        spi.reset();
+        assert_eq!(spi.stats(), &Stats::new());
+    }
+}
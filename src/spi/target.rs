@@ -0,0 +1,60 @@
+//! SPI target (slave) mode API
+//!
+//! The traits in this module model the target side of an SPI bus, i.e. a peripheral whose
+//! clock and chip-select line are driven by an external controller. A transaction lasts from
+//! the assertion to the deassertion of CS by the controller.
+//!
+//! Since the controller decides when a transaction starts, the data to send has to be ready
+//! beforehand: [`Target::transfer`] pre-loads the words to send and the buffer to receive
+//! into, then waits for the controller to complete a transaction. The async counterpart is
+//! `spi::asynch::Target`, available with the `async` feature.
+//!
+//! ## Example
+//!
+//! A link to another MCU answering each request with a status word:
+//!
+//! ```
+//! use embedded_hal::spi::target::Target;
+//!
+//! pub fn serve<T: Target>(target: &mut T, status: u8) -> Result<[u8; 4], T::Error> {
+//!     let mut request = [0; 4];
+//!     loop {
+//!         let n = target.transfer(&mut request, &[status])?;
+//!         // Ignore transactions aborted by the controller
+//!         if n >= request.len() {
+//!             return Ok(request);
+//!         }
+//!     }
+//! }
+//! ```
+
+use super::{Error, Word};
+
+/// SPI target
+pub trait Target<W: Word = u8> {
+    /// Error type
+    type Error: Error;
+
+    /// Pre-loads `write`, then blocks until the controller has completed a transaction
+    ///
+    /// Words received during the transaction are stored in `read`. Returns the number of
+    /// words clocked by the controller, which may be less or more than the lengths of the
+    /// buffers:
+    ///
+    /// - Words received after `read` is full are discarded.
+    /// - Once `write` is exhausted, the value of the words sent is implementation-defined,
+    ///   typically all zeros or all ones.
+    ///
+    /// Words of a transaction which started before this method was called are not received;
+    /// implementations either wait for the next transaction or return
+    /// [`ErrorKind::Overrun`](super::ErrorKind::Overrun).
+    fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<usize, Self::Error>;
+}
+
+impl<T: Target<W>, W: Word> Target<W> for &mut T {
+    type Error = T::Error;
+
+    fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<usize, Self::Error> {
+        T::transfer(self, read, write)
+    }
+}
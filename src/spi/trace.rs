@@ -0,0 +1,278 @@
+//! Tracing of SPI traffic
+//!
+//! [`Trace`] wraps a bus or device and reports every transfer as an [`Event`] to a [`Sink`]
+//! before forwarding it to the inner implementation, so protocol issues can be debugged
+//! without a logic analyzer.
+//!
+//! Any `FnMut(Event<W>)` closure is a sink, which allows forwarding the events to any logging
+//! framework, e.g. `defmt`. With the `log` feature, [`LogSink`] emits them with the `log`
+//! crate.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::spi::blocking::SpiDevice;
+//! use embedded_hal::spi::trace::{Event, Trace};
+//!
+//! /// Counts the bytes written to `spi`
+//! fn count_written<SPI: SpiDevice>(spi: SPI) -> Result<usize, SPI::Error> {
+//!     let mut written = 0;
+//!     let mut spi = Trace::new(spi, |event: Event<u8>| {
+//!         if let Event::Write(words) = event {
+//!             written += words.len();
+//!         }
+//!     });
+//!     spi.write(&[0x06])?;
+//!     spi.write(&[0x02, 0x00, 0x10, 0x00, 0xAA])?;
+//!     drop(spi);
+//!     Ok(written)
+//! }
+//! ```
+
+use super::blocking::{
+    Operation, SpiBus, SpiBusFlush, SpiBusRead, SpiBusWrite, SpiDevice, SpiDeviceHalfDuplex,
+};
+use super::{Error, ErrorKind, Word};
+use core::marker::PhantomData;
+
+/// Traced SPI activity
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Event<'a, W> {
+    /// A device transaction starts, i.e. CS is asserted
+    Start,
+    /// Words written to the bus
+    Write(&'a [W]),
+    /// Words read from the bus
+    Read(&'a [W]),
+    /// Delay within a device transaction
+    DelayUs(u32),
+    /// The bus is flushed
+    Flush,
+    /// A call to the inner implementation failed
+    Error(ErrorKind),
+    /// A device transaction ended, i.e. CS is deasserted
+    End,
+}
+
+/// Receiver of [`Event`]s
+pub trait Sink<W> {
+    /// Handles an event
+    fn event(&mut self, event: Event<'_, W>);
+}
+
+impl<W, F: FnMut(Event<'_, W>)> Sink<W> for F {
+    fn event(&mut self, event: Event<'_, W>) {
+        self(event)
+    }
+}
+
+/// [`Sink`] logging events at the `debug` level with the `log` crate
+///
+/// Only available with the `log` feature.
+#[cfg(feature = "log")]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct LogSink;
+
+#[cfg(feature = "log")]
+impl<W: core::fmt::Debug> Sink<W> for LogSink {
+    fn event(&mut self, event: Event<'_, W>) {
+        log::debug!("spi: {:?}", event);
+    }
+}
+
+/// SPI bus or device reporting its traffic to a [`Sink`]
+///
+/// `W` is the word type of the traced traffic.
+///
+/// For device transactions, the words written by all operations are reported after
+/// [`Event::Start`] and before the transaction is executed, and the words read are reported
+/// afterwards, followed by [`Event::End`]. This way, the words written by
+/// [`Operation::TransferInPlace`] are reported before they are overwritten.
+#[derive(Debug)]
+pub struct Trace<T, S, W = u8> {
+    inner: T,
+    sink: S,
+    word: PhantomData<W>,
+}
+
+impl<T, S, W> Trace<T, S, W> {
+    /// Creates a new traced bus or device
+    pub fn new(inner: T, sink: S) -> Self {
+        Trace {
+            inner,
+            sink,
+            word: PhantomData,
+        }
+    }
+
+    /// Releases the inner implementation and the sink
+    pub fn release(self) -> (T, S) {
+        (self.inner, self.sink)
+    }
+
+    fn check<R, E: Error>(&mut self, result: Result<R, E>) -> Result<R, E>
+    where
+        S: Sink<W>,
+    {
+        if let Err(e) = &result {
+            self.sink.event(Event::Error(e.kind()));
+        }
+        result
+    }
+}
+
+impl<T: SpiBusFlush, S: Sink<W>, W: Word> SpiBusFlush for Trace<T, S, W> {
+    type Error = T::Error;
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.sink.event(Event::Flush);
+        let result = self.inner.flush();
+        self.check(result)
+    }
+}
+
+impl<T: SpiBusRead<W>, S: Sink<W>, W: Word> SpiBusRead<W> for Trace<T, S, W> {
+    fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        let result = self.inner.read(words);
+        self.check(result)?;
+        self.sink.event(Event::Read(words));
+        Ok(())
+    }
+}
+
+impl<T: SpiBusWrite<W>, S: Sink<W>, W: Word> SpiBusWrite<W> for Trace<T, S, W> {
+    fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        self.sink.event(Event::Write(words));
+        let result = self.inner.write(words);
+        self.check(result)
+    }
+}
+
+impl<T: SpiBus<W>, S: Sink<W>, W: Word> SpiBus<W> for Trace<T, S, W> {
+    fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+        self.sink.event(Event::Write(write));
+        let result = self.inner.transfer(read, write);
+        self.check(result)?;
+        self.sink.event(Event::Read(read));
+        Ok(())
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        self.sink.event(Event::Write(words));
+        let result = self.inner.transfer_in_place(words);
+        self.check(result)?;
+        self.sink.event(Event::Read(words));
+        Ok(())
+    }
+}
+
+impl<T: SpiDevice<W>, S: Sink<W>, W: Word> SpiDevice<W> for Trace<T, S, W> {
+    type Error = T::Error;
+
+    fn transaction<'a>(&mut self, operations: &mut [Operation<'a, W>]) -> Result<(), Self::Error> {
+        self.sink.event(Event::Start);
+        for operation in operations.iter() {
+            match operation {
+                Operation::Write(words) | Operation::Transfer(_, words) => {
+                    self.sink.event(Event::Write(words))
+                }
+                Operation::TransferInPlace(words) => self.sink.event(Event::Write(words)),
+                Operation::DelayUs(us) => self.sink.event(Event::DelayUs(*us)),
+                Operation::Read(_) => {}
+            }
+        }
+        let result = self.inner.transaction(operations);
+        if result.is_ok() {
+            for operation in operations.iter() {
+                match operation {
+                    Operation::Read(words)
+                    | Operation::Transfer(words, _)
+                    | Operation::TransferInPlace(words) => self.sink.event(Event::Read(words)),
+                    Operation::Write(_) | Operation::DelayUs(_) => {}
+                }
+            }
+        }
+        let result = self.check(result);
+        self.sink.event(Event::End);
+        result
+    }
+}
+
+impl<T: SpiDeviceHalfDuplex<W>, S: Sink<W>, W: Word> SpiDeviceHalfDuplex<W> for Trace<T, S, W> {
+    type Error = T::Error;
+
+    fn write_then_read(&mut self, write: &[W], read: &mut [W]) -> Result<(), Self::Error> {
+        self.sink.event(Event::Start);
+        self.sink.event(Event::Write(write));
+        let result = self.inner.write_then_read(write, read);
+        if result.is_ok() {
+            self.sink.event(Event::Read(read));
+        }
+        let result = self.check(result);
+        self.sink.event(Event::End);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Device answering every read with the inverted written words
+    struct Invert;
+
+    impl SpiDevice for Invert {
+        type Error = ErrorKind;
+
+        fn transaction<'a>(
+            &mut self,
+            operations: &mut [Operation<'a, u8>],
+        ) -> Result<(), Self::Error> {
+            for operation in operations {
+                match operation {
+                    Operation::TransferInPlace(words) => {
+                        for word in words.iter_mut() {
+                            *word = !*word;
+                        }
+                    }
+                    Operation::Read(_) => return Err(ErrorKind::Overrun),
+                    _ => {}
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn reports_device_transactions() {
+        let mut events = [None; 8];
+        let mut n = 0;
+        let mut words = [0x0F, 0xF0];
+        {
+            let mut spi = Trace::new(Invert, |event: Event<u8>| {
+                events[n] = Some(match event {
+                    Event::Write(words) | Event::Read(words) => words[0],
+                    Event::Error(_) => 0xEE,
+                    Event::Start | Event::End => 0x55,
+                    _ => 0,
+                });
+                n += 1;
+            });
+            spi.transfer_in_place(&mut words).unwrap();
+            assert_eq!(spi.read(&mut [0]), Err(ErrorKind::Overrun));
+        }
+        assert_eq!(words, [0xF0, 0x0F]);
+        assert_eq!(
+            &events[..n],
+            &[
+                Some(0x55),
+                Some(0x0F),
+                Some(0xF0),
+                Some(0x55),
+                Some(0x55),
+                Some(0xEE),
+                Some(0x55),
+            ]
+        );
+    }
+}
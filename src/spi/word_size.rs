@@ -0,0 +1,341 @@
+//! Wider words on a byte-wide bus
+//!
+//! [`WordSize`] implements the bus traits for `u16` and `u32` words on top of a bus which
+//! only supports `u8` words, by sending every word as 2 or 4 bytes in the configured
+//! [`ByteOrder`]. The `u8` traits are forwarded as they are, so a single bus can serve both
+//! byte- and word-oriented drivers. Combined with a device implementation such as
+//! [`ExclusiveDevice`](super::exclusive::ExclusiveDevice), this provides `SpiDevice<u16>`
+//! and `SpiDevice<u32>`.
+//!
+//! Note that this only works for devices which don't care about the gaps between the bytes
+//! of a word, which some HALs insert.
+//!
+//! ## Example
+//!
+//! ```
+//! use embedded_hal::spi::blocking::{SpiBus, SpiBusWrite};
+//! use embedded_hal::spi::word_size::{ByteOrder, WordSize};
+//!
+//! /// Sends a command to a DAC with 16-bit, MSB-first frames
+//! fn set_output<BUS: SpiBus>(bus: BUS, value: u16) -> Result<(), BUS::Error> {
+//!     let mut bus = WordSize::new(bus, ByteOrder::BigEndian);
+//!     bus.write(&[0x3000 | (value & 0x0FFF)])
+//! }
+//! ```
+
+use super::blocking::{SpiBus, SpiBusFlush, SpiBusRead, SpiBusWrite};
+
+/// Order in which the bytes of a word are sent
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ByteOrder {
+    /// Most significant byte first
+    BigEndian,
+    /// Least significant byte first
+    LittleEndian,
+}
+
+/// Size of the chunks words are converted in
+const CHUNK: usize = 32;
+
+/// Bus sending `u16` and `u32` words as bytes
+#[derive(Debug)]
+pub struct WordSize<BUS> {
+    bus: BUS,
+    order: ByteOrder,
+}
+
+impl<BUS> WordSize<BUS> {
+    /// Creates a new bus sending the bytes of every word in `order`
+    pub fn new(bus: BUS, order: ByteOrder) -> Self {
+        WordSize { bus, order }
+    }
+
+    /// Releases the byte-wide bus
+    pub fn release(self) -> BUS {
+        self.bus
+    }
+}
+
+/// Conversion between words and bytes
+trait Bytes: Copy + Default {
+    /// Number of bytes of a word
+    const BYTES: usize;
+
+    fn encode(self, order: ByteOrder, bytes: &mut [u8]);
+
+    fn decode(order: ByteOrder, bytes: &[u8]) -> Self;
+}
+
+macro_rules! bytes {
+    ($($word:ty: $n:expr),+) => {
+        $(
+            impl Bytes for $word {
+                const BYTES: usize = $n;
+
+                fn encode(self, order: ByteOrder, bytes: &mut [u8]) {
+                    let encoded = match order {
+                        ByteOrder::BigEndian => self.to_be_bytes(),
+                        ByteOrder::LittleEndian => self.to_le_bytes(),
+                    };
+                    bytes.copy_from_slice(&encoded);
+                }
+
+                fn decode(order: ByteOrder, bytes: &[u8]) -> Self {
+                    let mut raw = [0; $n];
+                    raw.copy_from_slice(bytes);
+                    match order {
+                        ByteOrder::BigEndian => <$word>::from_be_bytes(raw),
+                        ByteOrder::LittleEndian => <$word>::from_le_bytes(raw),
+                    }
+                }
+            }
+        )+
+    };
+}
+
+bytes!(u16: 2, u32: 4);
+
+fn encode<W: Bytes>(order: ByteOrder, words: &[W], buffer: &mut [u8]) {
+    for (word, bytes) in words.iter().zip(buffer.chunks_mut(W::BYTES)) {
+        word.encode(order, bytes);
+    }
+}
+
+fn decode<W: Bytes>(order: ByteOrder, buffer: &[u8], words: &mut [W]) {
+    for (word, bytes) in words.iter_mut().zip(buffer.chunks(W::BYTES)) {
+        *word = W::decode(order, bytes);
+    }
+}
+
+impl<BUS> WordSize<BUS> {
+    fn read_words<W: Bytes>(&mut self, words: &mut [W]) -> Result<(), BUS::Error>
+    where
+        BUS: SpiBusRead,
+    {
+        let mut buffer = [0; CHUNK];
+        for chunk in words.chunks_mut(CHUNK / W::BYTES) {
+            let buffer = &mut buffer[..chunk.len() * W::BYTES];
+            self.bus.read(buffer)?;
+            decode(self.order, buffer, chunk);
+        }
+        Ok(())
+    }
+
+    fn write_words<W: Bytes>(&mut self, words: &[W]) -> Result<(), BUS::Error>
+    where
+        BUS: SpiBusWrite,
+    {
+        let mut buffer = [0; CHUNK];
+        for chunk in words.chunks(CHUNK / W::BYTES) {
+            let buffer = &mut buffer[..chunk.len() * W::BYTES];
+            encode(self.order, chunk, buffer);
+            self.bus.write(buffer)?;
+        }
+        Ok(())
+    }
+
+    fn transfer_words<W: Bytes>(&mut self, read: &mut [W], write: &[W]) -> Result<(), BUS::Error>
+    where
+        BUS: SpiBus,
+    {
+        let mut buffer = [0; CHUNK];
+        let len = read.len().max(write.len());
+        let mut start = 0;
+        while start < len {
+            let end = len.min(start + CHUNK / W::BYTES);
+            let buffer = &mut buffer[..(end - start) * W::BYTES];
+            let written = &write[start.min(write.len())..end.min(write.len())];
+            for (i, bytes) in buffer.chunks_mut(W::BYTES).enumerate() {
+                written
+                    .get(i)
+                    .copied()
+                    .unwrap_or_default()
+                    .encode(self.order, bytes);
+            }
+            self.bus.transfer_in_place(buffer)?;
+            let read_len = read.len();
+            decode(
+                self.order,
+                buffer,
+                &mut read[start.min(read_len)..end.min(read_len)],
+            );
+            start = end;
+        }
+        Ok(())
+    }
+
+    fn transfer_words_in_place<W: Bytes>(&mut self, words: &mut [W]) -> Result<(), BUS::Error>
+    where
+        BUS: SpiBus,
+    {
+        let mut buffer = [0; CHUNK];
+        for chunk in words.chunks_mut(CHUNK / W::BYTES) {
+            let buffer = &mut buffer[..chunk.len() * W::BYTES];
+            encode(self.order, chunk, buffer);
+            self.bus.transfer_in_place(buffer)?;
+            decode(self.order, buffer, chunk);
+        }
+        Ok(())
+    }
+}
+
+impl<BUS: SpiBusFlush> SpiBusFlush for WordSize<BUS> {
+    type Error = BUS::Error;
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.bus.flush()
+    }
+}
+
+impl<BUS: SpiBusRead> SpiBusRead for WordSize<BUS> {
+    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus.read(words)
+    }
+}
+
+impl<BUS: SpiBusWrite> SpiBusWrite for WordSize<BUS> {
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.bus.write(words)
+    }
+}
+
+impl<BUS: SpiBus> SpiBus for WordSize<BUS> {
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        self.bus.transfer(read, write)
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus.transfer_in_place(words)
+    }
+}
+
+macro_rules! word_size {
+    ($($word:ty),+) => {
+        $(
+            impl<BUS: SpiBusRead> SpiBusRead<$word> for WordSize<BUS> {
+                fn read(&mut self, words: &mut [$word]) -> Result<(), Self::Error> {
+                    self.read_words(words)
+                }
+            }
+
+            impl<BUS: SpiBusWrite> SpiBusWrite<$word> for WordSize<BUS> {
+                fn write(&mut self, words: &[$word]) -> Result<(), Self::Error> {
+                    self.write_words(words)
+                }
+            }
+
+            impl<BUS: SpiBus> SpiBus<$word> for WordSize<BUS> {
+                /// Words sent after `write` has been exhausted are zero
+                fn transfer(&mut self, read: &mut [$word], write: &[$word]) -> Result<(), Self::Error> {
+                    self.transfer_words(read, write)
+                }
+
+                fn transfer_in_place(&mut self, words: &mut [$word]) -> Result<(), Self::Error> {
+                    self.transfer_words_in_place(words)
+                }
+            }
+        )+
+    };
+}
+
+word_size!(u16, u32);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spi::ErrorKind;
+
+    /// Bus recording written bytes and answering with the byte index
+    struct Bus {
+        written: [u8; 64],
+        len: usize,
+    }
+
+    impl Bus {
+        fn new() -> Self {
+            Bus {
+                written: [0; 64],
+                len: 0,
+            }
+        }
+    }
+
+    impl SpiBusFlush for Bus {
+        type Error = ErrorKind;
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    impl SpiBusRead for Bus {
+        fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            for word in words {
+                *word = self.len as u8;
+                self.len += 1;
+            }
+            Ok(())
+        }
+    }
+
+    impl SpiBusWrite for Bus {
+        fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+            self.written[self.len..self.len + words.len()].copy_from_slice(words);
+            self.len += words.len();
+            Ok(())
+        }
+    }
+
+    impl SpiBus for Bus {
+        fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+            for i in 0..read.len().max(write.len()) {
+                self.written[self.len] = write.get(i).copied().unwrap_or(0);
+                if let Some(word) = read.get_mut(i) {
+                    *word = self.len as u8;
+                }
+                self.len += 1;
+            }
+            Ok(())
+        }
+
+        fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            for word in words {
+                self.written[self.len] = *word;
+                *word = self.len as u8;
+                self.len += 1;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn byte_order() {
+        let mut bus = WordSize::new(Bus::new(), ByteOrder::BigEndian);
+        bus.write(&[0x1234u16]).unwrap();
+        let mut bus = WordSize::new(bus.release(), ByteOrder::LittleEndian);
+        bus.write(&[0x1234_5678u32]).unwrap();
+        let mut words = [0u16; 1];
+        bus.read(&mut words).unwrap();
+        assert_eq!(words, [0x0706]);
+        assert_eq!(
+            &bus.release().written[..6],
+            &[0x12, 0x34, 0x78, 0x56, 0x34, 0x12]
+        );
+    }
+
+    #[test]
+    fn transfers_in_chunks() {
+        let mut bus = WordSize::new(Bus::new(), ByteOrder::BigEndian);
+        let write = [0xAAAAu16; 20];
+        let mut read = [0u16; 18];
+        bus.transfer(&mut read, &write).unwrap();
+        assert_eq!(read[17], 0x2223);
+        let mut words = [0xBBBBu16; 2];
+        bus.transfer_in_place(&mut words).unwrap();
+        assert_eq!(words, [0x2829, 0x2A2B]);
+        let bus = bus.release();
+        assert_eq!(bus.len, 44);
+        assert!(bus.written[..40].iter().all(|&b| b == 0xAA));
+        assert!(bus.written[40..44].iter().all(|&b| b == 0xBB));
+    }
+}